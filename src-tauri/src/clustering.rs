@@ -0,0 +1,176 @@
+use crate::rng::Rng;
+
+/// Optional settings of `cluster_embeddings`.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClusterOptions {
+    /// Number of clusters, 8 by default.
+    pub(crate) k: Option<usize>,
+    /// Most rounds of reassignment, 50 by default.
+    pub(crate) iterations: Option<usize>,
+    /// Seed of the k-means++ initialisation, so a run can be repeated.
+    pub(crate) seed: Option<u64>,
+    /// Records clustered, 5000 by default, spread evenly over the matches.
+    pub(crate) sample_size: Option<u32>,
+    /// Records listed per cluster, nearest to its centroid first. 5 by default.
+    pub(crate) nearest: Option<usize>,
+}
+
+/// Outcome of `kmeans`: one centroid per cluster and the cluster of each vector.
+pub(crate) struct KMeans {
+    pub(crate) centroids: Vec<Vec<f32>>,
+    pub(crate) assignments: Vec<usize>,
+    pub(crate) iterations: usize,
+    pub(crate) converged: bool,
+    pub(crate) inertia: f64,
+}
+
+/// Lloyd's k-means under Euclidean distance, seeded with k-means++. Stops when
+/// a round moves no vector or after `iterations` rounds. A cluster left empty
+/// takes over the vector furthest from its own centroid, if any is off one.
+///
+/// `vectors` must share one dimension and hold at least `k` entries.
+pub(crate) fn kmeans(vectors: &[Vec<f32>], k: usize, iterations: usize, seed: u64) -> KMeans {
+    let mut rng = Rng::new(seed);
+    let mut centroids = initial_centroids(vectors, k, &mut rng);
+    let mut assignments = vec![usize::MAX; vectors.len()];
+    let mut rounds = 0;
+    let mut converged = false;
+
+    while rounds < iterations {
+        rounds += 1;
+        let mut moved = false;
+        for (vector, assignment) in vectors.iter().zip(assignments.iter_mut()) {
+            let (nearest, _) = nearest_centroid(&centroids, vector).unwrap_or((0, 0.0));
+            if nearest != *assignment {
+                *assignment = nearest;
+                moved = true;
+            }
+        }
+        if !moved {
+            converged = true;
+            break;
+        }
+
+        let width = vectors.first().map_or(0, Vec::len);
+        let mut sums = vec![vec![0.0f64; width]; k];
+        let mut counts = vec![0usize; k];
+        for (vector, &assignment) in vectors.iter().zip(&assignments) {
+            if let (Some(sum), Some(count)) = (sums.get_mut(assignment), counts.get_mut(assignment))
+            {
+                for (s, &c) in sum.iter_mut().zip(vector) {
+                    *s += f64::from(c);
+                }
+                *count += 1;
+            }
+        }
+        for (cluster, (sum, count)) in sums.into_iter().zip(counts).enumerate() {
+            let centroid = if count > 0 {
+                Some(sum.into_iter().map(|s| (s / count as f64) as f32).collect())
+            } else {
+                // With every vector on its centroid there is nothing to take
+                // over, so the empty cluster keeps its place.
+                furthest_vector(vectors, &assignments, &centroids)
+                    .filter(|(_, distance)| *distance > 0.0)
+                    .and_then(|(i, _)| {
+                        if let Some(assignment) = assignments.get_mut(i) {
+                            *assignment = cluster;
+                        }
+                        vectors.get(i).cloned()
+                    })
+            };
+            if let (Some(centroid), Some(slot)) = (centroid, centroids.get_mut(cluster)) {
+                *slot = centroid;
+            }
+        }
+    }
+
+    let inertia = vectors
+        .iter()
+        .zip(&assignments)
+        .filter_map(|(vector, &assignment)| {
+            centroids
+                .get(assignment)
+                .map(|centroid| squared_distance(vector, centroid))
+        })
+        .sum();
+    KMeans {
+        centroids,
+        assignments,
+        iterations: rounds,
+        converged,
+        inertia,
+    }
+}
+
+/// The index of the centroid closest to `vector` and the Euclidean distance
+/// to it, or `None` without centroids.
+pub(crate) fn nearest_centroid(centroids: &[Vec<f32>], vector: &[f32]) -> Option<(usize, f32)> {
+    centroids
+        .iter()
+        .map(|centroid| squared_distance(vector, centroid))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, distance)| (i, distance.sqrt() as f32))
+}
+
+/// k-means++: each centroid after a random first one is drawn with probability
+/// proportional to the squared distance to the nearest already chosen.
+fn initial_centroids(vectors: &[Vec<f32>], k: usize, rng: &mut Rng) -> Vec<Vec<f32>> {
+    let mut centroids: Vec<Vec<f32>> = Vec::with_capacity(k);
+    let mut distances = vec![f64::INFINITY; vectors.len()];
+    let mut next = (rng.next_u64() % vectors.len().max(1) as u64) as usize;
+    while centroids.len() < k {
+        let Some(chosen) = vectors.get(next) else {
+            break;
+        };
+        centroids.push(chosen.clone());
+        for (distance, vector) in distances.iter_mut().zip(vectors) {
+            *distance = distance.min(squared_distance(vector, chosen));
+        }
+
+        let total: f64 = distances.iter().sum();
+        next = if total > 0.0 {
+            let mut target = rng.next_f64() * total;
+            distances
+                .iter()
+                .position(|&distance| {
+                    target -= distance;
+                    target < 0.0
+                })
+                .unwrap_or(vectors.len() - 1)
+        } else {
+            // Every vector sits on a centroid already; repeat one at random.
+            (rng.next_u64() % vectors.len() as u64) as usize
+        };
+    }
+    centroids
+}
+
+fn furthest_vector(
+    vectors: &[Vec<f32>],
+    assignments: &[usize],
+    centroids: &[Vec<f32>],
+) -> Option<(usize, f64)> {
+    vectors
+        .iter()
+        .zip(assignments)
+        .enumerate()
+        .filter_map(|(i, (vector, &assignment))| {
+            centroids
+                .get(assignment)
+                .map(|centroid| (i, squared_distance(vector, centroid)))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+pub(crate) fn distance(a: &[f32], b: &[f32]) -> f32 {
+    squared_distance(a, b).sqrt() as f32
+}
+
+fn squared_distance(a: &[f32], b: &[f32]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(&x, &y)| (f64::from(x) - f64::from(y)).powi(2))
+        .sum()
+}
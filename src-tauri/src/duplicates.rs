@@ -0,0 +1,211 @@
+use crate::structs::{DuplicateGroup, DuplicateReport};
+use chroma::types::Include;
+use std::collections::HashMap;
+
+/// Cosine distance under which two vectors are near-duplicates by default.
+const DEFAULT_NEAR_DISTANCE: f32 = 0.02;
+
+/// Records compared pairwise for near-duplicates before the scan is refused.
+const MAX_NEAR_RECORDS: usize = 10_000;
+
+/// What makes two records duplicates in `find_duplicates`.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum DuplicateMode {
+    /// Identical document text.
+    Document,
+    /// Identical vectors.
+    Vector,
+    /// Vectors within `threshold` cosine distance, 0.02 by default. Records
+    /// are grouped transitively, so a group may span more than the threshold.
+    Near { threshold: Option<f32> },
+}
+
+impl DuplicateMode {
+    pub(crate) fn include(self) -> Include {
+        match self {
+            DuplicateMode::Document => Include::Document,
+            DuplicateMode::Vector | DuplicateMode::Near { .. } => Include::Embedding,
+        }
+    }
+}
+
+/// What exact modes group records by. The content itself is the key, so
+/// records are only grouped when they are equal, never on a hash collision.
+#[derive(PartialEq, Eq, Hash)]
+enum ExactKey {
+    Document(String),
+    /// Component bits, with -0.0 folded into 0.0.
+    Vector(Vec<u32>),
+}
+
+/// Collects records page by page for `find_duplicates`. Exact modes keep each
+/// distinct document or vector once; near-duplicates keep every normalised
+/// vector.
+pub(crate) struct DuplicateFinder {
+    mode: DuplicateMode,
+    ids: Vec<String>,
+    skipped: usize,
+    exact: HashMap<ExactKey, Vec<usize>>,
+    vectors: Vec<Vec<f32>>,
+}
+
+impl DuplicateFinder {
+    pub(crate) fn new(mode: DuplicateMode) -> Result<Self, String> {
+        if let DuplicateMode::Near {
+            threshold: Some(threshold),
+        } = mode
+        {
+            if !(0.0..=2.0).contains(&threshold) {
+                return Err(format!(
+                    "Threshold must be a cosine distance between 0 and 2, got {}",
+                    threshold
+                ));
+            }
+        }
+
+        Ok(Self {
+            mode,
+            ids: Vec::new(),
+            skipped: 0,
+            exact: HashMap::new(),
+            vectors: Vec::new(),
+        })
+    }
+
+    /// Takes one record; `document` is read in document mode and `embedding`
+    /// in the vector modes. Fails once there are too many near-duplicate
+    /// candidates to compare.
+    pub(crate) fn add(
+        &mut self,
+        id: &str,
+        document: Option<&str>,
+        embedding: Option<&[f32]>,
+    ) -> Result<(), String> {
+        let key = match self.mode {
+            DuplicateMode::Document => match document {
+                Some(document) => ExactKey::Document(document.to_string()),
+                None => {
+                    self.skipped += 1;
+                    return Ok(());
+                }
+            },
+            DuplicateMode::Vector => match embedding {
+                // -0.0 and 0.0 are the same component.
+                Some(embedding) => {
+                    ExactKey::Vector(embedding.iter().map(|c| (c + 0.0).to_bits()).collect())
+                }
+                None => {
+                    self.skipped += 1;
+                    return Ok(());
+                }
+            },
+            DuplicateMode::Near { .. } => {
+                let Some(vector) = embedding.and_then(normalized) else {
+                    self.skipped += 1;
+                    return Ok(());
+                };
+                if self.vectors.len() >= MAX_NEAR_RECORDS {
+                    return Err(format!(
+                        "Near-duplicate search is limited to {} records, narrow the filter",
+                        MAX_NEAR_RECORDS
+                    ));
+                }
+                self.vectors.push(vector);
+                self.ids.push(id.to_string());
+                return Ok(());
+            }
+        };
+
+        self.exact.entry(key).or_default().push(self.ids.len());
+        self.ids.push(id.to_string());
+        Ok(())
+    }
+
+    pub(crate) fn finish(self, scanned: usize) -> DuplicateReport {
+        let groups: Vec<Vec<usize>> = match self.mode {
+            DuplicateMode::Document | DuplicateMode::Vector => self.exact.into_values().collect(),
+            DuplicateMode::Near { threshold } => {
+                near_groups(&self.vectors, threshold.unwrap_or(DEFAULT_NEAR_DISTANCE))
+            }
+        };
+
+        let mut groups: Vec<DuplicateGroup> = groups
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .filter_map(|mut group| {
+                group.sort_unstable();
+                let mut ids = group
+                    .into_iter()
+                    .filter_map(|index| self.ids.get(index).cloned());
+                Some(DuplicateGroup {
+                    keep: ids.next()?,
+                    duplicates: ids.collect(),
+                })
+            })
+            .collect();
+        groups.sort_by(|a, b| {
+            b.duplicates
+                .len()
+                .cmp(&a.duplicates.len())
+                .then_with(|| a.keep.cmp(&b.keep))
+        });
+
+        DuplicateReport {
+            scanned,
+            skipped: self.skipped,
+            delete_ids: groups
+                .iter()
+                .flat_map(|group| group.duplicates.iter().cloned())
+                .collect(),
+            groups,
+        }
+    }
+}
+
+fn normalized(embedding: &[f32]) -> Option<Vec<f32>> {
+    let norm = embedding.iter().map(|c| c * c).sum::<f32>().sqrt();
+    (norm > 0.0 && norm.is_finite()).then(|| embedding.iter().map(|c| c / norm).collect())
+}
+
+/// Connected components of the graph linking vectors within `threshold`
+/// cosine distance. Vectors must be normalised.
+fn near_groups(vectors: &[Vec<f32>], threshold: f32) -> Vec<Vec<usize>> {
+    let mut parents: Vec<usize> = (0..vectors.len()).collect();
+    for (i, a) in vectors.iter().enumerate() {
+        for (j, b) in vectors.iter().enumerate().skip(i + 1) {
+            if a.len() != b.len() {
+                continue;
+            }
+            let similarity: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+            if 1.0 - similarity <= threshold {
+                let (root_a, root_b) = (find_root(&mut parents, i), find_root(&mut parents, j));
+                if let Some(parent) = parents.get_mut(root_a.max(root_b)) {
+                    *parent = root_a.min(root_b);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..vectors.len() {
+        let root = find_root(&mut parents, i);
+        groups.entry(root).or_default().push(i);
+    }
+    groups.into_values().collect()
+}
+
+fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while let Some(&parent) = parents.get(i) {
+        if parent == i {
+            break;
+        }
+        // Point at the grandparent on the way up to keep the chains short.
+        let grandparent = parents.get(parent).copied().unwrap_or(parent);
+        if let Some(slot) = parents.get_mut(i) {
+            *slot = grandparent;
+        }
+        i = parent;
+    }
+    i
+}
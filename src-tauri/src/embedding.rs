@@ -0,0 +1,166 @@
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// How text is turned into vectors for a collection. Chroma stores whatever
+/// vectors it is given, so ChromaMind has to call the same model the
+/// collection was built with before it can query or re-embed by text.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub(crate) enum EmbeddingProvider {
+    /// Any server implementing OpenAI's `POST /embeddings`.
+    #[serde(rename = "openai")]
+    OpenAi {
+        url: String,
+        model: String,
+        /// Never sent back to the frontend once configured.
+        #[serde(rename = "apiKey", default, skip_serializing)]
+        api_key: Option<String>,
+        dimensions: Option<u32>,
+    },
+    /// An Ollama server's `POST /api/embed`.
+    Ollama { url: String, model: String },
+    /// Feature hashing of the text's words. Deterministic and offline, so it
+    /// suits tests and demos but has no semantic meaning.
+    Hash { dimension: usize },
+}
+
+impl EmbeddingProvider {
+    /// Embeds `texts`, returning one vector per input in the same order.
+    pub(crate) async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let embeddings = match self {
+            EmbeddingProvider::OpenAi {
+                url,
+                model,
+                api_key,
+                dimensions,
+            } => {
+                let mut body = json!({ "model": model, "input": texts });
+                if let (Some(dimensions), Some(object)) = (dimensions, body.as_object_mut()) {
+                    object.insert("dimensions".to_string(), json!(dimensions));
+                }
+                let response = post_json(url, "embeddings", api_key.as_deref(), &body).await?;
+                parse_openai_response(response)?
+            }
+            EmbeddingProvider::Ollama { url, model } => {
+                let body = json!({ "model": model, "input": texts });
+                let response = post_json(url, "api/embed", None, &body).await?;
+                serde_json::from_value::<OllamaResponse>(response)
+                    .map_err(|e| format!("Unexpected embedding response: {}", e))?
+                    .embeddings
+            }
+            EmbeddingProvider::Hash { dimension } => {
+                texts.iter().map(|t| hash_embed(t, *dimension)).collect()
+            }
+        };
+
+        if embeddings.len() != texts.len() {
+            return Err(format!(
+                "Embedding provider returned {} vector(s) for {} input(s)",
+                embeddings.len(),
+                texts.len()
+            ));
+        }
+
+        Ok(embeddings)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct OllamaResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(serde::Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(serde::Deserialize)]
+struct OpenAiEmbedding {
+    embedding: Vec<f32>,
+    index: usize,
+}
+
+/// OpenAI documents `data` as ordered by input, but each item carries its
+/// `index`, so sort on it rather than trust the server.
+fn parse_openai_response(response: Value) -> Result<Vec<Vec<f32>>, String> {
+    let mut data = serde_json::from_value::<OpenAiResponse>(response)
+        .map_err(|e| format!("Unexpected embedding response: {}", e))?
+        .data;
+    data.sort_by_key(|d| d.index);
+
+    Ok(data.into_iter().map(|d| d.embedding).collect())
+}
+
+async fn post_json(
+    base_url: &str,
+    path: &str,
+    bearer_token: Option<&str>,
+    body: &Value,
+) -> Result<Value, String> {
+    let mut base_url = base_url
+        .parse::<reqwest::Url>()
+        .map_err(|e| format!("Invalid embedding provider url: {}", e))?;
+    if !base_url.path().ends_with('/') {
+        if let Ok(mut segments) = base_url.path_segments_mut() {
+            segments.push("");
+        }
+    }
+    let url = base_url
+        .join(path)
+        .map_err(|e| format!("Invalid embedding provider url: {}", e))?;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .map_err(|e| format!("{e}"))?;
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string());
+    if let Some(token) = bearer_token {
+        request = request.bearer_auth(token);
+    }
+
+    let bytes = request
+        .send()
+        .await
+        .map_err(|e| format!("Embedding request failed: {e}"))?
+        .error_for_status()
+        .map_err(|e| format!("Embedding provider error: {e}"))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Embedding response failed: {e}"))?;
+
+    serde_json::from_slice(&bytes).map_err(|e| format!("Unexpected embedding response: {}", e))
+}
+
+/// Signed feature hashing over lowercase alphanumeric words, L2-normalised.
+/// Uses FNV-1a rather than `DefaultHasher` so vectors stay identical across
+/// Rust versions.
+fn hash_embed(text: &str, dimension: usize) -> Vec<f32> {
+    let mut vector = vec![0.0_f32; dimension];
+    if dimension == 0 {
+        return vector;
+    }
+
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let hash = word
+            .to_lowercase()
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        if let Some(slot) = vector.get_mut((hash % dimension as u64) as usize) {
+            *slot += sign;
+        }
+    }
+
+    // An empty text stays the zero vector rather than failing.
+    let _ = crate::vector::normalize(&mut vector);
+    vector
+}
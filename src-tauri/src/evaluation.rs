@@ -0,0 +1,110 @@
+use crate::structs::QueryEvaluation;
+use std::collections::HashSet;
+
+/// A labelled query set, as a path to a JSONL file or its content.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "format", content = "value", rename_all = "lowercase")]
+pub(crate) enum GoldenSetInput {
+    Path(String),
+    Jsonl(String),
+}
+
+/// One line of a golden set, e.g.
+/// `{"id": "q1", "query": "How do I reset?", "expected_ids": ["doc7"]}`.
+/// With `embedding` the query is not embedded, so `query` may be left out.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct GoldenCase {
+    pub(crate) id: Option<String>,
+    #[serde(alias = "question")]
+    pub(crate) query: Option<String>,
+    #[serde(alias = "expected")]
+    pub(crate) expected_ids: Vec<String>,
+    pub(crate) embedding: Option<Vec<f32>>,
+}
+
+impl GoldenSetInput {
+    /// Reads the set, skipping blank lines. Cases without an id are named
+    /// after their line number. A file is read on the blocking thread pool.
+    pub(crate) async fn parse(self) -> Result<Vec<GoldenCase>, String> {
+        let text = match self {
+            GoldenSetInput::Path(path) => {
+                let read_path = path.clone();
+                tauri::async_runtime::spawn_blocking(move || std::fs::read_to_string(read_path))
+                    .await
+                    .map_err(|e| format!("Error reading {}: {}", path, e))?
+                    .map_err(|e| format!("Error reading {}: {}", path, e))?
+            }
+            GoldenSetInput::Jsonl(text) => text,
+        };
+
+        let mut cases = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut case: GoldenCase = serde_json::from_str(line)
+                .map_err(|e| format!("Invalid golden set line {}: {}", line_number, e))?;
+            if case.query.is_none() && case.embedding.is_none() {
+                return Err(format!(
+                    "Golden set line {} has neither a query nor an embedding",
+                    line_number
+                ));
+            }
+            if case.expected_ids.is_empty() {
+                return Err(format!(
+                    "Golden set line {} has no expected ids",
+                    line_number
+                ));
+            }
+            case.id
+                .get_or_insert_with(|| format!("line {}", line_number));
+            cases.push(case);
+        }
+
+        if cases.is_empty() {
+            return Err("Golden set has no queries".to_string());
+        }
+        Ok(cases)
+    }
+}
+
+/// Scores the ids retrieved for `case` against its expected ids, with binary
+/// relevance for nDCG.
+pub(crate) fn score(case: GoldenCase, retrieved: Vec<String>, k: usize) -> QueryEvaluation {
+    let expected: HashSet<&str> = case.expected_ids.iter().map(String::as_str).collect();
+    let retrieved: Vec<String> = retrieved.into_iter().take(k).collect();
+    let relevant: Vec<bool> = retrieved
+        .iter()
+        .map(|id| expected.contains(id.as_str()))
+        .collect();
+
+    let rank = relevant.iter().position(|&hit| hit).map(|i| i + 1);
+    let gain = |position: usize| 1.0 / ((position + 2) as f64).log2();
+    let dcg: f64 = relevant
+        .iter()
+        .enumerate()
+        .filter(|(_, &hit)| hit)
+        .map(|(i, _)| gain(i))
+        .sum();
+    let ideal: f64 = (0..expected.len().min(k)).map(gain).sum();
+    let found = relevant.iter().filter(|&&hit| hit).count();
+
+    QueryEvaluation {
+        missed: case
+            .expected_ids
+            .iter()
+            .filter(|id| !retrieved.contains(id))
+            .cloned()
+            .collect(),
+        id: case.id.unwrap_or_default(),
+        query: case.query,
+        rank,
+        reciprocal_rank: rank.map_or(0.0, |rank| 1.0 / rank as f64),
+        ndcg: if ideal > 0.0 { dcg / ideal } else { 0.0 },
+        recall: found as f64 / expected.len().max(1) as f64,
+        expected: case.expected_ids,
+        retrieved,
+    }
+}
@@ -0,0 +1,107 @@
+use crate::histogram::numeric_bins;
+use crate::structs::{EmbeddingHealth, EmbeddingIssue, NormStats};
+use std::collections::HashMap;
+
+/// Ids listed per issue; beyond this only the count grows.
+const MAX_REPORTED_IDS: usize = 1000;
+
+/// How far a norm may be from 1 for its vector to count as normalised.
+const NORM_TOLERANCE: f64 = 1e-3;
+
+/// Checks vectors page by page for `scan_embeddings`. Ids are grouped by
+/// dimension, since the expected one is only known once every vector is seen.
+#[derive(Default)]
+pub(crate) struct EmbeddingScanner {
+    scanned: usize,
+    dimensions: HashMap<usize, EmbeddingIssue>,
+    non_finite: EmbeddingIssue,
+    zero: EmbeddingIssue,
+    norms: Vec<f64>,
+}
+
+impl EmbeddingScanner {
+    pub(crate) fn add(&mut self, id: &str, embedding: &[f32]) {
+        self.scanned += 1;
+        self.dimensions.entry(embedding.len()).or_default().push(id);
+
+        if embedding.iter().any(|c| !c.is_finite()) {
+            self.non_finite.push(id);
+            return;
+        }
+        let norm = embedding
+            .iter()
+            .map(|&c| f64::from(c) * f64::from(c))
+            .sum::<f64>()
+            .sqrt();
+        if norm == 0.0 {
+            self.zero.push(id);
+        } else {
+            self.norms.push(norm);
+        }
+    }
+
+    pub(crate) fn finish(self, bins: usize) -> EmbeddingHealth {
+        let dimension = self
+            .dimensions
+            .iter()
+            .max_by(|a, b| a.1.count.cmp(&b.1.count).then_with(|| b.0.cmp(a.0)))
+            .map(|(dimension, _)| *dimension);
+        let mut dimension_mismatches = EmbeddingIssue::default();
+        let mut others: Vec<(usize, EmbeddingIssue)> = self
+            .dimensions
+            .into_iter()
+            .filter(|(d, _)| Some(*d) != dimension)
+            .collect();
+        others.sort_by_key(|(d, _)| *d);
+        for (_, issue) in others {
+            dimension_mismatches.count += issue.count;
+            for id in issue.ids {
+                dimension_mismatches.push_id(id);
+            }
+        }
+
+        let normalized = self
+            .norms
+            .iter()
+            .filter(|norm| (*norm - 1.0).abs() <= NORM_TOLERANCE)
+            .count();
+        let norms = self
+            .norms
+            .iter()
+            .copied()
+            .reduce(f64::min)
+            .zip(self.norms.iter().copied().reduce(f64::max))
+            .map(|(min, max)| NormStats {
+                min,
+                mean: self.norms.iter().sum::<f64>() / self.norms.len() as f64,
+                max,
+                bins: numeric_bins(&self.norms, min, max, bins),
+            });
+
+        EmbeddingHealth {
+            scanned: self.scanned,
+            dimension,
+            dimension_mismatches,
+            non_finite: self.non_finite,
+            zero: self.zero,
+            looks_normalized: norms.is_some() && normalized == self.norms.len(),
+            norms,
+            normalized,
+        }
+    }
+}
+
+impl EmbeddingIssue {
+    fn push(&mut self, id: &str) {
+        self.count += 1;
+        if self.ids.len() < MAX_REPORTED_IDS {
+            self.ids.push(id.to_string());
+        }
+    }
+
+    fn push_id(&mut self, id: String) {
+        if self.ids.len() < MAX_REPORTED_IDS {
+            self.ids.push(id);
+        }
+    }
+}
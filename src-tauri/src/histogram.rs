@@ -0,0 +1,368 @@
+use crate::structs::{DateUnit, Histogram, HistogramBin, HistogramKind};
+use chroma::types::{Metadata, MetadataValue};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime};
+use std::ops::RangeInclusive;
+
+/// Bins of a numeric histogram when no count is given, and the most a date
+/// histogram picks on its own.
+const DEFAULT_BINS: usize = 20;
+
+/// Upper bound on the bins of any histogram.
+pub(crate) const MAX_BINS: usize = 1000;
+
+/// Numbers in this range (2000-01-01 to 2100-01-01) are taken for epoch
+/// seconds when the kind of a key is detected.
+const EPOCH_SECONDS: RangeInclusive<f64> = 946_684_800.0..=4_102_444_800.0;
+
+/// Optional settings of `metadata_histogram`. `min` and `max` are epoch
+/// seconds for a date histogram.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HistogramOptions {
+    pub(crate) bins: Option<usize>,
+    pub(crate) min: Option<f64>,
+    pub(crate) max: Option<f64>,
+    /// Detected from the values when not given.
+    pub(crate) kind: Option<HistogramKind>,
+    /// For a date histogram, the finest unit that fits in `bins` when not
+    /// given.
+    pub(crate) unit: Option<DateUnit>,
+}
+
+/// Collects the values of one key over metadata pages for `metadata_histogram`.
+pub(crate) struct Histogrammer {
+    key: String,
+    options: HistogramOptions,
+    numbers: Vec<f64>,
+    dates: Vec<f64>,
+    scanned: usize,
+    missing: usize,
+    invalid: usize,
+}
+
+impl Histogrammer {
+    pub(crate) fn new(key: &str, options: HistogramOptions) -> Result<Self, String> {
+        if options
+            .bins
+            .is_some_and(|bins| bins == 0 || bins > MAX_BINS)
+        {
+            return Err(format!("Bin count must be between 1 and {}", MAX_BINS));
+        }
+        if let (Some(min), Some(max)) = (options.min, options.max) {
+            if min > max {
+                return Err(format!(
+                    "Histogram min {} is greater than its max {}",
+                    min, max
+                ));
+            }
+        }
+
+        Ok(Self {
+            key: key.to_string(),
+            options,
+            numbers: Vec::new(),
+            dates: Vec::new(),
+            scanned: 0,
+            missing: 0,
+            invalid: 0,
+        })
+    }
+
+    pub(crate) fn add(&mut self, metadata: Option<&Metadata>) {
+        self.scanned += 1;
+        match metadata.and_then(|m| m.get(&self.key)) {
+            Some(MetadataValue::Int(i)) => self.numbers.push(*i as f64),
+            Some(MetadataValue::Float(f)) if f.is_finite() => self.numbers.push(*f),
+            Some(MetadataValue::Str(s)) => match parse_date(s) {
+                Some(seconds) => self.dates.push(seconds),
+                None => self.invalid += 1,
+            },
+            Some(
+                MetadataValue::Float(_)
+                | MetadataValue::Bool(_)
+                | MetadataValue::SparseVector(_)
+                | MetadataValue::BoolArray(_)
+                | MetadataValue::IntArray(_)
+                | MetadataValue::FloatArray(_)
+                | MetadataValue::StringArray(_),
+            ) => self.invalid += 1,
+            None => self.missing += 1,
+        }
+    }
+
+    pub(crate) fn finish(self) -> Result<Histogram, String> {
+        let kind = self.options.kind.unwrap_or_else(|| self.detect_kind());
+        let mut invalid = self.invalid;
+        let values = match kind {
+            HistogramKind::Numeric => {
+                invalid += self.dates.len();
+                self.numbers
+            }
+            HistogramKind::Date => {
+                let mut values = self.dates;
+                for number in self.numbers {
+                    if DateTime::from_timestamp(number.floor() as i64, 0).is_some() {
+                        values.push(number);
+                    } else {
+                        invalid += 1;
+                    }
+                }
+                values
+            }
+        };
+
+        let min = self
+            .options
+            .min
+            .or_else(|| values.iter().copied().reduce(f64::min));
+        let max = self
+            .options
+            .max
+            .or_else(|| values.iter().copied().reduce(f64::max));
+        let in_range: Vec<f64> = match (min, max) {
+            (Some(min), Some(max)) => values
+                .iter()
+                .copied()
+                .filter(|v| (min..=max).contains(v))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let outside = values.len() - in_range.len();
+
+        let (unit, bins) = match (min, max) {
+            (Some(min), Some(max)) if min <= max => match kind {
+                HistogramKind::Numeric => (
+                    None,
+                    numeric_bins(
+                        &in_range,
+                        min,
+                        max,
+                        self.options.bins.unwrap_or(DEFAULT_BINS),
+                    ),
+                ),
+                HistogramKind::Date => {
+                    let (unit, bins) = date_bins(
+                        &in_range,
+                        min,
+                        max,
+                        self.options.bins.unwrap_or(DEFAULT_BINS),
+                        self.options.unit,
+                    )?;
+                    (Some(unit), bins)
+                }
+            },
+            // A single bound past every value leaves an empty range: `new`
+            // already refused inverted bounds given together, so every value
+            // is simply counted as outside.
+            _ => (None, Vec::new()),
+        };
+
+        Ok(Histogram {
+            key: self.key,
+            kind,
+            unit,
+            scanned: self.scanned,
+            missing: self.missing,
+            invalid,
+            outside,
+            min,
+            max,
+            bins,
+        })
+    }
+
+    /// Dates when most values are date strings, or every number looks like
+    /// epoch seconds.
+    fn detect_kind(&self) -> HistogramKind {
+        if self.dates.len() > self.numbers.len()
+            || (!self.numbers.is_empty() && self.numbers.iter().all(|n| EPOCH_SECONDS.contains(n)))
+        {
+            HistogramKind::Date
+        } else {
+            HistogramKind::Numeric
+        }
+    }
+}
+
+/// Splits `min..=max` into `bins` equal bins. Integer values spanning fewer
+/// integers than `bins` get one bin per integer instead.
+pub(crate) fn numeric_bins(values: &[f64], min: f64, max: f64, bins: usize) -> Vec<HistogramBin> {
+    let per_integer = max - min < bins as f64
+        && min.fract() == 0.0
+        && max.fract() == 0.0
+        && values.iter().all(|v| v.fract() == 0.0);
+    let (bins, width) = if per_integer {
+        ((max - min) as usize + 1, 1.0)
+    } else if max == min {
+        (1, 0.0)
+    } else {
+        (bins, (max - min) / bins as f64)
+    };
+
+    let mut out: Vec<HistogramBin> = (0..bins)
+        .map(|i| HistogramBin {
+            start: min + i as f64 * width,
+            end: if i + 1 == bins && !per_integer {
+                max
+            } else {
+                min + (i + 1) as f64 * width
+            },
+            count: 0,
+            label: None,
+        })
+        .collect();
+    for value in values {
+        let index = if width == 0.0 {
+            0
+        } else {
+            (((value - min) / width).floor() as usize).min(bins - 1)
+        };
+        if let Some(bin) = out.get_mut(index) {
+            bin.count += 1;
+        }
+    }
+    out
+}
+
+/// Buckets epoch seconds by calendar `unit`, or by the finest unit giving at
+/// most `bins` buckets over `min..=max`.
+fn date_bins(
+    values: &[f64],
+    min: f64,
+    max: f64,
+    bins: usize,
+    unit: Option<DateUnit>,
+) -> Result<(DateUnit, Vec<HistogramBin>), String> {
+    let out_of_range = || format!("Date range {} to {} is out of range", min, max);
+    let span = |unit: DateUnit| -> Option<(i64, i64)> {
+        Some((
+            unit.bucket(min.floor() as i64)?,
+            unit.bucket(max.floor() as i64)?,
+        ))
+    };
+
+    let unit = unit.unwrap_or_else(|| {
+        DateUnit::FINEST_FIRST
+            .into_iter()
+            .find(|unit| span(*unit).is_some_and(|(first, last)| last - first < bins as i64))
+            .unwrap_or(DateUnit::Year)
+    });
+    let (first, last) = span(unit).ok_or_else(out_of_range)?;
+    let count = last - first + 1;
+    if count > MAX_BINS as i64 {
+        return Err(format!(
+            "Date range needs {} bins of one {:?}, more than the limit of {}",
+            count, unit, MAX_BINS
+        ));
+    }
+
+    let mut out = (first..=last)
+        .map(|bucket| {
+            let start = unit.start(bucket)?;
+            Some(HistogramBin {
+                start: start as f64,
+                end: unit.start(bucket + 1)? as f64,
+                count: 0,
+                label: Some(unit.label(start)),
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(out_of_range)?;
+    for value in values {
+        let index = unit
+            .bucket(value.floor() as i64)
+            .and_then(|bucket| usize::try_from(bucket - first).ok());
+        if let Some(bin) = index.and_then(|index| out.get_mut(index)) {
+            bin.count += 1;
+        }
+    }
+    Ok((unit, out))
+}
+
+impl DateUnit {
+    const FINEST_FIRST: [DateUnit; 5] = [
+        DateUnit::Minute,
+        DateUnit::Hour,
+        DateUnit::Day,
+        DateUnit::Month,
+        DateUnit::Year,
+    ];
+
+    fn seconds(self) -> Option<i64> {
+        match self {
+            DateUnit::Minute => Some(60),
+            DateUnit::Hour => Some(3600),
+            DateUnit::Day => Some(86_400),
+            DateUnit::Month | DateUnit::Year => None,
+        }
+    }
+
+    /// Number of whole units between the epoch and the bucket holding `seconds`.
+    fn bucket(self, seconds: i64) -> Option<i64> {
+        if let Some(length) = self.seconds() {
+            return Some(seconds.div_euclid(length));
+        }
+        let date = DateTime::from_timestamp(seconds, 0)?;
+        let year = i64::from(date.year());
+        Some(match self {
+            DateUnit::Year => year,
+            DateUnit::Minute | DateUnit::Hour | DateUnit::Day | DateUnit::Month => {
+                year * 12 + i64::from(date.month0())
+            }
+        })
+    }
+
+    /// Epoch seconds at which `bucket` starts.
+    fn start(self, bucket: i64) -> Option<i64> {
+        if let Some(length) = self.seconds() {
+            return bucket.checked_mul(length);
+        }
+        let (year, month0) = match self {
+            DateUnit::Year => (bucket, 0),
+            DateUnit::Minute | DateUnit::Hour | DateUnit::Day | DateUnit::Month => {
+                (bucket.div_euclid(12), bucket.rem_euclid(12))
+            }
+        };
+        NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, month0 as u32 + 1, 1)?
+            .and_hms_opt(0, 0, 0)
+            .map(|start| start.and_utc().timestamp())
+    }
+
+    fn label(self, start: i64) -> String {
+        let format = match self {
+            DateUnit::Minute => "%Y-%m-%dT%H:%M",
+            DateUnit::Hour => "%Y-%m-%dT%H:00",
+            DateUnit::Day => "%Y-%m-%d",
+            DateUnit::Month => "%Y-%m",
+            DateUnit::Year => "%Y",
+        };
+        DateTime::from_timestamp(start, 0)
+            .map(|start| start.format(format).to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Reads an ISO-8601 date or date-time as epoch seconds. Times without an
+/// offset are taken as UTC.
+fn parse_date(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let seconds = if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        time.timestamp_millis() as f64 / 1000.0
+    } else if let Some(time) = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    {
+        time.and_utc().timestamp_millis() as f64 / 1000.0
+    } else {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?
+            .and_utc()
+            .timestamp() as f64
+    };
+    Some(seconds)
+}
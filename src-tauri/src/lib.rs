@@ -1,33 +1,117 @@
+mod clustering;
+mod duplicates;
+mod embedding;
+mod evaluation;
+mod health;
+mod histogram;
+mod profile;
+mod projection;
+mod recall;
+mod rng;
+mod search;
 pub mod structs;
+mod vector;
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
-use chroma::client::ChromaAuthMethod;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use chroma::client::{ChromaAuthMethod, ChromaHttpClientError};
 use chroma::types::{
-    Include, IncludeList, Metadata, MetadataValue, UpdateMetadata, UpdateMetadataValue, Where,
+    GetResponse, Include, IncludeList, Metadata, MetadataValue, QueryResponse, SearchPayload,
+    UpdateMetadata, UpdateMetadataValue, Where,
 };
-use chroma::{ChromaHttpClient, ChromaHttpClientOptions};
-use chroma_types::RawWhereFields;
+use chroma::{ChromaCollection, ChromaHttpClient, ChromaHttpClientOptions};
+use chroma_types::{RawWhereFields, WhereValidationError};
+use clustering::ClusterOptions;
+use duplicates::{DuplicateFinder, DuplicateMode};
+use embedding::EmbeddingProvider;
+use evaluation::GoldenSetInput;
+use health::EmbeddingScanner;
+use histogram::{HistogramOptions, Histogrammer, MAX_BINS};
+use image::{DynamicImage, ImageFormat};
 use parking_lot::Mutex;
+use profile::MetadataProfiler;
+use projection::ProjectionMethod;
+use recall::IndexResult;
+use search::SearchRequest;
 use serde_json::{json, Map, Value};
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::HashMap;
 use std::env;
+use std::hash::{Hash, Hasher};
+use std::ops::ControlFlow;
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use structs::EmbeddingData;
+use structs::{
+    Cluster, ClusterMember, Clustering, DeletePreview, DistanceSpace, DocumentUpdate,
+    DuplicateReport, EmbeddingComparison, EmbeddingData, EmbeddingHealth, FacetCounts, FilterError,
+    Histogram, MetadataPatch, MetadataPatchSample, MetadataProfile, ProjectedPoint, Projection,
+    QueryEvaluation, QueryMatch, RecallReport, RecordChunk, RetrievalEvaluation, RowCount,
+    ScanProgress, SearchMatch, ValueCount,
+};
+use tauri::ipc::{Channel, Response};
 use tauri::menu::{AboutMetadata, Menu, MenuItem, PredefinedMenuItem, Submenu, WINDOW_SUBMENU_ID};
 use tauri::{Manager, State};
 use tauri_plugin_log::{Target, TargetKind};
+use vector::{
+    retain_common_dimension, sparse_vector_from_json, sparse_vector_to_json, validate_vector,
+    write_le_f32, VectorInput,
+};
 
 const TIMEOUT: i32 = 20;
 
+/// Number of records fetched or updated per request when walking every match of a filter.
+const PAGE_SIZE: u32 = 1000;
+
+/// Default `cap` of `count_records`.
+const DEFAULT_COUNT_CAP: u32 = 10_000;
+
+/// How long a cached filtered row count is reused.
+const ROW_COUNT_TTL: Duration = Duration::from_secs(60);
+
+/// Default number of records per message of `stream_embeddings`.
+const STREAM_CHUNK_SIZE: usize = 25;
+
+/// Default `sample_size` of `project_embeddings`.
+const DEFAULT_PROJECTION_SAMPLE: u32 = 2000;
+
+/// Largest `sample_size` of the commands that hold sampled vectors in memory.
+const MAX_VECTOR_SAMPLE: u32 = 20_000;
+
+/// Most records `measure_recall` downloads for its exact search.
+const MAX_EXACT_RECORDS: usize = 100_000;
+
+/// Queries embedded or sent per request by `evaluate_retrieval`.
+const EVALUATION_BATCH: usize = 100;
+
+/// Most ids `compare_embeddings` takes, keeping its matrices small enough to render.
+const MAX_COMPARED_IDS: usize = 500;
+
 #[derive(Clone)]
 struct HttpContext {
     endpoint: reqwest::Url,
     client: reqwest::Client,
 }
 
+/// Row counts of one collection, keyed by filter and cap, with the time each
+/// was taken.
+type RowCountCache = HashMap<String, (Instant, RowCount)>;
+
 struct AppState {
     client: Mutex<Option<ChromaHttpClient>>,
     http: Mutex<Option<HttpContext>>,
+    embedding_providers: Mutex<HashMap<String, EmbeddingProvider>>,
+    /// Filtered row counts per collection. Commands that write to a collection
+    /// drop its entries before writing, so partial batch writes never leave a
+    /// stale count behind. Writes by other clients show up once an entry is
+    /// older than `ROW_COUNT_TTL` or the count is refreshed.
+    row_counts: Mutex<HashMap<String, RowCountCache>>,
+    /// Bumped whenever cached counts are dropped, so a count that was already
+    /// running is not stored afterwards.
+    row_count_generation: AtomicU64,
+    /// Cancellation flags of the counts in progress, keyed by their `count_id`.
+    active_counts: Mutex<HashMap<String, Arc<AtomicBool>>>,
 }
 
 impl AppState {
@@ -48,6 +132,25 @@ impl AppState {
             .cloned() // clone the ChromaHttpClient so the MutexGuard can be dropped before any .await
             .ok_or_else(|| "ChromaDB client not initialized".into())
     }
+
+    fn invalidate_row_counts(&self, collection_name: &str) {
+        let mut row_counts = self.row_counts.lock();
+        self.row_count_generation.fetch_add(1, Ordering::Relaxed);
+        row_counts.remove(collection_name);
+    }
+
+    fn get_embedding_provider(&self, collection_name: &str) -> Result<EmbeddingProvider, String> {
+        self.embedding_providers
+            .lock()
+            .get(collection_name)
+            .cloned()
+            .ok_or_else(|| {
+                format!(
+                    "No embedding provider configured for collection: {}",
+                    collection_name
+                )
+            })
+    }
 }
 
 struct Environment {
@@ -139,6 +242,11 @@ fn create_client(config: ConnectionConfig, state: State<AppState>) -> Result<(),
         .build()
         .map_err(|err| format!("{err}"))?;
     *state.client.lock() = Some(client);
+    {
+        let mut row_counts = state.row_counts.lock();
+        state.row_count_generation.fetch_add(1, Ordering::Relaxed);
+        row_counts.clear();
+    }
     *state.http.lock() = Some(HttpContext {
         endpoint,
         client: http_client,
@@ -414,19 +522,430 @@ async fn fetch_collections(state: State<'_, AppState>) -> Result<Vec<Value>, Str
     Ok(collections_list)
 }
 
-/// Build an `Option<Where>` metadata filter from the optional Mongo-style JSON `where`
-/// payload sent by the frontend. Returns `Ok(None)` when no filter is set.
-fn build_where_filter(where_filter: Option<Value>) -> Result<Option<Where>, String> {
-    let raw = RawWhereFields::new(where_filter.unwrap_or(Value::Null), Value::Null);
-    raw.parse()
-        .map_err(|e| format!("Invalid metadata filter: {}", e))
+/// Build an `Option<Where>` filter from the optional Mongo-style JSON `where` and
+/// `where_document` payloads sent by the frontend. Returns `Ok(None)` when no filter is set.
+/// On a metadata key `$contains`/`$not_contains` test membership of an array value,
+/// e.g. `{"tags": {"$contains": "news"}}`.
+fn build_where_filter(
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+) -> Result<Option<Where>, String> {
+    parse_where_filter(where_filter, where_document).map_err(|e| e.to_string())
+}
+
+/// Like `build_where_filter`, but keeps the error typed so the filter builder
+/// can tell which half of the filter is wrong.
+fn parse_where_filter(
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+) -> Result<Option<Where>, FilterError> {
+    if let Some(where_document) = &where_document {
+        check_where_document(where_document).map_err(FilterError::WhereDocument)?;
+    }
+
+    let raw = RawWhereFields::new(
+        where_filter.unwrap_or(Value::Null),
+        where_document.unwrap_or(Value::Null),
+    );
+    raw.parse().map_err(|e| match e {
+        WhereValidationError::WhereClause => FilterError::Where(e.to_string()),
+        WhereValidationError::Regex(_) | WhereValidationError::WhereDocumentClause => {
+            FilterError::WhereDocument(e.to_string())
+        }
+    })
+}
+
+/// Walks a `where_document` payload and describes the first structural problem.
+/// Chroma's own parser only reports "Invalid where document clause", which is
+/// not enough to point at the offending part. Regex syntax is left to Chroma.
+fn check_where_document(where_document: &Value) -> Result<(), String> {
+    let Some(object) = where_document.as_object() else {
+        return Err(r#"Expected an object such as {"$contains": "text"}"#.to_string());
+    };
+
+    let mut entries = object.iter();
+    let (operator, value) = match (entries.next(), entries.next()) {
+        (Some(entry), None) => entry,
+        (None, _) | (Some(_), Some(_)) => {
+            return Err(format!(
+                "Expected exactly one operator, found {}",
+                object.len()
+            ))
+        }
+    };
+
+    match operator.as_str() {
+        "$and" | "$or" => {
+            let children = value
+                .as_array()
+                .ok_or_else(|| format!("{} expects a list of conditions", operator))?;
+            children.iter().try_for_each(check_where_document)
+        }
+        "$contains" | "$not_contains" | "$regex" | "$not_regex" => {
+            if value.is_string() {
+                Ok(())
+            } else {
+                Err(format!("{} expects a string", operator))
+            }
+        }
+        _ => Err(format!(
+            "Unknown operator {}, expected one of $contains, $not_contains, $regex, $not_regex, $and, $or",
+            operator
+        )),
+    }
+}
+
+/// Validates filter payloads without running them, so the filter builder can
+/// show parse errors inline.
+#[tauri::command]
+fn validate_filter(
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+) -> Result<(), FilterError> {
+    parse_where_filter(where_filter, where_document).map(|_| ())
+}
+
+/// Converts a record's metadata into the JSON map sent to the frontend.
+fn metadata_to_json(metadata: Metadata) -> Map<String, Value> {
+    metadata
+        .into_iter()
+        .map(|(k, v)| (k, metadata_value_to_json(v)))
+        .collect()
+}
+
+fn metadata_value_to_json(value: MetadataValue) -> Value {
+    match value {
+        MetadataValue::Bool(b) => Value::Bool(b),
+        MetadataValue::Int(i) => Value::Number(i.into()),
+        MetadataValue::Float(f) => serde_json::Number::from_f64(f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        MetadataValue::Str(s) => Value::String(s),
+        MetadataValue::BoolArray(values) => {
+            Value::Array(values.into_iter().map(Value::Bool).collect())
+        }
+        MetadataValue::IntArray(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|i| Value::Number(i.into()))
+                .collect(),
+        ),
+        MetadataValue::FloatArray(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|f| {
+                    serde_json::Number::from_f64(f)
+                        .map(Value::Number)
+                        .unwrap_or(Value::Null)
+                })
+                .collect(),
+        ),
+        MetadataValue::StringArray(values) => {
+            Value::Array(values.into_iter().map(Value::String).collect())
+        }
+        MetadataValue::SparseVector(vector) => sparse_vector_to_json(vector),
+    }
+}
+
+/// Converts a JSON metadata value into Chroma's typed form. An object with
+/// `indices` is a sparse vector, see `sparse_vector_from_json`. Arrays must be
+/// non-empty and hold a single type, since Chroma stores them as typed lists;
+/// integers and floats may be mixed and are then stored as floats.
+fn json_to_metadata_value(key: &str, value: Value) -> Result<MetadataValue, String> {
+    match value {
+        Value::Bool(b) => Ok(MetadataValue::Bool(b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Ok(MetadataValue::Int(i)),
+            None => n
+                .as_f64()
+                .map(MetadataValue::Float)
+                .ok_or_else(|| format!("Unsupported metadata number for key {}", key)),
+        },
+        Value::String(s) => Ok(MetadataValue::Str(s)),
+        Value::Array(values) => json_to_metadata_array(key, values),
+        Value::Object(object) if object.contains_key("indices") => sparse_vector_from_json(object)
+            .map(MetadataValue::SparseVector)
+            .map_err(|e| format!("Invalid sparse vector for key {}: {}", key, e)),
+        Value::Null | Value::Object(_) => {
+            Err(format!("Unsupported metadata value for key {}", key))
+        }
+    }
+}
+
+fn json_to_metadata_array(key: &str, values: Vec<Value>) -> Result<MetadataValue, String> {
+    let mixed = || format!("Array values for key {} must all have the same type", key);
+
+    match values.first() {
+        None => Err(format!(
+            "Empty array for key {}, Chroma cannot store an empty list",
+            key
+        )),
+        Some(Value::Bool(_)) => values
+            .iter()
+            .map(|v| v.as_bool().ok_or_else(mixed))
+            .collect::<Result<_, _>>()
+            .map(MetadataValue::BoolArray),
+        Some(Value::String(_)) => values
+            .into_iter()
+            .map(|v| match v {
+                Value::String(s) => Ok(s),
+                Value::Null
+                | Value::Bool(_)
+                | Value::Number(_)
+                | Value::Array(_)
+                | Value::Object(_) => Err(mixed()),
+            })
+            .collect::<Result<_, _>>()
+            .map(MetadataValue::StringArray),
+        Some(Value::Number(_)) if values.iter().all(Value::is_i64) => values
+            .iter()
+            .map(|v| v.as_i64().ok_or_else(mixed))
+            .collect::<Result<_, _>>()
+            .map(MetadataValue::IntArray),
+        Some(Value::Number(_)) => values
+            .iter()
+            .map(|v| v.as_f64().ok_or_else(mixed))
+            .collect::<Result<_, _>>()
+            .map(MetadataValue::FloatArray),
+        Some(Value::Null | Value::Array(_) | Value::Object(_)) => Err(format!(
+            "Unsupported array value for key {}, expected strings, numbers or booleans",
+            key
+        )),
+    }
+}
+
+/// Builds the `UpdateMetadata` for an edit: `metadata` holds the keys to set
+/// and `removed_keys` the keys to drop (sent as `UpdateMetadataValue::None`).
+fn build_update_metadata(
+    metadata: Map<String, Value>,
+    removed_keys: Vec<String>,
+) -> Result<UpdateMetadata, String> {
+    let mut update_metadata: UpdateMetadata = metadata
+        .into_iter()
+        .map(|(k, v)| {
+            let value = json_to_metadata_value(&k, v)?;
+            Ok((k, value.into()))
+        })
+        .collect::<Result<UpdateMetadata, String>>()?;
+
+    for key in removed_keys {
+        update_metadata.insert(key, UpdateMetadataValue::None);
+    }
+
+    Ok(update_metadata)
+}
+
+/// Collects the ids of every record matching `where_clause`, one page at a time.
+///
+/// Callers that go on to mutate the matches should work from this list rather
+/// than paging and writing in the same loop: an edit can move a record out of
+/// the filter and shift the offsets of the pages not yet read.
+async fn collect_matching_ids(
+    collection: &ChromaCollection,
+    where_clause: Option<Where>,
+) -> Result<Vec<String>, ChromaHttpClientError> {
+    let mut ids = Vec::new();
+    let mut offset = 0u32;
+
+    loop {
+        let page = collection
+            .get(
+                None,
+                where_clause.clone(),
+                Some(PAGE_SIZE),
+                Some(offset),
+                Some(IncludeList(vec![])),
+            )
+            .await?;
+        let page_len = page.ids.len() as u32;
+        ids.extend(page.ids);
+
+        if page_len < PAGE_SIZE {
+            return Ok(ids);
+        }
+        offset += page_len;
+    }
+}
+
+/// Counts the records matching `ids` and `where_clause`, stopping once more
+/// than `cap` have been seen.
+///
+/// `count()` takes no filter in the chroma crate, so a filtered count walks the
+/// matches a page at a time with an empty include (ids only) and keeps nothing
+/// but the running total. Unfiltered counts use the server-side `count()`.
+/// Returns `Ok(None)` when `cancelled` is set between pages.
+async fn count_matching(
+    collection: &ChromaCollection,
+    ids: Option<Vec<String>>,
+    where_clause: Option<Where>,
+    cap: Option<u32>,
+    cancelled: &AtomicBool,
+) -> Result<Option<RowCount>, ChromaHttpClientError> {
+    if ids.is_none() && where_clause.is_none() {
+        let count = collection.count().await?;
+        return Ok(Some(match cap {
+            Some(cap) if count > cap => RowCount {
+                count: cap,
+                capped: true,
+                cached: false,
+            },
+            Some(_) | None => RowCount {
+                count,
+                capped: false,
+                cached: false,
+            },
+        }));
+    }
+
+    let mut count = 0u32;
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        // Ask for one record past the cap so reaching it exactly is not reported as capped.
+        let limit = match cap {
+            Some(cap) => PAGE_SIZE.min(cap.saturating_add(1).saturating_sub(count)),
+            None => PAGE_SIZE,
+        };
+        let page = collection
+            .get(
+                ids.clone(),
+                where_clause.clone(),
+                Some(limit),
+                Some(count),
+                Some(IncludeList(vec![])),
+            )
+            .await?;
+        let page_len = page.ids.len() as u32;
+        count += page_len;
+
+        if let Some(cap) = cap {
+            if count > cap {
+                return Ok(Some(RowCount {
+                    count: cap,
+                    capped: true,
+                    cached: false,
+                }));
+            }
+        }
+        if page_len < limit {
+            return Ok(Some(RowCount {
+                count,
+                capped: false,
+                cached: false,
+            }));
+        }
+    }
+}
+
+/// Counts matching records, reusing a cached count of the same filter when it
+/// is younger than `ROW_COUNT_TTL` and `refresh` is not set. Unfiltered counts
+/// are a single server-side `count()` and always run. `count_id` registers
+/// the count so `cancel_row_count` can stop it; it must not belong to a count
+/// that is still running.
+#[allow(clippy::too_many_arguments)]
+async fn cached_row_count(
+    collection_name: &str,
+    ids: Option<Vec<String>>,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    cap: Option<u32>,
+    count_id: Option<String>,
+    refresh: bool,
+    state: &AppState,
+) -> Result<RowCount, String> {
+    let client = state.get_client()?;
+
+    let cache_key = json!([ids, where_filter, where_document, cap]).to_string();
+    let where_clause = build_where_filter(where_filter, where_document)?;
+    let cacheable = ids.is_some() || where_clause.is_some();
+
+    if cacheable && !refresh {
+        if let Some((_, count)) = state
+            .row_counts
+            .lock()
+            .get(collection_name)
+            .and_then(|counts| counts.get(&cache_key))
+            .filter(|(counted_at, _)| counted_at.elapsed() < ROW_COUNT_TTL)
+        {
+            log::debug!("(row_count) Using cached row count: {:?}", count);
+            return Ok(RowCount {
+                cached: true,
+                ..count.clone()
+            });
+        }
+    }
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(row_count) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let generation = state.row_count_generation.load(Ordering::Relaxed);
+    let counted_at = Instant::now();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    if let Some(count_id) = &count_id {
+        match state.active_counts.lock().entry(count_id.clone()) {
+            Entry::Occupied(_) => {
+                log::error!("(row_count) Row count already running: {}", count_id);
+                return Err(format!(
+                    "A row count with id {} is already running",
+                    count_id
+                ));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(cancelled.clone());
+            }
+        }
+    }
+    let result = count_matching(&collection, ids, where_clause, cap, &cancelled).await;
+    if let Some(count_id) = &count_id {
+        let mut active_counts = state.active_counts.lock();
+        if active_counts
+            .get(count_id)
+            .is_some_and(|active| Arc::ptr_eq(active, &cancelled))
+        {
+            active_counts.remove(count_id);
+        }
+    }
+
+    let count = result
+        .map_err(|e| {
+            log::error!("(row_count) Error fetching row count: {}", e);
+            format!("Error fetching row count: {}", e)
+        })?
+        .ok_or_else(|| {
+            log::info!("(row_count) Row count cancelled");
+            "Row count cancelled".to_string()
+        })?;
+
+    if cacheable {
+        // Invalidation bumps the generation while holding this lock, so a
+        // count that overlapped a write is dropped rather than stored.
+        let mut row_counts = state.row_counts.lock();
+        if state.row_count_generation.load(Ordering::Relaxed) == generation {
+            row_counts
+                .entry(collection_name.to_string())
+                .or_default()
+                .insert(cache_key, (counted_at, count.clone()));
+        }
+    }
+
+    Ok(count)
 }
 
+/// Returns the exact number of records matching the optional filters.
+/// Prefer `count_records` for filters that may match many records. `refresh`
+/// recounts instead of using a cached count.
 #[tauri::command]
 async fn fetch_row_count(
     collection_name: &str,
     ids: Option<Vec<String>>,
     where_filter: Option<Value>,
+    where_document: Option<Value>,
+    count_id: Option<String>,
+    refresh: Option<bool>,
     state: State<'_, AppState>,
 ) -> Result<u32, String> {
     let start_time = Instant::now();
@@ -434,44 +953,19 @@ async fn fetch_row_count(
         "(fetch_row_count) Fetching row count for collection: {}",
         collection_name
     );
-    let client = state.get_client()?;
-
-    let collection = client.get_collection(collection_name).await;
-    if collection.is_err() {
-        log::error!(
-            "(fetch_row_count) Error fetching collection: {}",
-            collection.as_ref().err().unwrap()
-        );
-        return Err(format!(
-            "Error fetching collection: {}",
-            collection.err().unwrap()
-        ));
-    }
-
-    let collection = collection.unwrap();
-
-    let where_clause = build_where_filter(where_filter)?;
-    let has_filter = ids.is_some() || where_clause.is_some();
 
-    // `count()` has no filter parameter, so when a filter/ids are active we count the
-    // matching ids returned by a metadata-only `get` (empty include -> ids only, no
-    // embeddings/documents over the wire). NOTE: this materializes all matching ids in
-    // memory, so it can be slow for very large filtered result sets. Move to a
-    // server-side filtered count when the chroma crate exposes one.
-    let count = if has_filter {
-        let get_result = collection
-            .get(ids, where_clause, None, None, Some(IncludeList(vec![])))
-            .await;
-        match get_result {
-            Ok(res) => res.ids.len() as u32,
-            Err(e) => return Err(format!("Error fetching row count: {}", e)),
-        }
-    } else {
-        match collection.count().await {
-            Ok(c) => c,
-            Err(e) => return Err(format!("Error fetching row count: {}", e)),
-        }
-    };
+    let count = cached_row_count(
+        collection_name,
+        ids,
+        where_filter,
+        where_document,
+        None,
+        count_id,
+        refresh.unwrap_or(false),
+        &state,
+    )
+    .await?
+    .count;
 
     let elapsed_time = start_time.elapsed();
     log::debug!(
@@ -484,6 +978,61 @@ async fn fetch_row_count(
     Ok(count)
 }
 
+/// Counts matching records, stopping at `cap` (10,000 by default) so a broad
+/// filter on a large collection can be shown as "10,000+" without walking
+/// every match. `refresh` recounts instead of using a cached count.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn count_records(
+    collection_name: &str,
+    ids: Option<Vec<String>>,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    cap: Option<u32>,
+    count_id: Option<String>,
+    refresh: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<RowCount, String> {
+    log::info!(
+        "(count_records) Counting records in collection: {}",
+        collection_name
+    );
+    let cap = cap.unwrap_or(DEFAULT_COUNT_CAP);
+    log::debug!(
+        "(count_records) cap: {}, where_filter: {:?}, where_document: {:?}",
+        cap,
+        where_filter,
+        where_document
+    );
+
+    cached_row_count(
+        collection_name,
+        ids,
+        where_filter,
+        where_document,
+        Some(cap),
+        count_id,
+        refresh.unwrap_or(false),
+        &state,
+    )
+    .await
+}
+
+/// Stops the count registered under `count_id`. Returns `false` when no such
+/// count is running, e.g. because it already finished.
+#[tauri::command]
+fn cancel_row_count(count_id: &str, state: State<AppState>) -> Result<bool, String> {
+    log::info!("(cancel_row_count) Cancelling row count: {}", count_id);
+
+    Ok(match state.active_counts.lock().get(count_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
+
 #[tauri::command]
 async fn fetch_embeddings(
     collection_name: &str,
@@ -491,6 +1040,7 @@ async fn fetch_embeddings(
     offset: usize,
     ids: Option<Vec<String>>,
     where_filter: Option<Value>,
+    where_document: Option<Value>,
     state: State<'_, AppState>,
 ) -> Result<Vec<EmbeddingData>, String> {
     log::info!(
@@ -500,7 +1050,7 @@ async fn fetch_embeddings(
     log::debug!("(fetch_embeddings) limit: {}, offset: {}", limit, offset,);
     let client = state.get_client()?;
 
-    let where_clause = build_where_filter(where_filter)?;
+    let where_clause = build_where_filter(where_filter, where_document)?;
 
     let collection = client.get_collection(collection_name).await;
     if collection.is_err() {
@@ -522,7 +1072,11 @@ async fn fetch_embeddings(
             where_clause,
             Some(limit as u32),
             Some(offset as u32),
-            Some(IncludeList(vec![Include::Metadata, Include::Document])),
+            Some(IncludeList(vec![
+                Include::Metadata,
+                Include::Document,
+                Include::Uri,
+            ])),
         )
         .await;
 
@@ -537,10 +1091,20 @@ async fn fetch_embeddings(
         ));
     }
 
-    let get_result = get_result.unwrap();
+    records_from_get_response(get_result.unwrap(), None)
+}
+
+/// Turns a `get` response (metadatas, documents and optionally URIs) into
+/// table rows, cutting documents to `truncate_documents` characters if set.
+fn records_from_get_response(
+    get_result: GetResponse,
+    truncate_documents: Option<usize>,
+) -> Result<Vec<EmbeddingData>, String> {
     let ids = get_result.ids;
     let documents = get_result.documents.unwrap_or_default();
     let metadatas = get_result.metadatas.unwrap_or_default();
+    // Only multimodal collections have URIs, so a missing list is not a mismatch.
+    let mut uris = get_result.uris.unwrap_or_default().into_iter();
 
     if ids.len() != documents.len() || ids.len() != metadatas.len() {
         log::error!(
@@ -557,30 +1121,23 @@ async fn fetch_embeddings(
         .zip(documents)
         .zip(metadatas)
         .map(|((id, document), metadata)| {
-            let metadata = metadata
-                .unwrap_or_default()
-                .into_iter()
-                .map(|(k, v)| {
-                    let json_val = match v {
-                        MetadataValue::Bool(b) => Value::Bool(b),
-                        MetadataValue::Int(i) => Value::Number(i.into()),
-                        MetadataValue::Float(f) => serde_json::Number::from_f64(f)
-                            .map(Value::Number)
-                            .unwrap_or(Value::Null),
-                        MetadataValue::Str(s) => Value::String(s),
-                        MetadataValue::SparseVector(_)
-                        | MetadataValue::BoolArray(_)
-                        | MetadataValue::IntArray(_)
-                        | MetadataValue::FloatArray(_)
-                        | MetadataValue::StringArray(_) => Value::Null,
-                    };
-                    (k, json_val)
-                })
-                .collect();
+            let metadata = metadata_to_json(metadata.unwrap_or_default());
+            let mut document = document.unwrap_or_default();
+            let document_truncated = match truncate_documents
+                .and_then(|max_chars| document.char_indices().nth(max_chars))
+            {
+                Some((end, _)) => {
+                    document.truncate(end);
+                    true
+                }
+                None => false,
+            };
             EmbeddingData {
                 id,
                 metadata,
-                document: document.unwrap_or_default(),
+                document,
+                uri: uris.next().flatten(),
+                document_truncated,
             }
         })
         .collect();
@@ -588,21 +1145,112 @@ async fn fetch_embeddings(
     Ok(embeddings_list)
 }
 
+/// Streams a page of records to the webview through `on_chunk`, `chunk_size`
+/// records at a time, so the table can render rows as they arrive instead of
+/// waiting for one large response. Each chunk is fetched from Chroma only
+/// after the previous one has been sent.
+///
+/// With `truncate_documents` set, documents are cut to that many characters
+/// and flagged with `document_truncated`; `fetch_document` returns the full
+/// text. Resolves to the number of records sent.
 #[tauri::command]
-async fn fetch_embedding(
+#[allow(clippy::too_many_arguments)]
+async fn stream_embeddings(
+    collection_name: &str,
+    limit: usize,
+    offset: usize,
+    ids: Option<Vec<String>>,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    chunk_size: Option<usize>,
+    truncate_documents: Option<usize>,
+    on_chunk: Channel<RecordChunk>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    log::info!(
+        "(stream_embeddings) Streaming embeddings for collection: {}",
+        collection_name
+    );
+    let chunk_size = chunk_size.unwrap_or(STREAM_CHUNK_SIZE).max(1);
+    log::debug!(
+        "(stream_embeddings) limit: {}, offset: {}, chunk_size: {}, truncate_documents: {:?}",
+        limit,
+        offset,
+        chunk_size,
+        truncate_documents
+    );
+    let client = state.get_client()?;
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(stream_embeddings) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let mut sent = 0;
+    while sent < limit {
+        let chunk_limit = chunk_size.min(limit - sent);
+        let get_result = collection
+            .get(
+                ids.clone(),
+                where_clause.clone(),
+                Some(chunk_limit as u32),
+                Some((offset + sent) as u32),
+                Some(IncludeList(vec![
+                    Include::Metadata,
+                    Include::Document,
+                    Include::Uri,
+                ])),
+            )
+            .await
+            .map_err(|e| {
+                log::error!("(stream_embeddings) Error fetching embeddings: {}", e);
+                format!("Error fetching embeddings: {}", e)
+            })?;
+
+        let records = records_from_get_response(get_result, truncate_documents)?;
+        let chunk_len = records.len();
+        if chunk_len == 0 {
+            break;
+        }
+
+        on_chunk
+            .send(RecordChunk {
+                offset: offset + sent,
+                records,
+            })
+            .map_err(|e| {
+                log::error!("(stream_embeddings) Error sending records: {}", e);
+                format!("Error sending records: {}", e)
+            })?;
+        sent += chunk_len;
+
+        if chunk_len < chunk_limit {
+            break;
+        }
+    }
+
+    Ok(sent)
+}
+
+/// Returns a record's full document, e.g. after `stream_embeddings` sent a
+/// truncated one.
+#[tauri::command]
+async fn fetch_document(
     collection_name: &str,
     id: &str,
     state: State<'_, AppState>,
-) -> Result<Vec<f32>, String> {
+) -> Result<String, String> {
     log::info!(
-        "(fetch_embedding) Fetching embedding for id: {} in collection: {}",
+        "(fetch_document) Fetching document for id: {} in collection: {}",
         id,
         collection_name
     );
     let client = state.get_client()?;
 
     let collection = client.get_collection(collection_name).await.map_err(|e| {
-        log::error!("(fetch_embedding) Error fetching collection: {}", e);
+        log::error!("(fetch_document) Error fetching collection: {}", e);
         format!("Error fetching collection: {}", e)
     })?;
 
@@ -612,562 +1260,7219 @@ async fn fetch_embedding(
             None,
             Some(1u32),
             None,
-            Some(IncludeList(vec![Include::Embedding])),
+            Some(IncludeList(vec![Include::Document])),
         )
         .await
         .map_err(|e| {
-            log::error!("(fetch_embedding) Error fetching embedding: {}", e);
-            format!("Error fetching embedding: {}", e)
+            log::error!("(fetch_document) Error fetching document: {}", e);
+            format!("Error fetching document: {}", e)
         })?;
 
-    let embeddings = get_result.embeddings.unwrap_or_default();
-    embeddings
+    if get_result.ids.is_empty() {
+        return Err(format!("Record not found for id {}", id));
+    }
+
+    Ok(get_result
+        .documents
+        .unwrap_or_default()
         .into_iter()
         .next()
-        .ok_or_else(|| format!("Embedding not found for id {}", id))
+        .flatten()
+        .unwrap_or_default())
 }
 
-/// Updates a single record's metadata.
-///
-/// `metadata` is the full desired key/value map and `removed_keys` are the keys
-/// to drop. Both are needed because Chroma *merges* the supplied metadata into
-/// the existing record rather than replacing it — a key only disappears when it
-/// is explicitly sent as `UpdateMetadataValue::None`. Renaming a key is
-/// therefore "new key in `metadata`, old key in `removed_keys`".
+/// Largest image file `load_uri_thumbnail` will decode.
+const MAX_THUMBNAIL_SOURCE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Largest SVG `load_uri_thumbnail` will inline. SVGs are sent as stored
+/// rather than rasterised, since the webview scales them itself.
+const MAX_SVG_BYTES: u64 = 1024 * 1024;
+
+/// Width and height of the box thumbnails are scaled down to fit.
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// Reads the image behind a local `file://` record URI, scales it to fit a
+/// 256x256 box and returns it as a `data:` URL, since the webview cannot load
+/// arbitrary local files itself. Opaque images are sent as JPEG and images
+/// with transparency as PNG.
 #[tauri::command]
-async fn update_record_metadata(
+async fn load_uri_thumbnail(uri: &str) -> Result<String, String> {
+    log::info!("(load_uri_thumbnail) Loading thumbnail for uri: {}", uri);
+
+    let url = uri
+        .parse::<reqwest::Url>()
+        .map_err(|e| format!("Invalid uri {}: {}", uri, e))?;
+    if url.scheme() != "file" {
+        return Err(format!("Only file:// uris can be previewed, got {}", uri));
+    }
+    let path = url
+        .to_file_path()
+        .map_err(|_| format!("Invalid file uri: {}", uri))?;
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let format = match extension.as_str() {
+        "png" => Some(ImageFormat::Png),
+        "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+        "gif" => Some(ImageFormat::Gif),
+        "webp" => Some(ImageFormat::WebP),
+        "bmp" => Some(ImageFormat::Bmp),
+        "svg" => None,
+        _ => return Err(format!("Unsupported image type: {}", path.display())),
+    };
+
+    let limit = match format {
+        Some(_) => MAX_THUMBNAIL_SOURCE_BYTES,
+        None => MAX_SVG_BYTES,
+    };
+    let size = std::fs::metadata(&path)
+        .map_err(|e| format!("Error reading {}: {}", path.display(), e))?
+        .len();
+    if size > limit {
+        return Err(format!(
+            "{} is {} bytes, larger than the {} byte preview limit",
+            path.display(),
+            size,
+            limit
+        ));
+    }
+
+    let bytes =
+        std::fs::read(&path).map_err(|e| format!("Error reading {}: {}", path.display(), e))?;
+    let Some(format) = format else {
+        return Ok(format!(
+            "data:image/svg+xml;base64,{}",
+            BASE64_STANDARD.encode(bytes)
+        ));
+    };
+
+    // Decoding and resizing a large photo takes a while, so keep it off the
+    // async runtime.
+    tauri::async_runtime::spawn_blocking(move || {
+        let image = image::load_from_memory_with_format(&bytes, format)
+            .map_err(|e| format!("Error decoding {}: {}", path.display(), e))?;
+        let image = if image.width() > THUMBNAIL_SIZE || image.height() > THUMBNAIL_SIZE {
+            image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        } else {
+            image
+        };
+
+        let (image, format, mime) = if image.color().has_alpha() {
+            (image, ImageFormat::Png, "image/png")
+        } else {
+            (
+                DynamicImage::ImageRgb8(image.to_rgb8()),
+                ImageFormat::Jpeg,
+                "image/jpeg",
+            )
+        };
+        let mut encoded = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut encoded), format)
+            .map_err(|e| format!("Error encoding thumbnail: {}", e))?;
+
+        Ok(format!(
+            "data:{};base64,{}",
+            mime,
+            BASE64_STANDARD.encode(encoded)
+        ))
+    })
+    .await
+    .map_err(|e| {
+        log::error!("(load_uri_thumbnail) Error rendering thumbnail: {}", e);
+        format!("Error rendering thumbnail: {}", e)
+    })?
+}
+
+/// Returns a record's embedding as raw little-endian `f32` bytes rather than a
+/// JSON number array, which is several times larger and slow to parse for
+/// high-dimensional vectors. Read it with `new Float32Array(buffer)`.
+#[tauri::command]
+async fn fetch_embedding(
     collection_name: &str,
     id: &str,
-    metadata: Map<String, Value>,
-    removed_keys: Vec<String>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<Response, String> {
     log::info!(
-        "(update_record_metadata) Updating metadata for id: {} in collection: {}",
+        "(fetch_embedding) Fetching embedding for id: {} in collection: {}",
         id,
         collection_name
     );
-    log::debug!(
-        "(update_record_metadata) metadata: {:?}, removed_keys: {:?}",
-        metadata,
-        removed_keys
-    );
     let client = state.get_client()?;
 
-    let mut update_metadata: UpdateMetadata = metadata
-        .into_iter()
-        .map(|(k, v)| {
-            let value = match v {
-                Value::Bool(b) => UpdateMetadataValue::Bool(b),
-                Value::Number(n) => match n.as_i64() {
-                    Some(i) => UpdateMetadataValue::Int(i),
-                    None => match n.as_f64() {
-                        Some(f) => UpdateMetadataValue::Float(f),
-                        None => return Err(format!("Unsupported metadata number for key {}", k)),
-                    },
-                },
-                Value::String(s) => UpdateMetadataValue::Str(s),
-                Value::Null | Value::Array(_) | Value::Object(_) => {
-                    return Err(format!("Unsupported metadata value for key {}", k))
-                }
-            };
-            Ok((k, value))
-        })
-        .collect::<Result<UpdateMetadata, String>>()?;
-
-    for key in removed_keys {
-        update_metadata.insert(key, UpdateMetadataValue::None);
-    }
-
     let collection = client.get_collection(collection_name).await.map_err(|e| {
-        log::error!("(update_record_metadata) Error fetching collection: {}", e);
+        log::error!("(fetch_embedding) Error fetching collection: {}", e);
         format!("Error fetching collection: {}", e)
     })?;
 
-    collection
-        .update(
-            vec![id.to_string()],
-            None,
-            None,
-            None,
-            Some(vec![Some(update_metadata)]),
-        )
+    let embedding = get_record_embedding(&collection, id)
         .await
         .map_err(|e| {
-            log::error!("(update_record_metadata) Error updating metadata: {}", e);
-            format!("Error updating metadata: {}", e)
-        })?;
+            log::error!("(fetch_embedding) Error fetching embedding: {}", e);
+            format!("Error fetching embedding: {}", e)
+        })?
+        .ok_or_else(|| format!("Embedding not found for id {}", id))?;
 
-    Ok(())
+    let mut bytes = Vec::with_capacity(embedding.len() * 4);
+    write_le_f32(&embedding, &mut bytes);
+
+    Ok(Response::new(bytes))
 }
 
-/// Deletes records by id.
-///
-/// Returns nothing rather than a count: the server's `deleted` field is
-/// `#[serde(default)]` and ChromaDB 1.0.16 does not send it, so it always
-/// deserializes as 0. Callers should report the number of ids they requested.
+/// Compares the embeddings of `ids` pairwise by cosine similarity, Euclidean
+/// distance and inner product, for a heatmap of the selected records. At most
+/// 500 ids are compared at once.
 #[tauri::command]
-async fn delete_records(
+async fn compare_embeddings(
     collection_name: &str,
     ids: Vec<String>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<EmbeddingComparison, String> {
     log::info!(
-        "(delete_records) Deleting {} record(s) from collection: {}",
+        "(compare_embeddings) Comparing {} embedding(s) in collection: {}",
         ids.len(),
         collection_name
     );
+    let client = state.get_client()?;
 
-    // `delete` treats an empty id list as "no filter", so refuse it here rather
-    // than send an ambiguous request.
     if ids.is_empty() {
-        log::error!("(delete_records) No record ids provided");
+        log::error!("(compare_embeddings) No record ids provided");
         return Err("No record ids provided".to_string());
     }
-
-    let client = state.get_client()?;
+    if ids.len() > MAX_COMPARED_IDS {
+        log::error!("(compare_embeddings) Too many ids: {}", ids.len());
+        return Err(format!(
+            "At most {} records can be compared at once, got {}",
+            MAX_COMPARED_IDS,
+            ids.len()
+        ));
+    }
 
     let collection = client.get_collection(collection_name).await.map_err(|e| {
-        log::error!("(delete_records) Error fetching collection: {}", e);
+        log::error!("(compare_embeddings) Error fetching collection: {}", e);
         format!("Error fetching collection: {}", e)
     })?;
 
-    collection
-        .delete(Some(ids), None, None)
-        .await
-        .map_err(|e| {
-            log::error!("(delete_records) Error deleting records: {}", e);
-            format!("Error deleting records: {}", e)
-        })?;
+    let vectors = fetch_vectors_by_id(&collection, &ids).await?;
+    common_dimension(&ids, &vectors).map_err(|e| {
+        log::error!("(compare_embeddings) {}", e);
+        e
+    })?;
 
-    Ok(())
+    let norms: Vec<f32> = vectors.iter().map(|v| vector::l2_norm(v)).collect();
+    let mut cosine_similarity = Vec::with_capacity(vectors.len());
+    let mut l2_distance = Vec::with_capacity(vectors.len());
+    let mut inner_product = Vec::with_capacity(vectors.len());
+    for (a, norm_a) in vectors.iter().zip(&norms) {
+        let mut cosine_row = Vec::with_capacity(vectors.len());
+        let mut l2_row = Vec::with_capacity(vectors.len());
+        let mut inner_row = Vec::with_capacity(vectors.len());
+        for (b, norm_b) in vectors.iter().zip(&norms) {
+            let (mut dot, mut squared) = (0.0f64, 0.0f64);
+            for (&x, &y) in a.iter().zip(b) {
+                dot += f64::from(x) * f64::from(y);
+                squared += (f64::from(x) - f64::from(y)).powi(2);
+            }
+            let norms = f64::from(*norm_a) * f64::from(*norm_b);
+            cosine_row.push((norms > 0.0).then(|| (dot / norms).clamp(-1.0, 1.0) as f32));
+            l2_row.push(squared.sqrt() as f32);
+            inner_row.push(dot as f32);
+        }
+        cosine_similarity.push(cosine_row);
+        l2_distance.push(l2_row);
+        inner_product.push(inner_row);
+    }
+
+    Ok(EmbeddingComparison {
+        ids,
+        norms,
+        cosine_similarity,
+        l2_distance,
+        inner_product,
+    })
+}
+
+/// Fetches the embeddings of `ids` in the order given, failing if any id has
+/// none.
+async fn fetch_vectors_by_id(
+    collection: &ChromaCollection,
+    ids: &[String],
+) -> Result<Vec<Vec<f32>>, String> {
+    let mut embeddings: HashMap<String, Vec<f32>> = HashMap::with_capacity(ids.len());
+    for chunk in ids.chunks(PAGE_SIZE as usize) {
+        let get_result = collection
+            .get(
+                Some(chunk.to_vec()),
+                None,
+                None,
+                None,
+                Some(IncludeList(vec![Include::Embedding])),
+            )
+            .await
+            .map_err(|e| {
+                log::error!("(fetch_vectors_by_id) Error fetching embeddings: {}", e);
+                format!("Error fetching embeddings: {}", e)
+            })?;
+        embeddings.extend(
+            get_result
+                .ids
+                .into_iter()
+                .zip(get_result.embeddings.unwrap_or_default()),
+        );
+    }
+
+    let missing: Vec<&str> = ids
+        .iter()
+        .filter(|id| !embeddings.contains_key(id.as_str()))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Embeddings not found for ids {}",
+            missing.join(", ")
+        ));
+    }
+
+    Ok(ids
+        .iter()
+        .filter_map(|id| embeddings.get(id).cloned())
+        .collect())
+}
+
+/// Returns the length shared by `vectors`, fetched for `ids`, or an error
+/// naming the first record whose length differs from the first vector's.
+fn common_dimension(ids: &[String], vectors: &[Vec<f32>]) -> Result<usize, String> {
+    let dimension = vectors.first().map_or(0, Vec::len);
+    match ids
+        .iter()
+        .zip(vectors)
+        .find(|(_, vector)| vector.len() != dimension)
+    {
+        Some((id, vector)) => Err(format!(
+            "Embedding of {} has dimension {}, but the first has {}",
+            id,
+            vector.len(),
+            dimension
+        )),
+        None => Ok(dimension),
+    }
 }
 
+/// Returns the embeddings of `ids` in one binary buffer, in the order given:
+/// the dimension as a little-endian `u32`, then each vector as little-endian
+/// `f32`s. The 4-byte header keeps the vectors aligned for
+/// `new Float32Array(buffer, 4)`.
 #[tauri::command]
-async fn fetch_collection_data(
+async fn fetch_embeddings_vectors(
     collection_name: &str,
+    ids: Vec<String>,
     state: State<'_, AppState>,
-) -> Result<Value, String> {
+) -> Result<Response, String> {
     log::info!(
-        "(fetch_collection_data) Fetching collection data for collection: {}",
+        "(fetch_embeddings_vectors) Fetching {} embedding(s) in collection: {}",
+        ids.len(),
         collection_name
     );
     let client = state.get_client()?;
 
-    let collection = client.get_collection(collection_name).await;
-
-    if collection.is_err() {
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
         log::error!(
-            "(fetch_collection_data) Error fetching collection: {}",
-            collection.as_ref().err().unwrap()
+            "(fetch_embeddings_vectors) Error fetching collection: {}",
+            e
         );
-        return Err("Error fetching collection".to_string());
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let vectors = fetch_vectors_by_id(&collection, &ids).await?;
+
+    // The single header dimension only describes the buffer if every vector
+    // has it.
+    let dimension = common_dimension(&ids, &vectors).map_err(|e| {
+        log::error!("(fetch_embeddings_vectors) {}", e);
+        e
+    })?;
+    let mut bytes = Vec::with_capacity(4 + vectors.len() * dimension * 4);
+    bytes.extend_from_slice(&(dimension as u32).to_le_bytes());
+    for vector in &vectors {
+        write_le_f32(vector, &mut bytes);
     }
-    let collection = collection.unwrap();
 
-    let collection_id = collection.id();
-    let collection_metadata = collection.metadata();
+    Ok(Response::new(bytes))
+}
 
-    // Probe one record with embedding to determine dimension.
-    // (chroma_types::Collection::dimension is pub(crate) and not exposed by the wrapper.)
-    let dimension: Option<u32> = collection
+/// Fetches a single record's embedding, or `None` if the id does not exist.
+async fn get_record_embedding(
+    collection: &ChromaCollection,
+    id: &str,
+) -> Result<Option<Vec<f32>>, ChromaHttpClientError> {
+    let get_result = collection
         .get(
-            None,
+            Some(vec![id.to_string()]),
             None,
             Some(1u32),
-            Some(0u32),
+            None,
             Some(IncludeList(vec![Include::Embedding])),
         )
-        .await
-        .ok()
-        .and_then(|r| r.embeddings)
-        .and_then(|e| e.into_iter().next())
-        .map(|v| v.len() as u32);
+        .await?;
 
-    log::debug!(
-        "(fetch_collection_data) Fetched collection: {}, {:?}, dimension: {:?}",
-        collection_id,
-        collection_metadata,
-        dimension
-    );
-    let metadata = json!({
-        "id": collection_id,
-        "metadata": collection_metadata,
-        "configuration": json!({}),
-        "dimension": dimension,
-    });
+    Ok(get_result.embeddings.unwrap_or_default().into_iter().next())
+}
 
-    Ok(metadata)
+/// Flattens the results of a single-embedding `query` into `QueryMatch`es,
+/// dropping `exclude_id` and keeping at most `n_results`.
+fn query_matches(
+    response: QueryResponse,
+    exclude_id: Option<&str>,
+    n_results: usize,
+) -> Vec<QueryMatch> {
+    let ids = response.ids.into_iter().next().unwrap_or_default();
+    let documents = response
+        .documents
+        .and_then(|d| d.into_iter().next())
+        .unwrap_or_default();
+    let metadatas = response
+        .metadatas
+        .and_then(|m| m.into_iter().next())
+        .unwrap_or_default();
+    let distances = response
+        .distances
+        .and_then(|d| d.into_iter().next())
+        .unwrap_or_default();
+
+    let mut documents = documents.into_iter();
+    let mut metadatas = metadatas.into_iter();
+    let mut distances = distances.into_iter();
+
+    ids.into_iter()
+        .map(|id| QueryMatch {
+            id,
+            document: documents.next().flatten().unwrap_or_default(),
+            metadata: metadata_to_json(metadatas.next().flatten().unwrap_or_default()),
+            distance: distances.next().flatten(),
+        })
+        .filter(|m| Some(m.id.as_str()) != exclude_id)
+        .take(n_results)
+        .collect()
 }
 
+/// Finds the records nearest to an existing record ("more like this").
+///
+/// The record's own embedding is used as the query vector. One extra result is
+/// requested so that `n_results` remain once the record itself is dropped.
 #[tauri::command]
-async fn create_collection(
+async fn query_similar(
     collection_name: &str,
-    metadata: Option<Value>,
+    id: &str,
+    n_results: Option<u32>,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
     state: State<'_, AppState>,
-) -> Result<Value, String> {
+) -> Result<Vec<QueryMatch>, String> {
     log::info!(
-        "(create_collection) Creating collection: {} with metadata: {:?}",
-        collection_name,
-        metadata
+        "(query_similar) Querying records similar to id: {} in collection: {}",
+        id,
+        collection_name
     );
-    let client = state.get_client()?;
-
-    let collection_metadata: Option<Metadata> = metadata.and_then(|m| {
-        m.as_object().map(|obj| {
-            obj.iter()
-                .filter_map(|(k, v)| {
-                    let mv = match v {
-                        Value::Bool(b) => Some(MetadataValue::Bool(*b)),
-                        Value::Number(n) => {
-                            if let Some(i) = n.as_i64() {
-                                Some(MetadataValue::Int(i))
-                            } else {
-                                n.as_f64().map(MetadataValue::Float)
-                            }
-                        }
-                        Value::String(s) => Some(MetadataValue::Str(s.clone())),
-                        Value::Null | Value::Array(_) | Value::Object(_) => None,
-                    };
-                    mv.map(|v| (k.clone(), v))
-                })
-                .collect()
-        })
-    });
+    let n_results = n_results.unwrap_or(10);
     log::debug!(
-        "(create_collection) Creating collection with name: {} and metadata: {:?}",
-        collection_name,
-        collection_metadata,
+        "(query_similar) n_results: {}, where_filter: {:?}, where_document: {:?}",
+        n_results,
+        where_filter,
+        where_document
     );
-    let result = client
-        .get_or_create_collection(collection_name, None, collection_metadata)
-        .await;
+    let client = state.get_client()?;
 
-    if result.is_err() {
-        log::error!(
-            "(create_collection) Error creating collection: {}",
-            result.as_ref().err().unwrap()
-        );
-        return Err(format!(
-            "Error creating collection: {}",
-            result.err().unwrap()
-        ));
-    }
+    let where_clause = build_where_filter(where_filter, where_document)?;
 
-    let collection = result.unwrap();
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(query_similar) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
 
-    Ok(json!({
-        "id": collection.id(),
-        "name": collection.name(),
-        "metadata": collection.metadata()
-    }))
+    let embedding = get_record_embedding(&collection, id)
+        .await
+        .map_err(|e| {
+            log::error!("(query_similar) Error fetching embedding: {}", e);
+            format!("Error fetching embedding: {}", e)
+        })?
+        .ok_or_else(|| format!("Embedding not found for id {}", id))?;
+
+    let response = collection
+        .query(
+            vec![embedding],
+            Some(n_results.saturating_add(1)),
+            where_clause,
+            None,
+            Some(IncludeList(vec![
+                Include::Document,
+                Include::Metadata,
+                Include::Distance,
+            ])),
+        )
+        .await
+        .map_err(|e| {
+            log::error!("(query_similar) Error querying collection: {}", e);
+            format!("Error querying collection: {}", e)
+        })?;
+
+    Ok(query_matches(response, Some(id), n_results as usize))
 }
 
+/// Runs a nearest-neighbour query with a vector supplied by the user, e.g. one
+/// captured from a production retrieval, so its results can be reproduced.
+///
+/// The vector goes through the same checks as `update_record_embedding`
+/// before the query is sent.
 #[tauri::command]
-async fn delete_collection(
-    collection_names: Vec<String>,
+async fn query_by_vector(
+    collection_name: &str,
+    vector: VectorInput,
+    n_results: Option<u32>,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<Vec<QueryMatch>, String> {
     log::info!(
-        "(delete_collection) Deleting collection: {:?}",
-        collection_names
+        "(query_by_vector) Querying collection: {} by vector",
+        collection_name
+    );
+    let n_results = n_results.unwrap_or(10);
+    log::debug!(
+        "(query_by_vector) n_results: {}, where_filter: {:?}, where_document: {:?}",
+        n_results,
+        where_filter,
+        where_document
     );
     let client = state.get_client()?;
 
-    let mut errors = vec![];
-    for collection_name in &collection_names {
-        if let Err(e) = client.delete_collection(collection_name).await {
-            errors.push(e.to_string());
-        }
-    }
-
-    if !errors.is_empty() {
-        log::error!(
-            "(delete_collection) Error deleting collections: {}",
-            errors.join(", ")
-        );
-        return Err(format!("Error deleting collections: {}", errors.join(", ")));
-    }
+    let embedding = vector.parse().await?;
+    let where_clause = build_where_filter(where_filter, where_document)?;
 
-    Ok(())
-}
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(query_by_vector) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
 
-fn get_current_date_string() -> String {
-    use chrono::Local;
+    let dimension = probe_dimension(&collection).await;
+    validate_vector(&embedding, dimension).map_err(|e| {
+        log::error!("(query_by_vector) Invalid vector: {}", e);
+        e
+    })?;
 
-    let current_date = Local::now().format("%Y-%m-%d").to_string();
+    let response = collection
+        .query(
+            vec![embedding],
+            Some(n_results),
+            where_clause,
+            None,
+            Some(IncludeList(vec![
+                Include::Document,
+                Include::Metadata,
+                Include::Distance,
+            ])),
+        )
+        .await
+        .map_err(|e| {
+            log::error!("(query_by_vector) Error querying collection: {}", e);
+            format!("Error querying collection: {}", e)
+        })?;
 
-    current_date
+    Ok(query_matches(response, None, n_results as usize))
 }
 
-fn get_github_issue_body(environment: &Environment) -> String {
-    format!("#### Current Behavior
-<!-- A clear and concise description of the behavior. -->
+/// Reports whether the server implements Chroma's Search API, which `search`
+/// needs. Older servers do not, and the search panel is hidden for them.
+#[tauri::command]
+async fn check_search_support(
+    collection_name: &str,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    log::info!(
+        "(check_search_support) Checking search support with collection: {}",
+        collection_name
+    );
+    let client = state.get_client()?;
 
-#### Expected Behavior
-<!-- A clear and concise description of what you expected to happen. -->
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(check_search_support) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
 
-#### Additional context/Screenshots
-<!-- Add any other context about the problem here. If applicable, add screenshots to help explain. -->
+    match collection
+        .search(vec![SearchPayload::default().limit(Some(1), 0)])
+        .await
+    {
+        Ok(_) => Ok(true),
+        Err(e) if search::is_unsupported(&e) => Ok(false),
+        Err(e) => {
+            log::error!("(check_search_support) Error searching collection: {}", e);
+            Err(format!("Error searching collection: {}", e))
+        }
+    }
+}
 
-#### Possible Solution
-<!--- Only if you have suggestions on a fix for the bug -->
+/// Runs a search through Chroma's Search API: a filter, a ranking built from
+/// dense, sparse or text nearest-neighbour terms (summed or fused with RRF),
+/// a limit and the keys to return.
+#[tauri::command]
+async fn search(
+    collection_name: &str,
+    request: SearchRequest,
+    state: State<'_, AppState>,
+) -> Result<Vec<SearchMatch>, String> {
+    log::info!("(search) Searching collection: {}", collection_name);
+    log::debug!("(search) request: {:?}", request);
+    let client = state.get_client()?;
+    let provider = if request.needs_provider() {
+        Some(state.get_embedding_provider(collection_name).map_err(|e| {
+            log::error!("(search) {}", e);
+            e
+        })?)
+    } else {
+        None
+    };
 
-#### Environment
-- ChromaDB version: {}
-- Chromamind version: {}
-- Operating system: {} {}
-", environment.chroma_version, environment.chromamind_version, environment.os_name, environment.os_version)
-}
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(search) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
 
-fn create_github_issue_url(body: &str) -> String {
-    let escaped = urlencoding::encode(body).replace("%20", "+");
+    let dimension = probe_dimension(&collection).await;
+    let payload = request
+        .into_payload(provider.as_ref(), dimension)
+        .await
+        .map_err(|e| {
+            log::error!("(search) Invalid search: {}", e);
+            e
+        })?;
 
-    format!(
-        "https://github.com/probaku1234/chromamind/issues/new?template={}&body={}",
-        urlencoding::encode("bug_report.md"),
-        escaped
-    )
-    .chars()
-    .collect()
+    let response = collection.search(vec![payload]).await.map_err(|e| {
+        log::error!("(search) Error searching collection: {}", e);
+        if search::is_unsupported(&e) {
+            "This Chroma server does not support the Search API".to_string()
+        } else {
+            format!("Error searching collection: {}", e)
+        }
+    })?;
+
+    let ids = response.ids.into_iter().next().unwrap_or_default();
+    let mut documents = response
+        .documents
+        .into_iter()
+        .next()
+        .flatten()
+        .unwrap_or_default()
+        .into_iter();
+    let mut metadatas = response
+        .metadatas
+        .into_iter()
+        .next()
+        .flatten()
+        .unwrap_or_default()
+        .into_iter();
+    let mut scores = response
+        .scores
+        .into_iter()
+        .next()
+        .flatten()
+        .unwrap_or_default()
+        .into_iter();
+
+    Ok(ids
+        .into_iter()
+        .map(|id| SearchMatch {
+            id,
+            document: documents.next().flatten(),
+            metadata: metadatas.next().flatten().map(metadata_to_json),
+            score: scores.next().flatten(),
+        })
+        .collect())
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    #[cfg(debug_assertions)]
-    let max_log_level = log::LevelFilter::Debug;
+/// Updates a single record's metadata.
+///
+/// `metadata` is the full desired key/value map and `removed_keys` are the keys
+/// to drop. Both are needed because Chroma *merges* the supplied metadata into
+/// the existing record rather than replacing it — a key only disappears when it
+/// is explicitly sent as `UpdateMetadataValue::None`. Renaming a key is
+/// therefore "new key in `metadata`, old key in `removed_keys`".
+#[tauri::command]
+async fn update_record_metadata(
+    collection_name: &str,
+    id: &str,
+    metadata: Map<String, Value>,
+    removed_keys: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    log::info!(
+        "(update_record_metadata) Updating metadata for id: {} in collection: {}",
+        id,
+        collection_name
+    );
+    log::debug!(
+        "(update_record_metadata) metadata: {:?}, removed_keys: {:?}",
+        metadata,
+        removed_keys
+    );
+    let client = state.get_client()?;
+    state.invalidate_row_counts(collection_name);
 
-    #[cfg(not(debug_assertions))]
-    let max_log_level = log::LevelFilter::Info;
+    let update_metadata = build_update_metadata(metadata, removed_keys)?;
 
-    tauri::Builder::default()
-        .setup(|_app| {
-            // set timeout for minreq requests to 10 seconds
-            // this is to prevent hanging requests
-            env::set_var("MINREQ_TIMEOUT", TIMEOUT.to_string());
-            Ok(())
-        })
-        .manage(AppState {
-            client: Mutex::new(None),
-            http: Mutex::new(None),
-        })
-        .plugin(tauri_plugin_shell::init())
-        .plugin(if cfg!(debug_assertions) {
-            tauri_plugin_devtools::init()
-        } else {
-            tauri_plugin_log::Builder::new()
-                .timezone_strategy(tauri_plugin_log::TimezoneStrategy::UseLocal)
-                .level(max_log_level)
-                .clear_targets()
-                // logging to stderr, file and file name with current date
-                .targets([
-                    Target::new(TargetKind::Stderr),
-                    Target::new(TargetKind::LogDir {
-                        file_name: Some(get_current_date_string()),
-                    }),
-                ])
-                .build()
-        })
-        .invoke_handler(tauri::generate_handler![
-            greet,
-            create_client,
-            health_check,
-            create_window,
-            get_chroma_version,
-            reset_chroma,
-            fetch_embeddings,
-            fetch_collection_data,
-            fetch_row_count,
-            fetch_collections,
-            check_tenant_and_database,
-            create_collection,
-            delete_collection,
-            fetch_embedding,
-            update_record_metadata,
-            delete_records,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(update_record_metadata) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    collection
+        .update(
+            vec![id.to_string()],
+            None,
+            None,
+            None,
+            Some(vec![Some(update_metadata)]),
+        )
+        .await
+        .map_err(|e| {
+            log::error!("(update_record_metadata) Error updating metadata: {}", e);
+            format!("Error updating metadata: {}", e)
+        })?;
+
+    Ok(())
 }
 
-#[allow(clippy::all)]
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chroma::client::ChromaAuthMethod;
-    use chroma::{ChromaHttpClient, ChromaHttpClientOptions};
-    use pretty_assertions::assert_eq;
-    use std::collections::HashMap;
-    use tauri::{
-        ipc::InvokeResponseBody,
-        test::{mock_builder, mock_context, noop_assets, MockRuntime},
-        WebviewWindow,
-    };
-    use testcontainers::{
-        core::{IntoContainerPort, WaitFor},
-        runners::SyncRunner,
-        Container, GenericImage,
-    };
+/// Embeds a single text and checks the vector against the collection's dimension,
+/// so a provider configured with the wrong model is caught before anything is written.
+async fn embed_one(
+    provider: &EmbeddingProvider,
+    text: &str,
+    collection: &ChromaCollection,
+) -> Result<Vec<f32>, String> {
+    let embedding = provider
+        .embed(&[text.to_string()])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Embedding provider returned no vector".to_string())?;
 
+    let dimension = probe_dimension(collection).await;
+    validate_vector(&embedding, dimension)?;
 
-    enum TauriCommand {
-        Greet,
-        CreateClient,
-        HealthCheck,
-        GetChromaVersion,
-        ResetChroma,
-        FetchEmbeddings,
-        FetchCollectionData,
-        FetchRowCount,
-        FetchCollections,
-        CheckTenantAndDatabase,
-        CreateCollection,
-        DeleteCollection,
-        FetchEmbedding,
-        UpdateRecordMetadata,
-        DeleteRecords,
-    }
+    Ok(embedding)
+}
 
-    impl TauriCommand {
-        fn as_str(&self) -> &str {
-            match self {
-                TauriCommand::Greet => "greet",
-                TauriCommand::CreateClient => "create_client",
-                TauriCommand::HealthCheck => "health_check",
-                TauriCommand::GetChromaVersion => "get_chroma_version",
-                TauriCommand::ResetChroma => "reset_chroma",
-                TauriCommand::FetchEmbeddings => "fetch_embeddings",
-                TauriCommand::FetchCollectionData => "fetch_collection_data",
-                TauriCommand::FetchRowCount => "fetch_row_count",
-                TauriCommand::FetchCollections => "fetch_collections",
-                TauriCommand::CheckTenantAndDatabase => "check_tenant_and_database",
-                TauriCommand::CreateCollection => "create_collection",
-                TauriCommand::DeleteCollection => "delete_collection",
-                TauriCommand::FetchEmbedding => "fetch_embedding",
-                TauriCommand::UpdateRecordMetadata => "update_record_metadata",
-                TauriCommand::DeleteRecords => "delete_records",
-            }
+/// Sets the embedding provider used for `collection_name`, or clears it when
+/// `provider` is omitted. Providers are kept in memory for the session.
+#[tauri::command]
+fn set_embedding_provider(
+    collection_name: &str,
+    provider: Option<EmbeddingProvider>,
+    state: State<AppState>,
+) -> Result<(), String> {
+    log::info!(
+        "(set_embedding_provider) Setting embedding provider for collection: {}",
+        collection_name
+    );
+    let mut providers = state.embedding_providers.lock();
+    match provider {
+        Some(provider) => {
+            providers.insert(collection_name.to_string(), provider);
+        }
+        None => {
+            providers.remove(collection_name);
         }
     }
 
-    fn get_command_response(
-        webview: &WebviewWindow<MockRuntime>,
-        command: &str,
-        body: Value,
-    ) -> Result<InvokeResponseBody, Value> {
-        tauri::test::get_ipc_response(
-            webview,
-            tauri::webview::InvokeRequest {
-                cmd: command.into(),
-                callback: tauri::ipc::CallbackFn(0),
-                error: tauri::ipc::CallbackFn(1),
-                url: "http://tauri.localhost".parse().unwrap(),
-                body: tauri::ipc::InvokeBody::Json(body),
-                headers: Default::default(),
-                invoke_key: tauri::test::INVOKE_KEY.to_string(),
-            },
+    Ok(())
+}
+
+/// Returns the embedding provider configured for `collection_name`, if any.
+/// API keys are never included.
+#[tauri::command]
+fn get_embedding_provider(
+    collection_name: &str,
+    state: State<AppState>,
+) -> Result<Option<EmbeddingProvider>, String> {
+    Ok(state.get_embedding_provider(collection_name).ok())
+}
+
+/// Embeds `text` with the collection's provider and runs a nearest-neighbour query.
+#[tauri::command]
+async fn query_by_text(
+    collection_name: &str,
+    text: &str,
+    n_results: Option<u32>,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    state: State<'_, AppState>,
+) -> Result<Vec<QueryMatch>, String> {
+    log::info!(
+        "(query_by_text) Querying collection: {} by text",
+        collection_name
+    );
+    let n_results = n_results.unwrap_or(10);
+    log::debug!(
+        "(query_by_text) text: {}, n_results: {}, where_filter: {:?}, where_document: {:?}",
+        text,
+        n_results,
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+    let provider = state.get_embedding_provider(collection_name).map_err(|e| {
+        log::error!("(query_by_text) {}", e);
+        e
+    })?;
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(query_by_text) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let embedding = embed_one(&provider, text, &collection).await.map_err(|e| {
+        log::error!("(query_by_text) Error embedding text: {}", e);
+        format!("Error embedding text: {}", e)
+    })?;
+
+    let response = collection
+        .query(
+            vec![embedding],
+            Some(n_results),
+            where_clause,
+            None,
+            Some(IncludeList(vec![
+                Include::Document,
+                Include::Metadata,
+                Include::Distance,
+            ])),
         )
-    }
+        .await
+        .map_err(|e| {
+            log::error!("(query_by_text) Error querying collection: {}", e);
+            format!("Error querying collection: {}", e)
+        })?;
 
-    fn before_each<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::App<R> {
-        builder
-            .manage(AppState {
-                client: Mutex::new(None),
-                http: Mutex::new(None),
+    Ok(query_matches(response, None, n_results as usize))
+}
+
+/// Replaces a single record's document text.
+///
+/// Chroma never re-embeds on `update`, so changing the text alone leaves the
+/// old vector in place. With `reembed` the new text is embedded through the
+/// collection's configured provider and written in the same `update` call;
+/// otherwise the vector is kept and the result is flagged with `embedding_stale`.
+#[tauri::command]
+async fn update_record_document(
+    collection_name: &str,
+    id: &str,
+    document: String,
+    reembed: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<DocumentUpdate, String> {
+    log::info!(
+        "(update_record_document) Updating document for id: {} in collection: {}",
+        id,
+        collection_name
+    );
+    let reembed = reembed.unwrap_or(false);
+    log::debug!(
+        "(update_record_document) document length: {}, reembed: {}",
+        document.len(),
+        reembed
+    );
+    let client = state.get_client()?;
+    state.invalidate_row_counts(collection_name);
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(update_record_document) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let embedding = if reembed {
+        let provider = state.get_embedding_provider(collection_name).map_err(|e| {
+            log::error!("(update_record_document) {}", e);
+            e
+        })?;
+        let embedding = embed_one(&provider, &document, &collection)
+            .await
+            .map_err(|e| {
+                log::error!("(update_record_document) Error embedding document: {}", e);
+                format!("Error embedding document: {}", e)
+            })?;
+        Some(vec![Some(embedding)])
+    } else {
+        None
+    };
+
+    collection
+        .update(
+            vec![id.to_string()],
+            embedding,
+            Some(vec![Some(document)]),
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| {
+            log::error!("(update_record_document) Error updating document: {}", e);
+            format!("Error updating document: {}", e)
+        })?;
+
+    Ok(DocumentUpdate {
+        id: id.to_string(),
+        reembedded: reembed,
+        embedding_stale: !reembed,
+    })
+}
+
+/// Overwrites a single record's embedding with a vector supplied by the user.
+///
+/// The vector is checked before anything is written: it must be finite and
+/// match the collection's dimension. With `normalize` it is scaled to unit
+/// length first, which is how a bad vector from a cosine-space pipeline is
+/// repaired in place.
+#[tauri::command]
+async fn update_record_embedding(
+    collection_name: &str,
+    id: &str,
+    vector: VectorInput,
+    normalize: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    log::info!(
+        "(update_record_embedding) Updating embedding for id: {} in collection: {}",
+        id,
+        collection_name
+    );
+    let client = state.get_client()?;
+    state.invalidate_row_counts(collection_name);
+
+    let mut embedding = vector.parse().await?;
+    if normalize.unwrap_or(false) {
+        vector::normalize(&mut embedding)?;
+    }
+    log::debug!(
+        "(update_record_embedding) parsed vector of length: {}",
+        embedding.len()
+    );
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(update_record_embedding) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let dimension = probe_dimension(&collection).await;
+    validate_vector(&embedding, dimension).map_err(|e| {
+        log::error!("(update_record_embedding) Invalid vector: {}", e);
+        e
+    })?;
+
+    collection
+        .update(
+            vec![id.to_string()],
+            Some(vec![Some(embedding)]),
+            None,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| {
+            log::error!("(update_record_embedding) Error updating embedding: {}", e);
+            format!("Error updating embedding: {}", e)
+        })?;
+
+    Ok(())
+}
+
+/// Sets and removes metadata keys on every record matching `where_filter` and
+/// `where_document`.
+///
+/// `set` holds the keys to write and `remove` the keys to drop, with the same
+/// merge semantics as `update_record_metadata`. Omitting both filters patches
+/// the whole collection. With `preview` nothing is written; the result carries
+/// the match count and the before/after metadata of up to `sample_size`
+/// records so the patch can be checked first.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn patch_metadata_where(
+    collection_name: &str,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    set: Map<String, Value>,
+    remove: Vec<String>,
+    preview: Option<bool>,
+    sample_size: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<MetadataPatch, String> {
+    log::info!(
+        "(patch_metadata_where) Patching metadata in collection: {}",
+        collection_name
+    );
+    let preview = preview.unwrap_or(false);
+    log::debug!(
+        "(patch_metadata_where) where_filter: {:?}, where_document: {:?}, set: {:?}, remove: {:?}, preview: {}",
+        where_filter,
+        where_document,
+        set,
+        remove,
+        preview
+    );
+    let client = state.get_client()?;
+    if !preview {
+        state.invalidate_row_counts(collection_name);
+    }
+
+    if set.is_empty() && remove.is_empty() {
+        log::error!("(patch_metadata_where) No metadata changes provided");
+        return Err("No metadata changes provided".to_string());
+    }
+    if let Some(key) = remove.iter().find(|key| set.contains_key(*key)) {
+        log::error!("(patch_metadata_where) Key both set and removed: {}", key);
+        return Err(format!("Key {} is both set and removed", key));
+    }
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+    let update_metadata = build_update_metadata(set.clone(), remove.clone())?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(patch_metadata_where) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    if preview {
+        let matched = count_matching(
+            &collection,
+            None,
+            where_clause.clone(),
+            None,
+            &AtomicBool::new(false),
+        )
+        .await
+        .map_err(|e| {
+            log::error!("(patch_metadata_where) Error counting matches: {}", e);
+            format!("Error counting matches: {}", e)
+        })?
+        .map_or(0, |count| count.count as usize);
+
+        let sample = collection
+            .get(
+                None,
+                where_clause,
+                Some(sample_size.unwrap_or(10)),
+                None,
+                Some(IncludeList(vec![Include::Metadata])),
+            )
+            .await
+            .map_err(|e| {
+                log::error!("(patch_metadata_where) Error fetching sample: {}", e);
+                format!("Error fetching sample: {}", e)
+            })?;
+
+        let samples = sample
+            .ids
+            .into_iter()
+            .zip(sample.metadatas.unwrap_or_default())
+            .map(|(id, metadata)| {
+                let before = metadata_to_json(metadata.unwrap_or_default());
+                let mut after = before.clone();
+                for key in &remove {
+                    after.remove(key);
+                }
+                after.extend(set.clone());
+                MetadataPatchSample { id, before, after }
             })
-            .invoke_handler(tauri::generate_handler![
-                greet,
-                create_client,
-                health_check,
-                // create_window,
-                get_chroma_version,
-                reset_chroma,
-                fetch_embeddings,
-                fetch_collection_data,
-                fetch_row_count,
-                fetch_collections,
-                check_tenant_and_database,
-                create_collection,
-                delete_collection,
-                fetch_embedding,
-                update_record_metadata,
-                delete_records,
-            ])
-            // remove the string argument to use your app's config file
-            .build(mock_context(noop_assets()))
-            .expect("failed to build app")
+            .collect();
+
+        return Ok(MetadataPatch {
+            matched,
+            updated: 0,
+            preview: true,
+            samples,
+        });
+    }
+
+    let ids = collect_matching_ids(&collection, where_clause)
+        .await
+        .map_err(|e| {
+            log::error!("(patch_metadata_where) Error fetching matches: {}", e);
+            format!("Error fetching matches: {}", e)
+        })?;
+
+    let mut updated = 0;
+    for chunk in ids.chunks(PAGE_SIZE as usize) {
+        collection
+            .update(
+                chunk.to_vec(),
+                None,
+                None,
+                None,
+                Some(vec![Some(update_metadata.clone()); chunk.len()]),
+            )
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "(patch_metadata_where) Error updating metadata after {} record(s): {}",
+                    updated,
+                    e
+                );
+                format!(
+                    "Error updating metadata after {} of {} record(s): {}",
+                    updated,
+                    ids.len(),
+                    e
+                )
+            })?;
+        updated += chunk.len();
+    }
+
+    log::debug!(
+        "(patch_metadata_where) Patched {} record(s) in collection: {}",
+        updated,
+        collection_name
+    );
+
+    Ok(MetadataPatch {
+        matched: ids.len(),
+        updated,
+        preview: false,
+        samples: vec![],
+    })
+}
+
+/// Deletes records by id.
+///
+/// Returns nothing rather than a count: the server's `deleted` field is
+/// `#[serde(default)]` and ChromaDB 1.0.16 does not send it, so it always
+/// deserializes as 0. Callers should report the number of ids they requested.
+#[tauri::command]
+async fn delete_records(
+    collection_name: &str,
+    ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    log::info!(
+        "(delete_records) Deleting {} record(s) from collection: {}",
+        ids.len(),
+        collection_name
+    );
+
+    // `delete` treats an empty id list as "no filter", so refuse it here rather
+    // than send an ambiguous request.
+    if ids.is_empty() {
+        log::error!("(delete_records) No record ids provided");
+        return Err("No record ids provided".to_string());
+    }
+
+    let client = state.get_client()?;
+    state.invalidate_row_counts(collection_name);
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(delete_records) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    collection
+        .delete(Some(ids), None, None)
+        .await
+        .map_err(|e| {
+            log::error!("(delete_records) Error deleting records: {}", e);
+            format!("Error deleting records: {}", e)
+        })?;
+
+    Ok(())
+}
+
+/// Confirmation token for a filtered delete, bound to the collection, the
+/// filter payloads and the exact set of matching ids. Any change to the
+/// matches between preview and delete produces a different token.
+fn delete_token(
+    collection_name: &str,
+    where_filter: &Option<Value>,
+    where_document: &Option<Value>,
+    ids: &[String],
+) -> String {
+    let mut sorted_ids = ids.to_vec();
+    sorted_ids.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    collection_name.hash(&mut hasher);
+    json!([where_filter, where_document])
+        .to_string()
+        .hash(&mut hasher);
+    sorted_ids.len().hash(&mut hasher);
+    sorted_ids.hash(&mut hasher);
+
+    format!("{:016x}", hasher.finish())
+}
+
+/// Counts the records a filtered delete would remove and issues the token
+/// `delete_records_where` requires to go ahead.
+#[tauri::command]
+async fn preview_delete_where(
+    collection_name: &str,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    state: State<'_, AppState>,
+) -> Result<DeletePreview, String> {
+    log::info!(
+        "(preview_delete_where) Previewing delete in collection: {}",
+        collection_name
+    );
+    log::debug!(
+        "(preview_delete_where) where_filter: {:?}, where_document: {:?}",
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+
+    let where_clause = build_where_filter(where_filter.clone(), where_document.clone())?;
+    if where_clause.is_none() {
+        log::error!("(preview_delete_where) No filter provided");
+        return Err("No filter provided".to_string());
+    }
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(preview_delete_where) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let ids = collect_matching_ids(&collection, where_clause)
+        .await
+        .map_err(|e| {
+            log::error!("(preview_delete_where) Error fetching matches: {}", e);
+            format!("Error fetching matches: {}", e)
+        })?;
+
+    Ok(DeletePreview {
+        matched: ids.len(),
+        token: delete_token(collection_name, &where_filter, &where_document, &ids),
+        sample_ids: ids.into_iter().take(10).collect(),
+    })
+}
+
+/// Deletes every record matching a filter, using a token from `preview_delete_where`.
+///
+/// The matches are fetched again and the delete is refused if they no longer
+/// produce the same token, so records added, removed or re-tagged since the
+/// preview are never deleted unseen. Each batch is deleted by the confirmed ids
+/// together with the where clause. Returns the number of ids deleted.
+#[tauri::command]
+async fn delete_records_where(
+    collection_name: &str,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    token: &str,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    log::info!(
+        "(delete_records_where) Deleting records by filter from collection: {}",
+        collection_name
+    );
+    log::debug!(
+        "(delete_records_where) where_filter: {:?}, where_document: {:?}",
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+    state.invalidate_row_counts(collection_name);
+
+    // Without a filter `delete` would wipe the whole collection.
+    let where_clause = build_where_filter(where_filter.clone(), where_document.clone())?;
+    if where_clause.is_none() {
+        log::error!("(delete_records_where) No filter provided");
+        return Err("No filter provided".to_string());
+    }
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(delete_records_where) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let ids = collect_matching_ids(&collection, where_clause.clone())
+        .await
+        .map_err(|e| {
+            log::error!("(delete_records_where) Error fetching matches: {}", e);
+            format!("Error fetching matches: {}", e)
+        })?;
+
+    if delete_token(collection_name, &where_filter, &where_document, &ids) != token {
+        log::error!(
+            "(delete_records_where) Matches changed since preview, now {} record(s)",
+            ids.len()
+        );
+        return Err(format!(
+            "Matching records changed since the preview (now {}), preview again before deleting",
+            ids.len()
+        ));
+    }
+
+    // An empty id list is treated as "no filter" by `delete`.
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let mut deleted = 0;
+    for chunk in ids.chunks(PAGE_SIZE as usize) {
+        collection
+            .delete(Some(chunk.to_vec()), where_clause.clone(), None)
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "(delete_records_where) Error deleting records after {} record(s): {}",
+                    deleted,
+                    e
+                );
+                format!(
+                    "Error deleting records after {} of {} record(s): {}",
+                    deleted,
+                    ids.len(),
+                    e
+                )
+            })?;
+        deleted += chunk.len();
     }
 
-    fn create_chroma_container() -> Container<GenericImage> {
-        GenericImage::new("chromadb/chroma", "1.0.16")
-            .with_exposed_port(8000.tcp())
-            // .with_wait_for(WaitFor::Http(HttpWaitStrategy::new("/api/v1/heartbeat").with_port(8000.tcp())))
-            .with_wait_for(WaitFor::message_on_stdout("Connect to Chroma at:"))
-            .start()
-            .expect("ChromaDB should start")
+    Ok(deleted)
+}
+
+/// Probe one record with embedding to determine the collection's dimension.
+/// (chroma_types::Collection::dimension is pub(crate) and not exposed by the wrapper.)
+/// Returns `None` for an empty collection.
+async fn probe_dimension(collection: &ChromaCollection) -> Option<u32> {
+    collection
+        .get(
+            None,
+            None,
+            Some(1u32),
+            Some(0u32),
+            Some(IncludeList(vec![Include::Embedding])),
+        )
+        .await
+        .ok()
+        .and_then(|r| r.embeddings)
+        .and_then(|e| e.into_iter().next())
+        .map(|v| v.len() as u32)
+}
+
+#[tauri::command]
+async fn fetch_collection_data(
+    collection_name: &str,
+    state: State<'_, AppState>,
+) -> Result<Value, String> {
+    log::info!(
+        "(fetch_collection_data) Fetching collection data for collection: {}",
+        collection_name
+    );
+    let client = state.get_client()?;
+
+    let collection = client.get_collection(collection_name).await;
+
+    if collection.is_err() {
+        log::error!(
+            "(fetch_collection_data) Error fetching collection: {}",
+            collection.as_ref().err().unwrap()
+        );
+        return Err("Error fetching collection".to_string());
+    }
+    let collection = collection.unwrap();
+
+    let collection_id = collection.id();
+    let collection_metadata = collection.metadata();
+
+    let dimension = probe_dimension(&collection).await;
+
+    log::debug!(
+        "(fetch_collection_data) Fetched collection: {}, {:?}, dimension: {:?}",
+        collection_id,
+        collection_metadata,
+        dimension
+    );
+    let metadata = json!({
+        "id": collection_id,
+        "metadata": collection_metadata,
+        "configuration": json!({}),
+        "dimension": dimension,
+    });
+
+    Ok(metadata)
+}
+
+/// Profiles the metadata of a collection: for each key, the types seen, how
+/// often it is present, its cardinality, numeric range and common strings.
+///
+/// With `sample_size` smaller than the collection, that many records are read
+/// in pages spread evenly across it rather than from the start only.
+#[tauri::command]
+async fn profile_metadata(
+    collection_name: &str,
+    sample_size: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<MetadataProfile, String> {
+    log::info!(
+        "(profile_metadata) Profiling metadata for collection: {}",
+        collection_name
+    );
+    log::debug!("(profile_metadata) sample_size: {:?}", sample_size);
+    let client = state.get_client()?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(profile_metadata) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let total = collection.count().await.map_err(|e| {
+        log::error!("(profile_metadata) Error counting records: {}", e);
+        format!("Error counting records: {}", e)
+    })?;
+
+    let target = sample_size.map_or(total, |size| size.min(total));
+    let mut profiler = MetadataProfiler::default();
+    sample_records(
+        &collection,
+        None,
+        total,
+        target,
+        vec![Include::Metadata],
+        |page| {
+            for metadata in &page.metadatas.unwrap_or_default() {
+                profiler.add(metadata.as_ref());
+            }
+        },
+    )
+    .await
+    .map_err(|e| {
+        log::error!("(profile_metadata) Error fetching metadata: {}", e);
+        format!("Error fetching metadata: {}", e)
+    })?;
+
+    Ok(profiler.finish(total))
+}
+
+/// The number of records a scan of `where_clause` will visit, for the
+/// `total` of its progress. Only an unfiltered count is a single request;
+/// counting a filter walks every match, as much traffic again as the scan
+/// itself, so the total is left unknown.
+async fn scan_total(
+    collection: &ChromaCollection,
+    where_clause: &Option<Where>,
+) -> Result<Option<u32>, ChromaHttpClientError> {
+    match where_clause {
+        Some(_) => Ok(None),
+        None => collection.count().await.map(Some),
+    }
+}
+
+/// The number of records matching `where_clause`, which `sample_records`
+/// needs to spread its pages. A filter is counted with ids only, a small
+/// fraction of the vectors a full scan would download.
+async fn sample_total(
+    collection: &ChromaCollection,
+    where_clause: &Option<Where>,
+) -> Result<u32, ChromaHttpClientError> {
+    if let Some(total) = scan_total(collection, where_clause).await? {
+        return Ok(total);
+    }
+    let count = count_matching(
+        collection,
+        None,
+        where_clause.clone(),
+        None,
+        &AtomicBool::new(false),
+    )
+    .await?;
+    Ok(count.map_or(0, |count| count.count))
+}
+
+/// Reads `target` of the `total` records matching `where_clause`, in pages
+/// spread evenly across them rather than from the start only, and hands each
+/// page to `visit`. Returns the number of records read.
+async fn sample_records(
+    collection: &ChromaCollection,
+    where_clause: Option<Where>,
+    total: u32,
+    target: u32,
+    include: Vec<Include>,
+    mut visit: impl FnMut(GetResponse),
+) -> Result<u32, ChromaHttpClientError> {
+    let target = target.min(total);
+    let page_size = PAGE_SIZE.min(target.max(1));
+    let pages = target.div_ceil(page_size);
+    // Records skipped between sampled pages; zero for a full scan.
+    let gap = if pages > 1 {
+        (total - target) / (pages - 1)
+    } else {
+        0
+    };
+
+    let mut read = 0u32;
+    for page in 0..pages {
+        let get_result = collection
+            .get(
+                None,
+                where_clause.clone(),
+                Some(page_size.min(target - read)),
+                Some(page * (page_size + gap)),
+                Some(IncludeList(include.clone())),
+            )
+            .await?;
+        read += get_result.ids.len() as u32;
+        visit(get_result);
+    }
+
+    Ok(read)
+}
+
+/// Walks every record matching `where_clause` one page at a time, fetching
+/// the fields in `include` and handing each page to `visit`, until `visit`
+/// breaks. Returns the number of records visited.
+async fn scan_records(
+    collection: &ChromaCollection,
+    where_clause: Option<Where>,
+    include: Vec<Include>,
+    mut visit: impl FnMut(GetResponse) -> ControlFlow<()>,
+) -> Result<usize, ChromaHttpClientError> {
+    let mut offset = 0u32;
+    loop {
+        let page = collection
+            .get(
+                None,
+                where_clause.clone(),
+                Some(PAGE_SIZE),
+                Some(offset),
+                Some(IncludeList(include.clone())),
+            )
+            .await?;
+        let page_len = page.ids.len() as u32;
+        offset += page_len;
+
+        if visit(page).is_break() || page_len < PAGE_SIZE {
+            return Ok(offset as usize);
+        }
+    }
+}
+
+/// Walks the metadata of every record matching `where_clause`, one page at a
+/// time, handing each page to `visit`. Returns the number of records visited.
+async fn scan_metadata(
+    collection: &ChromaCollection,
+    where_clause: Option<Where>,
+    mut visit: impl FnMut(Vec<Option<Metadata>>),
+) -> Result<usize, ChromaHttpClientError> {
+    scan_records(collection, where_clause, vec![Include::Metadata], |page| {
+        visit(page.metadatas.unwrap_or_default());
+        ControlFlow::Continue(())
+    })
+    .await
+}
+
+/// Counts how many matching records hold each value of `key`, most common
+/// first, keeping the `top_n` largest and folding the rest into `other`.
+/// Each element of an array value is counted separately.
+///
+/// Progress is sent through `on_progress` after every page.
+#[tauri::command]
+async fn facet_counts(
+    collection_name: &str,
+    key: &str,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    top_n: Option<usize>,
+    on_progress: Channel<ScanProgress>,
+    state: State<'_, AppState>,
+) -> Result<FacetCounts, String> {
+    log::info!(
+        "(facet_counts) Counting values of key: {} in collection: {}",
+        key,
+        collection_name
+    );
+    let top_n = top_n.unwrap_or(20);
+    log::debug!(
+        "(facet_counts) top_n: {}, where_filter: {:?}, where_document: {:?}",
+        top_n,
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(facet_counts) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let total = scan_total(&collection, &where_clause).await.map_err(|e| {
+        log::error!("(facet_counts) Error counting records: {}", e);
+        format!("Error counting records: {}", e)
+    })?;
+
+    let mut counts: HashMap<String, (Value, usize)> = HashMap::new();
+    let mut missing = 0;
+    let mut scanned = 0;
+    scan_metadata(&collection, where_clause, |page| {
+        for metadata in &page {
+            let values = match metadata.as_ref().and_then(|m| m.get(key)) {
+                Some(value) => match metadata_value_to_json(value.clone()) {
+                    Value::Array(values) => values,
+                    value @ (Value::Null
+                    | Value::Bool(_)
+                    | Value::Number(_)
+                    | Value::String(_)
+                    | Value::Object(_)) => vec![value],
+                },
+                None => {
+                    missing += 1;
+                    continue;
+                }
+            };
+            for value in values {
+                counts.entry(value.to_string()).or_insert((value, 0)).1 += 1;
+            }
+        }
+        scanned += page.len();
+        let _ = on_progress.send(ScanProgress { scanned, total });
+    })
+    .await
+    .map_err(|e| {
+        log::error!("(facet_counts) Error fetching metadata: {}", e);
+        format!("Error fetching metadata: {}", e)
+    })?;
+
+    let distinct = counts.len();
+    let mut buckets: Vec<(String, Value, usize)> = counts
+        .into_iter()
+        .map(|(repr, (value, count))| (repr, value, count))
+        .collect();
+    buckets.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    let other = buckets.iter().skip(top_n).map(|b| b.2).sum();
+    buckets.truncate(top_n);
+
+    Ok(FacetCounts {
+        key: key.to_string(),
+        scanned,
+        missing,
+        distinct,
+        buckets: buckets
+            .into_iter()
+            .map(|(_, value, count)| ValueCount { value, count })
+            .collect(),
+        other,
+    })
+}
+
+/// Bins the values of a numeric or date metadata `key` over the records
+/// matching the filter. Dates are ISO-8601 strings or epoch seconds and are
+/// bucketed by calendar unit; see `HistogramOptions` for the settings.
+///
+/// Progress is sent through `on_progress` after every page.
+#[tauri::command]
+async fn metadata_histogram(
+    collection_name: &str,
+    key: &str,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    options: Option<HistogramOptions>,
+    on_progress: Channel<ScanProgress>,
+    state: State<'_, AppState>,
+) -> Result<Histogram, String> {
+    log::info!(
+        "(metadata_histogram) Binning values of key: {} in collection: {}",
+        key,
+        collection_name
+    );
+    log::debug!(
+        "(metadata_histogram) options: {:?}, where_filter: {:?}, where_document: {:?}",
+        options,
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+    let mut histogrammer = Histogrammer::new(key, options.unwrap_or_default())?;
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(metadata_histogram) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let total = scan_total(&collection, &where_clause).await.map_err(|e| {
+        log::error!("(metadata_histogram) Error counting records: {}", e);
+        format!("Error counting records: {}", e)
+    })?;
+
+    let mut scanned = 0;
+    scan_metadata(&collection, where_clause, |page| {
+        for metadata in &page {
+            histogrammer.add(metadata.as_ref());
+        }
+        scanned += page.len();
+        let _ = on_progress.send(ScanProgress { scanned, total });
+    })
+    .await
+    .map_err(|e| {
+        log::error!("(metadata_histogram) Error fetching metadata: {}", e);
+        format!("Error fetching metadata: {}", e)
+    })?;
+
+    histogrammer.finish()
+}
+
+/// Checks every vector matching the filter for a dimension other than the
+/// most common one, NaN or infinite components and zero length, and reports
+/// the distribution of their norms in `bins` bins (20 by default).
+///
+/// Progress is sent through `on_progress` after every page.
+#[tauri::command]
+async fn scan_embeddings(
+    collection_name: &str,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    bins: Option<usize>,
+    on_progress: Channel<ScanProgress>,
+    state: State<'_, AppState>,
+) -> Result<EmbeddingHealth, String> {
+    log::info!(
+        "(scan_embeddings) Scanning embeddings of collection: {}",
+        collection_name
+    );
+    let bins = bins.unwrap_or(20);
+    log::debug!(
+        "(scan_embeddings) bins: {}, where_filter: {:?}, where_document: {:?}",
+        bins,
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+    if bins == 0 || bins > MAX_BINS {
+        return Err(format!("Bin count must be between 1 and {}", MAX_BINS));
+    }
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(scan_embeddings) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let total = scan_total(&collection, &where_clause).await.map_err(|e| {
+        log::error!("(scan_embeddings) Error counting records: {}", e);
+        format!("Error counting records: {}", e)
+    })?;
+
+    let mut scanner = EmbeddingScanner::default();
+    let mut scanned = 0;
+    scan_records(
+        &collection,
+        where_clause,
+        vec![Include::Embedding],
+        |page| {
+            let embeddings = page.embeddings.unwrap_or_default();
+            for (id, embedding) in page.ids.iter().zip(&embeddings) {
+                scanner.add(id, embedding);
+            }
+            scanned += page.ids.len();
+            let _ = on_progress.send(ScanProgress { scanned, total });
+            ControlFlow::Continue(())
+        },
+    )
+    .await
+    .map_err(|e| {
+        log::error!("(scan_embeddings) Error fetching embeddings: {}", e);
+        format!("Error fetching embeddings: {}", e)
+    })?;
+
+    Ok(scanner.finish(bins))
+}
+
+/// Groups records matching the filter that duplicate each other under `mode`
+/// and plans a delete that keeps one record per group. Near-duplicates are
+/// compared pairwise, so at most 10000 records are accepted in that mode.
+///
+/// Progress is sent through `on_progress` after every page.
+#[tauri::command]
+async fn find_duplicates(
+    collection_name: &str,
+    mode: DuplicateMode,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    on_progress: Channel<ScanProgress>,
+    state: State<'_, AppState>,
+) -> Result<DuplicateReport, String> {
+    log::info!(
+        "(find_duplicates) Finding duplicates in collection: {} by {:?}",
+        collection_name,
+        mode
+    );
+    log::debug!(
+        "(find_duplicates) where_filter: {:?}, where_document: {:?}",
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+    let mut finder = DuplicateFinder::new(mode)?;
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(find_duplicates) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let total = scan_total(&collection, &where_clause).await.map_err(|e| {
+        log::error!("(find_duplicates) Error counting records: {}", e);
+        format!("Error counting records: {}", e)
+    })?;
+
+    let mut scanned = 0;
+    let mut rejected = None;
+    scan_records(&collection, where_clause, vec![mode.include()], |page| {
+        let documents = page.documents.unwrap_or_default();
+        let embeddings = page.embeddings.unwrap_or_default();
+        for (i, id) in page.ids.iter().enumerate() {
+            let document = documents.get(i).and_then(|d| d.as_deref());
+            let embedding = embeddings.get(i).map(Vec::as_slice);
+            if let Err(e) = finder.add(id, document, embedding) {
+                rejected = Some(e);
+                return ControlFlow::Break(());
+            }
+        }
+        scanned += page.ids.len();
+        let _ = on_progress.send(ScanProgress { scanned, total });
+        ControlFlow::Continue(())
+    })
+    .await
+    .map_err(|e| {
+        log::error!("(find_duplicates) Error fetching records: {}", e);
+        format!("Error fetching records: {}", e)
+    })?;
+    if let Some(e) = rejected {
+        log::error!("(find_duplicates) {}", e);
+        return Err(e);
+    }
+
+    // The pairwise comparison of near-duplicates can take seconds, so keep it
+    // off the async runtime.
+    tauri::async_runtime::spawn_blocking(move || finder.finish(scanned))
+        .await
+        .map_err(|e| {
+            log::error!("(find_duplicates) Error comparing records: {}", e);
+            format!("Error comparing records: {}", e)
+        })
+}
+
+/// Projects the vectors of up to `sample_size` records matching the filter
+/// (2000 by default) to `dimensions` coordinates, 2 by default or 3, for a
+/// scatter plot. A sample is spread evenly across the matches. With
+/// `color_key`, each point also carries that metadata value.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn project_embeddings(
+    collection_name: &str,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    sample_size: Option<u32>,
+    method: Option<ProjectionMethod>,
+    dimensions: Option<usize>,
+    color_key: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Projection, String> {
+    log::info!(
+        "(project_embeddings) Projecting embeddings of collection: {}",
+        collection_name
+    );
+    let sample_size = sample_size.unwrap_or(DEFAULT_PROJECTION_SAMPLE);
+    let method = method.unwrap_or_default();
+    let dimensions = dimensions.unwrap_or(2);
+    log::debug!(
+        "(project_embeddings) sample_size: {}, method: {:?}, dimensions: {}, color_key: {:?}",
+        sample_size,
+        method,
+        dimensions,
+        color_key
+    );
+    let client = state.get_client()?;
+    if !(2..=3).contains(&dimensions) {
+        return Err(format!(
+            "Projections have 2 or 3 dimensions, got {}",
+            dimensions
+        ));
+    }
+    if sample_size > MAX_VECTOR_SAMPLE {
+        return Err(format!("Sample size must be at most {}", MAX_VECTOR_SAMPLE));
+    }
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(project_embeddings) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let total = sample_total(&collection, &where_clause)
+        .await
+        .map_err(|e| {
+            log::error!("(project_embeddings) Error counting records: {}", e);
+            format!("Error counting records: {}", e)
+        })?;
+
+    let mut include = vec![Include::Embedding];
+    if color_key.is_some() {
+        include.push(Include::Metadata);
+    }
+    let mut records: Vec<(String, Vec<f32>, Option<Value>)> = Vec::new();
+    sample_records(
+        &collection,
+        where_clause,
+        total,
+        sample_size,
+        include,
+        |page| {
+            let embeddings = page.embeddings.unwrap_or_default();
+            let metadatas = page.metadatas.unwrap_or_default();
+            for (i, (id, embedding)) in page.ids.into_iter().zip(embeddings).enumerate() {
+                let value = color_key.as_ref().and_then(|key| {
+                    metadatas
+                        .get(i)
+                        .and_then(Option::as_ref)
+                        .and_then(|metadata| metadata.get(key))
+                        .map(|value| metadata_value_to_json(value.clone()))
+                });
+                records.push((id, embedding, value));
+            }
+        },
+    )
+    .await
+    .map_err(|e| {
+        log::error!("(project_embeddings) Error fetching embeddings: {}", e);
+        format!("Error fetching embeddings: {}", e)
+    })?;
+
+    let (_, skipped) = retain_common_dimension(&mut records, |(_, embedding, _)| embedding);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let vectors: Vec<Vec<f32>> = records
+            .iter_mut()
+            .map(|(_, embedding, _)| std::mem::take(embedding))
+            .collect();
+        let (coordinates, explained_variance) = projection::project(&vectors, dimensions, method);
+        Projection {
+            total,
+            points: records
+                .into_iter()
+                .zip(coordinates)
+                .map(|((id, _, value), coordinates)| ProjectedPoint {
+                    id,
+                    coordinates,
+                    value,
+                })
+                .collect(),
+            explained_variance,
+            skipped,
+        }
+    })
+    .await
+    .map_err(|e| {
+        log::error!("(project_embeddings) Error projecting embeddings: {}", e);
+        format!("Error projecting embeddings: {}", e)
+    })
+}
+
+/// Runs k-means over a sample of the records matching the filter and reports
+/// each cluster's size, centroid and the records nearest to it. See
+/// `ClusterOptions` for the settings; the same seed and sample give the same
+/// clusters.
+#[tauri::command]
+async fn cluster_embeddings(
+    collection_name: &str,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    options: Option<ClusterOptions>,
+    state: State<'_, AppState>,
+) -> Result<Clustering, String> {
+    log::info!(
+        "(cluster_embeddings) Clustering embeddings of collection: {}",
+        collection_name
+    );
+    let options = options.unwrap_or_default();
+    log::debug!(
+        "(cluster_embeddings) options: {:?}, where_filter: {:?}, where_document: {:?}",
+        options,
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+    let k = options.k.unwrap_or(8);
+    let iterations = options.iterations.unwrap_or(50);
+    let sample_size = options.sample_size.unwrap_or(5000);
+    if k == 0 {
+        return Err("Cluster count must be at least 1".to_string());
+    }
+    if iterations == 0 {
+        return Err("Iteration count must be at least 1".to_string());
+    }
+    if sample_size > MAX_VECTOR_SAMPLE {
+        return Err(format!("Sample size must be at most {}", MAX_VECTOR_SAMPLE));
+    }
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(cluster_embeddings) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let total = sample_total(&collection, &where_clause)
+        .await
+        .map_err(|e| {
+            log::error!("(cluster_embeddings) Error counting records: {}", e);
+            format!("Error counting records: {}", e)
+        })?;
+
+    let mut records: Vec<(String, Vec<f32>)> = Vec::new();
+    sample_records(
+        &collection,
+        where_clause,
+        total,
+        sample_size,
+        vec![Include::Embedding],
+        |page| {
+            let embeddings = page.embeddings.unwrap_or_default();
+            records.extend(page.ids.into_iter().zip(embeddings));
+        },
+    )
+    .await
+    .map_err(|e| {
+        log::error!("(cluster_embeddings) Error fetching embeddings: {}", e);
+        format!("Error fetching embeddings: {}", e)
+    })?;
+    let (_, skipped) = retain_common_dimension(&mut records, |(_, embedding)| embedding);
+    if records.len() < k {
+        log::error!(
+            "(cluster_embeddings) {} record(s) cannot make {} clusters",
+            records.len(),
+            k
+        );
+        return Err(format!(
+            "Cannot make {} clusters from {} record(s)",
+            k,
+            records.len()
+        ));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (ids, vectors): (Vec<String>, Vec<Vec<f32>>) = records.into_iter().unzip();
+        let result = clustering::kmeans(&vectors, k, iterations, options.seed.unwrap_or(42));
+
+        let mut members: Vec<Vec<ClusterMember>> = vec![Vec::new(); k];
+        for ((id, vector), &assignment) in ids.into_iter().zip(&vectors).zip(&result.assignments) {
+            if let (Some(members), Some(centroid)) = (
+                members.get_mut(assignment),
+                result.centroids.get(assignment),
+            ) {
+                members.push(ClusterMember {
+                    id,
+                    distance: clustering::distance(vector, centroid),
+                });
+            }
+        }
+
+        let nearest = options.nearest.unwrap_or(5);
+        Clustering {
+            total,
+            sampled: vectors.len(),
+            skipped,
+            iterations: result.iterations,
+            converged: result.converged,
+            inertia: result.inertia,
+            clusters: result
+                .centroids
+                .into_iter()
+                .zip(members)
+                .map(|(centroid, mut members)| {
+                    let size = members.len();
+                    members.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+                    members.truncate(nearest);
+                    Cluster {
+                        size,
+                        centroid,
+                        nearest: members,
+                    }
+                })
+                .collect(),
+        }
+    })
+    .await
+    .map_err(|e| {
+        log::error!("(cluster_embeddings) Error clustering embeddings: {}", e);
+        format!("Error clustering embeddings: {}", e)
+    })
+}
+
+/// Stores in metadata `key` (`cluster_id` by default) the index of the
+/// centroid nearest to each record matching the filter, so clusters from
+/// `cluster_embeddings` can be filtered on. Centroids must have the
+/// collection's dimension; records with a vector of another dimension are
+/// left alone.
+///
+/// With `preview` nothing is written; the result carries the match count and
+/// the before/after metadata of up to `sample_size` records, as with
+/// `patch_metadata_where`. The previewed `matched` is an upper bound on the
+/// records written, since it does not read every match's vector.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn write_cluster_ids(
+    collection_name: &str,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    centroids: Vec<Vec<f32>>,
+    key: Option<String>,
+    preview: Option<bool>,
+    sample_size: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<MetadataPatch, String> {
+    log::info!(
+        "(write_cluster_ids) Writing cluster ids for {} centroid(s) in collection: {}",
+        centroids.len(),
+        collection_name
+    );
+    let key = key.unwrap_or_else(|| "cluster_id".to_string());
+    let preview = preview.unwrap_or(false);
+    log::debug!(
+        "(write_cluster_ids) key: {}, preview: {}, where_filter: {:?}, where_document: {:?}",
+        key,
+        preview,
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+    if !preview {
+        state.invalidate_row_counts(collection_name);
+    }
+
+    let Some(dimension) = centroids.first().map(Vec::len) else {
+        log::error!("(write_cluster_ids) No centroids provided");
+        return Err("No centroids provided".to_string());
+    };
+    for centroid in &centroids {
+        validate_vector(centroid, Some(dimension as u32))
+            .map_err(|e| format!("Invalid centroid: {}", e))?;
+    }
+
+    let where_clause = build_where_filter(where_filter, where_document)?;
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(write_cluster_ids) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+    if let Some(expected) = probe_dimension(&collection).await {
+        if dimension != expected as usize {
+            log::error!(
+                "(write_cluster_ids) Centroid dimension {} does not match {}",
+                dimension,
+                expected
+            );
+            return Err(format!(
+                "Centroids have dimension {}, but the collection expects {}",
+                dimension, expected
+            ));
+        }
+    }
+    let cluster_of = |embedding: &[f32]| {
+        (embedding.len() == dimension)
+            .then(|| clustering::nearest_centroid(&centroids, embedding))
+            .flatten()
+            .map(|(cluster, _)| cluster)
+    };
+
+    if preview {
+        let sample = collection
+            .get(
+                None,
+                where_clause.clone(),
+                Some(sample_size.unwrap_or(10)),
+                None,
+                Some(IncludeList(vec![Include::Embedding, Include::Metadata])),
+            )
+            .await
+            .map_err(|e| {
+                log::error!("(write_cluster_ids) Error fetching sample: {}", e);
+                format!("Error fetching sample: {}", e)
+            })?;
+        let matched = count_matching(
+            &collection,
+            None,
+            where_clause,
+            None,
+            &AtomicBool::new(false),
+        )
+        .await
+        .map_err(|e| {
+            log::error!("(write_cluster_ids) Error counting matches: {}", e);
+            format!("Error counting matches: {}", e)
+        })?
+        .map_or(0, |count| count.count as usize);
+
+        let samples = sample
+            .ids
+            .into_iter()
+            .zip(sample.embeddings.unwrap_or_default())
+            .zip(sample.metadatas.unwrap_or_default())
+            .map(|((id, embedding), metadata)| {
+                let before = metadata_to_json(metadata.unwrap_or_default());
+                let mut after = before.clone();
+                if let Some(cluster) = cluster_of(&embedding) {
+                    after.insert(key.clone(), json!(cluster));
+                }
+                MetadataPatchSample { id, before, after }
+            })
+            .collect();
+
+        return Ok(MetadataPatch {
+            matched,
+            updated: 0,
+            preview: true,
+            samples,
+        });
+    }
+
+    // Assign every match before writing, since the writes may move records in
+    // or out of the filter and shift the pages.
+    let mut assignments: Vec<(String, usize)> = Vec::new();
+    let matched = scan_records(
+        &collection,
+        where_clause,
+        vec![Include::Embedding],
+        |page| {
+            let embeddings = page.embeddings.unwrap_or_default();
+            for (id, embedding) in page.ids.into_iter().zip(embeddings) {
+                if let Some(cluster) = cluster_of(&embedding) {
+                    assignments.push((id, cluster));
+                }
+            }
+            ControlFlow::Continue(())
+        },
+    )
+    .await
+    .map_err(|e| {
+        log::error!("(write_cluster_ids) Error fetching embeddings: {}", e);
+        format!("Error fetching embeddings: {}", e)
+    })?;
+
+    let mut updated = 0;
+    for chunk in assignments.chunks(PAGE_SIZE as usize) {
+        let (ids, metadatas): (Vec<String>, Vec<Option<UpdateMetadata>>) = chunk
+            .iter()
+            .map(|(id, cluster)| {
+                let metadata = UpdateMetadata::from([(
+                    key.clone(),
+                    UpdateMetadataValue::Int(*cluster as i64),
+                )]);
+                (id.clone(), Some(metadata))
+            })
+            .unzip();
+        collection
+            .update(ids, None, None, None, Some(metadatas))
+            .await
+            .map_err(|e| {
+                log::error!(
+                    "(write_cluster_ids) Error updating metadata after {} record(s): {}",
+                    updated,
+                    e
+                );
+                format!(
+                    "Error updating metadata after {} of {} record(s): {}",
+                    updated,
+                    assignments.len(),
+                    e
+                )
+            })?;
+        updated += chunk.len();
+    }
+
+    log::debug!(
+        "(write_cluster_ids) Wrote cluster ids to {} record(s) in collection: {}",
+        updated,
+        collection_name
+    );
+
+    Ok(MetadataPatch {
+        matched,
+        updated,
+        preview: false,
+        samples: vec![],
+    })
+}
+
+/// Measures the recall of the collection's vector index. `query_count`
+/// records (100 by default) are drawn with `seed` and queried through the
+/// index for their `k` nearest neighbours (10 by default), which are compared
+/// with the exact neighbours over every downloaded vector. Without `space`
+/// the distance function is detected from the distances the index returns.
+///
+/// Progress of the download is sent through `on_progress` after every page.
+#[tauri::command]
+async fn measure_recall(
+    collection_name: &str,
+    query_count: Option<usize>,
+    k: Option<usize>,
+    space: Option<DistanceSpace>,
+    seed: Option<u64>,
+    on_progress: Channel<ScanProgress>,
+    state: State<'_, AppState>,
+) -> Result<RecallReport, String> {
+    log::info!(
+        "(measure_recall) Measuring recall of collection: {}",
+        collection_name
+    );
+    let query_count = query_count.unwrap_or(100);
+    let k = k.unwrap_or(10);
+    log::debug!(
+        "(measure_recall) query_count: {}, k: {}, space: {:?}, seed: {:?}",
+        query_count,
+        k,
+        space,
+        seed
+    );
+    let client = state.get_client()?;
+    if !(1..=1000).contains(&query_count) {
+        return Err("Query count must be between 1 and 1000".to_string());
+    }
+    if !(1..=100).contains(&k) {
+        return Err("k must be between 1 and 100".to_string());
+    }
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(measure_recall) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+
+    let total = collection.count().await.map_err(|e| {
+        log::error!("(measure_recall) Error counting records: {}", e);
+        format!("Error counting records: {}", e)
+    })?;
+    if total as usize > MAX_EXACT_RECORDS {
+        log::error!("(measure_recall) Too many records: {}", total);
+        return Err(format!(
+            "Exact search is limited to {} records, the collection has {}",
+            MAX_EXACT_RECORDS, total
+        ));
+    }
+
+    let mut records: Vec<(String, Vec<f32>)> = Vec::with_capacity(total as usize);
+    scan_records(&collection, None, vec![Include::Embedding], |page| {
+        let embeddings = page.embeddings.unwrap_or_default();
+        records.extend(page.ids.into_iter().zip(embeddings));
+        let _ = on_progress.send(ScanProgress {
+            scanned: records.len(),
+            total: Some(total),
+        });
+        ControlFlow::Continue(())
+    })
+    .await
+    .map_err(|e| {
+        log::error!("(measure_recall) Error fetching embeddings: {}", e);
+        format!("Error fetching embeddings: {}", e)
+    })?;
+    let (_, skipped) = retain_common_dimension(&mut records, |(_, embedding)| embedding);
+    if records.is_empty() {
+        return Err("No embeddings to measure".to_string());
+    }
+    let (ids, vectors): (Vec<String>, Vec<Vec<f32>>) = records.into_iter().unzip();
+    let k = k.min(vectors.len());
+
+    // One request per query, so each latency is that of a single search.
+    let mut results = Vec::with_capacity(query_count);
+    for query in recall::sample_indices(vectors.len(), query_count, seed.unwrap_or(42)) {
+        let Some(vector) = vectors.get(query) else {
+            continue;
+        };
+        let started = Instant::now();
+        let response = collection
+            .query(
+                vec![vector.clone()],
+                Some(k as u32),
+                None,
+                None,
+                Some(IncludeList(vec![Include::Distance])),
+            )
+            .await
+            .map_err(|e| {
+                log::error!("(measure_recall) Error querying collection: {}", e);
+                format!("Error querying collection: {}", e)
+            })?;
+        let latency = started.elapsed();
+        results.push(IndexResult {
+            query,
+            ids: response.ids.into_iter().next().unwrap_or_default(),
+            distances: response
+                .distances
+                .and_then(|distances| distances.into_iter().next())
+                .unwrap_or_default(),
+            latency,
+        });
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        recall::evaluate(&ids, &vectors, &results, k, space, skipped)
+    })
+    .await
+    .map_err(|e| {
+        log::error!("(measure_recall) Error computing exact neighbours: {}", e);
+        format!("Error computing exact neighbours: {}", e)
+    })
+}
+
+/// Runs a labelled query set against the collection and scores the top `k`
+/// results of each query (10 by default) by hit rate, MRR, nDCG and recall.
+/// Queries without an `embedding` are embedded with the collection's
+/// provider. With `save_path` the result is also written there as JSON, to
+/// be read back with `load_evaluation` and compared with other runs.
+///
+/// Progress is sent through `on_progress` after every batch of queries.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn evaluate_retrieval(
+    collection_name: &str,
+    golden_set: GoldenSetInput,
+    k: Option<usize>,
+    where_filter: Option<Value>,
+    where_document: Option<Value>,
+    save_path: Option<String>,
+    on_progress: Channel<ScanProgress>,
+    state: State<'_, AppState>,
+) -> Result<RetrievalEvaluation, String> {
+    log::info!(
+        "(evaluate_retrieval) Evaluating retrieval on collection: {}",
+        collection_name
+    );
+    let k = k.unwrap_or(10);
+    log::debug!(
+        "(evaluate_retrieval) k: {}, save_path: {:?}, where_filter: {:?}, where_document: {:?}",
+        k,
+        save_path,
+        where_filter,
+        where_document
+    );
+    let client = state.get_client()?;
+    if !(1..=100).contains(&k) {
+        return Err("k must be between 1 and 100".to_string());
+    }
+
+    let mut cases = golden_set.parse().await.map_err(|e| {
+        log::error!("(evaluate_retrieval) {}", e);
+        e
+    })?;
+    let where_clause = build_where_filter(where_filter.clone(), where_document.clone())?;
+
+    let collection = client.get_collection(collection_name).await.map_err(|e| {
+        log::error!("(evaluate_retrieval) Error fetching collection: {}", e);
+        format!("Error fetching collection: {}", e)
+    })?;
+    let dimension = probe_dimension(&collection).await;
+
+    let unembedded: Vec<usize> = cases
+        .iter()
+        .enumerate()
+        .filter(|(_, case)| case.embedding.is_none())
+        .map(|(i, _)| i)
+        .collect();
+    let provider = if unembedded.is_empty() {
+        None
+    } else {
+        Some(state.get_embedding_provider(collection_name).map_err(|e| {
+            log::error!("(evaluate_retrieval) {}", e);
+            e
+        })?)
+    };
+    if let Some(provider) = &provider {
+        for chunk in unembedded.chunks(EVALUATION_BATCH) {
+            let texts: Vec<String> = chunk
+                .iter()
+                .filter_map(|&i| cases.get(i).and_then(|case| case.query.clone()))
+                .collect();
+            let vectors = provider.embed(&texts).await.map_err(|e| {
+                log::error!("(evaluate_retrieval) Error embedding queries: {}", e);
+                format!("Error embedding queries: {}", e)
+            })?;
+            if vectors.len() != texts.len() {
+                return Err(format!(
+                    "Embedding provider returned {} vector(s) for {} queries",
+                    vectors.len(),
+                    texts.len()
+                ));
+            }
+            for (&i, vector) in chunk.iter().zip(vectors) {
+                if let Some(case) = cases.get_mut(i) {
+                    case.embedding = Some(vector);
+                }
+            }
+        }
+    }
+
+    let mut embeddings = Vec::with_capacity(cases.len());
+    for case in &mut cases {
+        let embedding = case.embedding.take().unwrap_or_default();
+        validate_vector(&embedding, dimension).map_err(|e| {
+            format!(
+                "Invalid embedding for query {}: {}",
+                case.id.as_deref().unwrap_or_default(),
+                e
+            )
+        })?;
+        embeddings.push(embedding);
+    }
+
+    let mut retrieved: Vec<Vec<String>> = Vec::with_capacity(cases.len());
+    for chunk in embeddings.chunks(EVALUATION_BATCH) {
+        let response = collection
+            .query(
+                chunk.to_vec(),
+                Some(k as u32),
+                where_clause.clone(),
+                None,
+                Some(IncludeList(vec![Include::Distance])),
+            )
+            .await
+            .map_err(|e| {
+                log::error!("(evaluate_retrieval) Error querying collection: {}", e);
+                format!("Error querying collection: {}", e)
+            })?;
+        retrieved.extend(response.ids);
+        let _ = on_progress.send(ScanProgress {
+            scanned: retrieved.len(),
+            total: Some(cases.len() as u32),
+        });
+    }
+
+    let cases: Vec<QueryEvaluation> = cases
+        .into_iter()
+        .zip(retrieved)
+        .map(|(case, retrieved)| evaluation::score(case, retrieved, k))
+        .collect();
+    let queries = cases.len();
+    let mean = |metric: fn(&QueryEvaluation) -> f64| {
+        cases.iter().map(metric).sum::<f64>() / queries.max(1) as f64
+    };
+    let report = RetrievalEvaluation {
+        collection: collection_name.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        k,
+        where_filter,
+        where_document,
+        provider: provider.and_then(|provider| serde_json::to_value(provider).ok()),
+        queries,
+        hit_rate: mean(|case| if case.rank.is_some() { 1.0 } else { 0.0 }),
+        mrr: mean(|case| case.reciprocal_rank),
+        ndcg: mean(|case| case.ndcg),
+        recall: mean(|case| case.recall),
+        cases,
+    };
+
+    if let Some(path) = save_path {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("Error serializing results: {}", e))?;
+        let write_path = path.clone();
+        tauri::async_runtime::spawn_blocking(move || std::fs::write(write_path, json))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|written| written.map_err(|e| e.to_string()))
+            .map_err(|e| {
+                log::error!(
+                    "(evaluate_retrieval) Error saving results to {}: {}",
+                    path,
+                    e
+                );
+                format!("Error saving results to {}: {}", path, e)
+            })?;
+    }
+
+    Ok(report)
+}
+
+/// Reads a result saved by `evaluate_retrieval`.
+#[tauri::command]
+async fn load_evaluation(path: &str) -> Result<RetrievalEvaluation, String> {
+    log::info!("(load_evaluation) Loading evaluation from: {}", path);
+    let read_path = path.to_string();
+    let json = tauri::async_runtime::spawn_blocking(move || std::fs::read_to_string(read_path))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|read| read.map_err(|e| e.to_string()))
+        .map_err(|e| {
+            log::error!("(load_evaluation) Error reading {}: {}", path, e);
+            format!("Error reading {}: {}", path, e)
+        })?;
+    serde_json::from_str(&json).map_err(|e| format!("Invalid evaluation file {}: {}", path, e))
+}
+
+#[tauri::command]
+async fn create_collection(
+    collection_name: &str,
+    metadata: Option<Value>,
+    state: State<'_, AppState>,
+) -> Result<Value, String> {
+    log::info!(
+        "(create_collection) Creating collection: {} with metadata: {:?}",
+        collection_name,
+        metadata
+    );
+    let client = state.get_client()?;
+    state.invalidate_row_counts(collection_name);
+
+    let collection_metadata: Option<Metadata> = metadata
+        .map(|m| {
+            let Value::Object(obj) = m else {
+                return Err("Collection metadata must be an object".to_string());
+            };
+            obj.into_iter()
+                .map(|(k, v)| {
+                    let value = json_to_metadata_value(&k, v)?;
+                    Ok((k, value))
+                })
+                .collect::<Result<Metadata, String>>()
+        })
+        .transpose()
+        .map_err(|e| {
+            log::error!("(create_collection) Invalid metadata: {}", e);
+            e
+        })?;
+    log::debug!(
+        "(create_collection) Creating collection with name: {} and metadata: {:?}",
+        collection_name,
+        collection_metadata,
+    );
+    let result = client
+        .get_or_create_collection(collection_name, None, collection_metadata)
+        .await;
+
+    if result.is_err() {
+        log::error!(
+            "(create_collection) Error creating collection: {}",
+            result.as_ref().err().unwrap()
+        );
+        return Err(format!(
+            "Error creating collection: {}",
+            result.err().unwrap()
+        ));
+    }
+
+    let collection = result.unwrap();
+
+    Ok(json!({
+        "id": collection.id(),
+        "name": collection.name(),
+        "metadata": collection.metadata()
+    }))
+}
+
+#[tauri::command]
+async fn delete_collection(
+    collection_names: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    log::info!(
+        "(delete_collection) Deleting collection: {:?}",
+        collection_names
+    );
+    let client = state.get_client()?;
+
+    let mut errors = vec![];
+    for collection_name in &collection_names {
+        state.invalidate_row_counts(collection_name);
+        if let Err(e) = client.delete_collection(collection_name).await {
+            errors.push(e.to_string());
+        }
+    }
+
+    if !errors.is_empty() {
+        log::error!(
+            "(delete_collection) Error deleting collections: {}",
+            errors.join(", ")
+        );
+        return Err(format!("Error deleting collections: {}", errors.join(", ")));
+    }
+
+    Ok(())
+}
+
+fn get_current_date_string() -> String {
+    use chrono::Local;
+
+    let current_date = Local::now().format("%Y-%m-%d").to_string();
+
+    current_date
+}
+
+fn get_github_issue_body(environment: &Environment) -> String {
+    format!("#### Current Behavior
+<!-- A clear and concise description of the behavior. -->
+
+#### Expected Behavior
+<!-- A clear and concise description of what you expected to happen. -->
+
+#### Additional context/Screenshots
+<!-- Add any other context about the problem here. If applicable, add screenshots to help explain. -->
+
+#### Possible Solution
+<!--- Only if you have suggestions on a fix for the bug -->
+
+#### Environment
+- ChromaDB version: {}
+- Chromamind version: {}
+- Operating system: {} {}
+", environment.chroma_version, environment.chromamind_version, environment.os_name, environment.os_version)
+}
+
+fn create_github_issue_url(body: &str) -> String {
+    let escaped = urlencoding::encode(body).replace("%20", "+");
+
+    format!(
+        "https://github.com/probaku1234/chromamind/issues/new?template={}&body={}",
+        urlencoding::encode("bug_report.md"),
+        escaped
+    )
+    .chars()
+    .collect()
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    #[cfg(debug_assertions)]
+    let max_log_level = log::LevelFilter::Debug;
+
+    #[cfg(not(debug_assertions))]
+    let max_log_level = log::LevelFilter::Info;
+
+    tauri::Builder::default()
+        .setup(|_app| {
+            // set timeout for minreq requests to 10 seconds
+            // this is to prevent hanging requests
+            env::set_var("MINREQ_TIMEOUT", TIMEOUT.to_string());
+            Ok(())
+        })
+        .manage(AppState {
+            client: Mutex::new(None),
+            http: Mutex::new(None),
+            embedding_providers: Mutex::new(HashMap::new()),
+            row_counts: Mutex::new(HashMap::new()),
+            row_count_generation: AtomicU64::new(0),
+            active_counts: Mutex::new(HashMap::new()),
+        })
+        .plugin(tauri_plugin_shell::init())
+        .plugin(if cfg!(debug_assertions) {
+            tauri_plugin_devtools::init()
+        } else {
+            tauri_plugin_log::Builder::new()
+                .timezone_strategy(tauri_plugin_log::TimezoneStrategy::UseLocal)
+                .level(max_log_level)
+                .clear_targets()
+                // logging to stderr, file and file name with current date
+                .targets([
+                    Target::new(TargetKind::Stderr),
+                    Target::new(TargetKind::LogDir {
+                        file_name: Some(get_current_date_string()),
+                    }),
+                ])
+                .build()
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            create_client,
+            health_check,
+            create_window,
+            get_chroma_version,
+            reset_chroma,
+            fetch_embeddings,
+            fetch_collection_data,
+            fetch_row_count,
+            fetch_collections,
+            check_tenant_and_database,
+            create_collection,
+            delete_collection,
+            fetch_embedding,
+            update_record_metadata,
+            delete_records,
+            update_record_document,
+            update_record_embedding,
+            patch_metadata_where,
+            preview_delete_where,
+            delete_records_where,
+            validate_filter,
+            query_similar,
+            query_by_vector,
+            set_embedding_provider,
+            get_embedding_provider,
+            query_by_text,
+            check_search_support,
+            search,
+            load_uri_thumbnail,
+            count_records,
+            cancel_row_count,
+            stream_embeddings,
+            fetch_document,
+            fetch_embeddings_vectors,
+            profile_metadata,
+            facet_counts,
+            metadata_histogram,
+            scan_embeddings,
+            find_duplicates,
+            project_embeddings,
+            cluster_embeddings,
+            write_cluster_ids,
+            compare_embeddings,
+            measure_recall,
+            evaluate_retrieval,
+            load_evaluation,
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
+
+#[allow(clippy::all)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chroma::client::ChromaAuthMethod;
+    use chroma::{ChromaHttpClient, ChromaHttpClientOptions};
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+    use tauri::{
+        ipc::InvokeResponseBody,
+        test::{mock_builder, mock_context, noop_assets, MockRuntime},
+        WebviewWindow,
+    };
+    use testcontainers::{
+        core::{IntoContainerPort, WaitFor},
+        runners::SyncRunner,
+        Container, GenericImage,
+    };
+
+
+    enum TauriCommand {
+        Greet,
+        CreateClient,
+        HealthCheck,
+        GetChromaVersion,
+        ResetChroma,
+        FetchEmbeddings,
+        FetchCollectionData,
+        FetchRowCount,
+        FetchCollections,
+        CheckTenantAndDatabase,
+        CreateCollection,
+        DeleteCollection,
+        FetchEmbedding,
+        UpdateRecordMetadata,
+        DeleteRecords,
+        UpdateRecordDocument,
+        UpdateRecordEmbedding,
+        PatchMetadataWhere,
+        PreviewDeleteWhere,
+        DeleteRecordsWhere,
+        ValidateFilter,
+        QuerySimilar,
+        QueryByVector,
+        SetEmbeddingProvider,
+        GetEmbeddingProvider,
+        QueryByText,
+        CheckSearchSupport,
+        Search,
+        LoadUriThumbnail,
+        CountRecords,
+        CancelRowCount,
+        StreamEmbeddings,
+        FetchDocument,
+        FetchEmbeddingsVectors,
+        ProfileMetadata,
+        FacetCounts,
+        MetadataHistogram,
+        ScanEmbeddings,
+        FindDuplicates,
+        ProjectEmbeddings,
+        ClusterEmbeddings,
+        WriteClusterIds,
+        CompareEmbeddings,
+        MeasureRecall,
+        EvaluateRetrieval,
+        LoadEvaluation,
+    }
+
+    impl TauriCommand {
+        fn as_str(&self) -> &str {
+            match self {
+                TauriCommand::Greet => "greet",
+                TauriCommand::CreateClient => "create_client",
+                TauriCommand::HealthCheck => "health_check",
+                TauriCommand::GetChromaVersion => "get_chroma_version",
+                TauriCommand::ResetChroma => "reset_chroma",
+                TauriCommand::FetchEmbeddings => "fetch_embeddings",
+                TauriCommand::FetchCollectionData => "fetch_collection_data",
+                TauriCommand::FetchRowCount => "fetch_row_count",
+                TauriCommand::FetchCollections => "fetch_collections",
+                TauriCommand::CheckTenantAndDatabase => "check_tenant_and_database",
+                TauriCommand::CreateCollection => "create_collection",
+                TauriCommand::DeleteCollection => "delete_collection",
+                TauriCommand::FetchEmbedding => "fetch_embedding",
+                TauriCommand::UpdateRecordMetadata => "update_record_metadata",
+                TauriCommand::DeleteRecords => "delete_records",
+                TauriCommand::UpdateRecordDocument => "update_record_document",
+                TauriCommand::UpdateRecordEmbedding => "update_record_embedding",
+                TauriCommand::PatchMetadataWhere => "patch_metadata_where",
+                TauriCommand::PreviewDeleteWhere => "preview_delete_where",
+                TauriCommand::DeleteRecordsWhere => "delete_records_where",
+                TauriCommand::ValidateFilter => "validate_filter",
+                TauriCommand::QuerySimilar => "query_similar",
+                TauriCommand::QueryByVector => "query_by_vector",
+                TauriCommand::SetEmbeddingProvider => "set_embedding_provider",
+                TauriCommand::GetEmbeddingProvider => "get_embedding_provider",
+                TauriCommand::QueryByText => "query_by_text",
+                TauriCommand::CheckSearchSupport => "check_search_support",
+                TauriCommand::Search => "search",
+                TauriCommand::LoadUriThumbnail => "load_uri_thumbnail",
+                TauriCommand::CountRecords => "count_records",
+                TauriCommand::CancelRowCount => "cancel_row_count",
+                TauriCommand::StreamEmbeddings => "stream_embeddings",
+                TauriCommand::FetchDocument => "fetch_document",
+                TauriCommand::FetchEmbeddingsVectors => "fetch_embeddings_vectors",
+                TauriCommand::ProfileMetadata => "profile_metadata",
+                TauriCommand::FacetCounts => "facet_counts",
+                TauriCommand::MetadataHistogram => "metadata_histogram",
+                TauriCommand::ScanEmbeddings => "scan_embeddings",
+                TauriCommand::FindDuplicates => "find_duplicates",
+                TauriCommand::ProjectEmbeddings => "project_embeddings",
+                TauriCommand::ClusterEmbeddings => "cluster_embeddings",
+                TauriCommand::WriteClusterIds => "write_cluster_ids",
+                TauriCommand::CompareEmbeddings => "compare_embeddings",
+                TauriCommand::MeasureRecall => "measure_recall",
+                TauriCommand::EvaluateRetrieval => "evaluate_retrieval",
+                TauriCommand::LoadEvaluation => "load_evaluation",
+            }
+        }
+    }
+
+    fn get_command_response(
+        webview: &WebviewWindow<MockRuntime>,
+        command: &str,
+        body: Value,
+    ) -> Result<InvokeResponseBody, Value> {
+        tauri::test::get_ipc_response(
+            webview,
+            tauri::webview::InvokeRequest {
+                cmd: command.into(),
+                callback: tauri::ipc::CallbackFn(0),
+                error: tauri::ipc::CallbackFn(1),
+                url: "http://tauri.localhost".parse().unwrap(),
+                body: tauri::ipc::InvokeBody::Json(body),
+                headers: Default::default(),
+                invoke_key: tauri::test::INVOKE_KEY.to_string(),
+            },
+        )
+    }
+
+    /// Decodes a binary command response of little-endian `f32`s.
+    fn decode_f32_le(body: InvokeResponseBody) -> Vec<f32> {
+        match body {
+            InvokeResponseBody::Raw(bytes) => bytes
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+            InvokeResponseBody::Json(json) => panic!("expected a binary response, got {}", json),
+        }
+    }
+
+    fn before_each<R: tauri::Runtime>(builder: tauri::Builder<R>) -> tauri::App<R> {
+        builder
+            .manage(AppState {
+                client: Mutex::new(None),
+                http: Mutex::new(None),
+                embedding_providers: Mutex::new(HashMap::new()),
+                row_counts: Mutex::new(HashMap::new()),
+                row_count_generation: AtomicU64::new(0),
+                active_counts: Mutex::new(HashMap::new()),
+            })
+            .invoke_handler(tauri::generate_handler![
+                greet,
+                create_client,
+                health_check,
+                // create_window,
+                get_chroma_version,
+                reset_chroma,
+                fetch_embeddings,
+                fetch_collection_data,
+                fetch_row_count,
+                fetch_collections,
+                check_tenant_and_database,
+                create_collection,
+                delete_collection,
+                fetch_embedding,
+                update_record_metadata,
+                delete_records,
+                update_record_document,
+                update_record_embedding,
+                patch_metadata_where,
+                preview_delete_where,
+                delete_records_where,
+                validate_filter,
+                query_similar,
+                query_by_vector,
+                set_embedding_provider,
+                get_embedding_provider,
+                query_by_text,
+                check_search_support,
+                search,
+                load_uri_thumbnail,
+                count_records,
+                cancel_row_count,
+                stream_embeddings,
+                fetch_document,
+                fetch_embeddings_vectors,
+                profile_metadata,
+                facet_counts,
+                metadata_histogram,
+                scan_embeddings,
+                find_duplicates,
+                project_embeddings,
+                cluster_embeddings,
+                write_cluster_ids,
+                compare_embeddings,
+                measure_recall,
+                evaluate_retrieval,
+                load_evaluation,
+            ])
+            // remove the string argument to use your app's config file
+            .build(mock_context(noop_assets()))
+            .expect("failed to build app")
+    }
+
+    fn create_chroma_container() -> Container<GenericImage> {
+        start_chroma_container("1.0.16")
+    }
+
+    /// A Chroma release with the Search API and array and sparse vector
+    /// metadata, none of which 1.0.16 supports.
+    fn create_recent_chroma_container() -> Container<GenericImage> {
+        start_chroma_container("1.5.0")
+    }
+
+    fn start_chroma_container(tag: &str) -> Container<GenericImage> {
+        GenericImage::new("chromadb/chroma", tag)
+            .with_exposed_port(8000.tcp())
+            // .with_wait_for(WaitFor::Http(HttpWaitStrategy::new("/api/v1/heartbeat").with_port(8000.tcp())))
+            .with_wait_for(WaitFor::message_on_stdout("Connect to Chroma at:"))
+            .start()
+            .expect("ChromaDB should start")
+    }
+
+    // fn crate_chroma_container_with_auth(
+    //     auth_method: chromadb::v1::client::ChromaAuthMethod,
+    // ) -> Container<GenericImage> {
+    //     let image = GenericImage::new("chromadb/chroma", "0.5.5")
+    //         .with_exposed_port(8000.tcp())
+    //         .with_wait_for(WaitFor::message_on_stdout("Application startup complete."));
+    //
+    //     let image = match auth_method {
+    //         chromadb::v1::client::ChromaAuthMethod::BasicAuth { username, password } => image
+    //             .with_env_var(
+    //                 "CHROMA_SERVER_AUTHN_PROVIDER",
+    //                 "chromadb.auth.basic_authn.BasicAuthenticationServerProvider",
+    //             )
+    //             .with_env_var(
+    //                 "CHROMA_SERVER_AUTHN_CREDENTIALS",
+    //                 format!("{}:{}", username, password),
+    //             ),
+    //         chromadb::v1::client::ChromaAuthMethod::TokenAuth { header, token } => {
+    //             let header = match header {
+    //                 chromadb::v1::client::ChromaTokenHeader::Authorization => "Authorization",
+    //                 chromadb::v1::client::ChromaTokenHeader::XChromaToken => "X-Chroma-Token",
+    //             };
+    //
+    //             image
+    //                 .with_env_var(
+    //                     "CHROMA_SERVER_AUTHN_PROVIDER",
+    //                     "chromadb.auth.token_authn.TokenAuthenticationServerProvider",
+    //                 )
+    //                 .with_env_var("CHROMA_SERVER_AUTHN_CREDENTIALS", token)
+    //                 .with_env_var("CHROMA_AUTH_TOKEN_TRANSPORT_HEADER", header)
+    //         }
+    //         chromadb::v1::client::ChromaAuthMethod::None => image.with_env_var("name", "value"),
+    //     };
+    //
+    //     image.start().expect("ChromaDB started")
+    // }
+
+    #[test]
+    fn test_chroma_container() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        println!("ChromaDB running at {}:{}", host, port);
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: format!("http://{}:{}", host, port)
+                .as_str()
+                .parse()
+                .unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let health_check_result = rt.block_on(client.heartbeat());
+        assert!(health_check_result.is_ok(), "ChromaDB not running");
+    }
+
+    #[test]
+    fn test_create_client_local() {
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        // Invalid config (missing required fields) should fail deserialization
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({ "config": { "mode": "local" } }),
+        );
+        assert!(
+            res.is_err(),
+            "create_client should fail with incomplete config"
+        );
+
+        // Valid local config should succeed
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        // Client is initialized: health_check should succeed against the real container
+        let res = get_command_response(&webview, TauriCommand::HealthCheck.as_str(), json!({}));
+        assert!(
+            res.is_ok(),
+            "health_check failed after local create_client: {:?}",
+            res.err()
+        );
+    }
+
+    #[test]
+    fn test_create_client_cloud() {
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        // Valid cloud config with a fake URL — create_client should succeed (no network call yet)
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "cloud",
+                    "url": "https://fake.cloud.example.invalid",
+                    "apiKey": "test-api-key",
+                    "database": "test-db"
+                }
+            }),
+        );
+        assert!(
+            res.is_ok(),
+            "create_client failed for cloud config: {:?}",
+            res.err()
+        );
+
+        // health_check must fail with a connection error, NOT "ChromaDB client not initialized"
+        // — this proves the client was set up and auth was configured
+        let res = get_command_response(&webview, TauriCommand::HealthCheck.as_str(), json!({}));
+        assert!(res.is_err(), "health_check should fail against fake URL");
+        assert_ne!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "cloud client should be initialized; expected a connection error"
+        );
+    }
+
+    #[test]
+    fn test_greet() {
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Greet.as_str(),
+            json!({
+                "name": connect_url
+            }),
+        )
+        .map(|res| res.deserialize::<String>().unwrap());
+
+        assert!(res.is_ok(), "greet failed: {:?}", res.err());
+    }
+
+    #[test]
+    fn test_health_check() {
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(&webview, TauriCommand::HealthCheck.as_str(), json!({}));
+        assert!(res.is_err(), "health_check should fail");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "health_check failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let res = get_command_response(&webview, TauriCommand::HealthCheck.as_str(), json!({}));
+
+        assert!(res.is_ok(), "health_check failed: {:?}", res.err());
+        // assert if res is u64
+        let res = res.unwrap().deserialize::<u64>();
+        assert!(
+            res.is_ok(),
+            "health_check result is not u64: {:?}",
+            res.err()
+        );
+    }
+
+    #[test]
+    fn test_check_tenant_and_database() {
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CheckTenantAndDatabase.as_str(),
+            json!({
+                "database": "default_database"
+            }),
+        );
+
+        assert!(res.is_err(), "check_tenant_and_database should fail");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "check_tenant_and_database failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CheckTenantAndDatabase.as_str(),
+            json!({
+                "database": "default_database"
+            }),
+        );
+
+        assert!(
+            res.is_ok(),
+            "check_tenant_and_database failed: {:?}",
+            res.err()
+        );
+        // assert if res is bool
+        let res = res.unwrap().deserialize::<bool>();
+        assert!(
+            res.is_ok(),
+            "check_tenant_and_database result is not bool: {:?}",
+            res.err()
+        );
+        assert!(res.unwrap(), "check_tenant_and_database result is not true");
+    }
+
+    #[test]
+    fn test_get_chroma_version() {
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res =
+            get_command_response(&webview, TauriCommand::GetChromaVersion.as_str(), json!({}));
+
+        assert!(res.is_err(), "get_chroma_version should fail");
+        assert_eq!(
+            res.err().unwrap(),
+            "No http context",
+            "get_chroma_version failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let res =
+            get_command_response(&webview, TauriCommand::GetChromaVersion.as_str(), json!({}));
+
+        assert!(res.is_ok(), "get_chroma_version failed: {:?}", res.err());
+        // assert if res is string
+        let res = res.unwrap().deserialize::<String>();
+        assert!(
+            res.is_ok(),
+            "get_chroma_version result is not string: {:?}",
+            res.err()
+        );
+
+        let actual = res.unwrap();
+        assert!(
+            actual.split('.').count() >= 2 && actual.chars().any(|c| c.is_ascii_digit()),
+            "version is not semver-shaped: {actual:?}"
+        );
+    }
+
+    #[test]
+    #[should_panic] // FIXME: this test panics because the ChromaDB container does not support the reset command
+    fn test_reset_chroma() {
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(&webview, TauriCommand::ResetChroma.as_str(), json!({}));
+
+        assert!(res.is_err(), "reset_chroma should fail");
+        assert_eq!(
+            res.err().unwrap(),
+            "No client found",
+            "reset_chroma failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let res = get_command_response(&webview, TauriCommand::ResetChroma.as_str(), json!({}));
+
+        assert!(res.is_ok(), "reset_chroma failed: {:?}", res.err());
+        // assert if res is bool
+        let res = res.unwrap().deserialize::<bool>();
+        assert!(
+            res.is_ok(),
+            "reset_chroma result is not bool: {:?}",
+            res.err()
+        );
+    }
+
+    #[test]
+    fn test_fetch_collections() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res =
+            get_command_response(&webview, TauriCommand::FetchCollections.as_str(), json!({}));
+
+        assert!(res.is_err(), "fetch_collections should fail");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "fetch_collections failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: format!("http://{}:{}", host, port)
+                .as_str()
+                .parse()
+                .unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection";
+        rt.block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let res =
+            get_command_response(&webview, TauriCommand::FetchCollections.as_str(), json!({}));
+
+        assert!(res.is_ok(), "fetch_collections failed: {:?}", res.err());
+        // assert if res is Vec<Value>
+        let res = res.unwrap().deserialize::<Vec<Value>>();
+        assert!(
+            res.is_ok(),
+            "fetch_collections result is not Vec<Value>: {:?}",
+            res.err()
+        );
+        let res = res.unwrap();
+        assert!(!res.is_empty(), "fetch_collections result is empty");
+        let collection_data = res.first().unwrap();
+        let expected = collection_data.get("name").unwrap().as_str().unwrap();
+        assert_eq!(
+            expected, collection_name,
+            "fetch_collections result is not equal to expected"
+        );
+    }
+
+    #[test]
+    fn test_fetch_row_count() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchRowCount.as_str(),
+            json!({
+                "collectionName": "test_collection"
+            }),
+        );
+
+        assert!(res.is_err(), "fetch_row_count should fail");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "fetch_row_count failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: format!("http://{}:{}", host, port)
+                .as_str()
+                .parse()
+                .unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collecton_name = "test_collection";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collecton_name, None, None))
+            .unwrap();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string()],
+            vec![vec![0.1, 0.2], vec![0.3, 0.4]],
+            Some(vec![
+                Some("First document".to_string()),
+                Some("Second document".to_string()),
+            ]),
+            None,
+            None,
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchRowCount.as_str(),
+            json!({
+                "collectionName": collecton_name
+            }),
+        );
+
+        assert!(res.is_ok(), "fetch_row_count failed: {:?}", res.err());
+
+        let res = res.unwrap().deserialize::<usize>();
+        assert!(
+            res.is_ok(),
+            "fetch_row_count result is not usize: {:?}",
+            res.err()
+        );
+        let res = res.unwrap();
+        assert_eq!(res, 2, "fetch_row_count result is not equal to expected");
+    }
+
+    #[test]
+    fn test_fetch_embeddings() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddings.as_str(),
+            json!({
+                "collectionName": "test_collection",
+                "limit": 0,
+                "offset": 0
+            }),
+        );
+
+        assert!(res.is_err(), "fetch_embeddings should fail");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "fetch_embeddings failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: format!("http://{}:{}", host, port)
+                .as_str()
+                .parse()
+                .unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collecton_name = "test_collection";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collecton_name, None, None))
+            .unwrap();
+
+        let ids = vec!["doc1".to_string(), "doc2".to_string()];
+        rt.block_on(collection.add(
+            ids.clone(),
+            vec![vec![0.1, 0.2], vec![0.3, 0.4]],
+            Some(vec![
+                Some("First document".to_string()),
+                Some("Second document".to_string()),
+            ]),
+            None,
+            None,
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddings.as_str(),
+            json!({
+                "collectionName": collecton_name,
+                "limit": 5,
+                "offset": 0
+            }),
+        );
+
+        assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
+        // assert if res is Vec<EmbeddingData>
+        let res = res.unwrap().deserialize::<Vec<EmbeddingData>>();
+        assert!(
+            res.is_ok(),
+            "fetch_embeddings result is not Vec<EmbeddingData>: {:?}",
+            res.err()
+        );
+
+        let res = res.unwrap();
+        let expected = ids.clone();
+
+        assert_eq!(
+            expected,
+            res.iter().map(|x| x.id.clone()).collect::<Vec<_>>()
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddings.as_str(),
+            json!({
+                "collectionName": collecton_name,
+                "limit": 0,
+                "offset": 0
+            }),
+        );
+
+        assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
+        let res = res.unwrap().deserialize::<Vec<EmbeddingData>>();
+        let res = res.unwrap();
+        assert!(
+            res.is_empty(),
+            "fetch_collection_data should return empty vec since limit is 0"
+        );
+    }
+
+    #[test]
+    fn test_fetch_embeddings_and_row_count_filters() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "filter_collection";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let ids = vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()];
+        let metadatas: Vec<Option<Metadata>> = vec![
+            Some(HashMap::from([
+                ("page".to_string(), MetadataValue::Int(1)),
+                ("category".to_string(), MetadataValue::Str("a".to_string())),
+            ])),
+            Some(HashMap::from([
+                ("page".to_string(), MetadataValue::Int(5)),
+                ("category".to_string(), MetadataValue::Str("b".to_string())),
+            ])),
+            Some(HashMap::from([
+                ("page".to_string(), MetadataValue::Int(10)),
+                ("category".to_string(), MetadataValue::Str("a".to_string())),
+            ])),
+        ];
+        rt.block_on(collection.add(
+            ids.clone(),
+            vec![vec![0.1, 0.2], vec![0.3, 0.4], vec![0.5, 0.6]],
+            Some(vec![
+                Some("apple pie".to_string()),
+                Some("banana bread".to_string()),
+                Some("cherry cake".to_string()),
+            ]),
+            None,
+            Some(metadatas),
+        ))
+        .unwrap();
+
+        // Helper to fetch ids for a given fetch_embeddings payload.
+        let fetch_ids = |payload: serde_json::Value| -> Vec<String> {
+            let res =
+                get_command_response(&webview, TauriCommand::FetchEmbeddings.as_str(), payload);
+            assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
+            let mut ids = res
+                .unwrap()
+                .deserialize::<Vec<EmbeddingData>>()
+                .unwrap()
+                .into_iter()
+                .map(|x| x.id)
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        // Metadata filter: page > 4 -> doc2, doc3
+        assert_eq!(
+            fetch_ids(json!({
+                "collectionName": collection_name,
+                "limit": 100,
+                "offset": 0,
+                "whereFilter": { "page": { "$gt": 4 } }
+            })),
+            vec!["doc2".to_string(), "doc3".to_string()]
+        );
+
+        // Metadata filter: category == "a" -> doc1, doc3
+        assert_eq!(
+            fetch_ids(json!({
+                "collectionName": collection_name,
+                "limit": 100,
+                "offset": 0,
+                "whereFilter": { "category": { "$eq": "a" } }
+            })),
+            vec!["doc1".to_string(), "doc3".to_string()]
+        );
+
+        // Composite $and: page >= 5 AND category == "a" -> doc3
+        assert_eq!(
+            fetch_ids(json!({
+                "collectionName": collection_name,
+                "limit": 100,
+                "offset": 0,
+                "whereFilter": { "$and": [
+                    { "page": { "$gte": 5 } },
+                    { "category": { "$eq": "a" } }
+                ]}
+            })),
+            vec!["doc3".to_string()]
+        );
+
+        // ID lookup -> doc1
+        assert_eq!(
+            fetch_ids(json!({
+                "collectionName": collection_name,
+                "limit": 100,
+                "offset": 0,
+                "ids": ["doc1"]
+            })),
+            vec!["doc1".to_string()]
+        );
+
+        // Unfiltered row count -> 3
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchRowCount.as_str(),
+            json!({ "collectionName": collection_name }),
+        );
+        assert_eq!(res.unwrap().deserialize::<u32>().unwrap(), 3);
+
+        // Filtered row count: page > 4 -> 2
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchRowCount.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "whereFilter": { "page": { "$gt": 4 } }
+            }),
+        );
+        assert_eq!(res.unwrap().deserialize::<u32>().unwrap(), 2);
+    }
+
+    #[test]
+    #[allow(clippy::indexing_slicing)]
+    fn test_fetch_embedding() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        // Should fail without client initialized
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbedding.as_str(),
+            json!({
+                "collectionName": "test_collection",
+                "id": "doc1"
+            }),
+        );
+        assert!(res.is_err(), "fetch_embedding should fail without client");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "fetch_embedding failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: format!("http://{}:{}", host, port)
+                .as_str()
+                .parse()
+                .unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_embed";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let ids = vec!["doc1".to_string(), "doc2".to_string()];
+        let embeddings = vec![
+            vec![0.1_f32, 0.2_f32, 0.3_f32],
+            vec![0.4_f32, 0.5_f32, 0.6_f32],
+        ];
+        rt.block_on(collection.add(
+            ids.clone(),
+            embeddings.clone(),
+            Some(vec![
+                Some("First document".to_string()),
+                Some("Second document".to_string()),
+            ]),
+            None,
+            None,
+        ))
+        .unwrap();
+
+        // Fetch embedding for doc1
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbedding.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1"
+            }),
+        );
+        assert!(res.is_ok(), "fetch_embedding failed: {:?}", res.err());
+        let embedding = decode_f32_le(res.unwrap());
+        assert_eq!(
+            embedding.len(),
+            3,
+            "fetch_embedding returned wrong dimension count"
+        );
+        assert!(
+            (embedding[0] - 0.1_f32).abs() < 1e-5_f32,
+            "embedding[0] mismatch"
+        );
+        assert!(
+            (embedding[1] - 0.2_f32).abs() < 1e-5_f32,
+            "embedding[1] mismatch"
+        );
+        assert!(
+            (embedding[2] - 0.3_f32).abs() < 1e-5_f32,
+            "embedding[2] mismatch"
+        );
+
+        // Fetch embedding for unknown id should error
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbedding.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "nonexistent_id"
+            }),
+        );
+        assert!(res.is_err(), "fetch_embedding should fail for unknown id");
+    }
+
+    #[test]
+    fn test_fetch_collection_data() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchCollectionData.as_str(),
+            json!({
+                "collectionName": "test_collection"
+            }),
+        );
+
+        assert!(res.is_err(), "fetch_collection_data should fail");
+
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "fetch_collection_data failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: format!("http://{}:{}", host, port)
+                .as_str()
+                .parse()
+                .unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection";
+        let collection = rt
+            .block_on(client.get_or_create_collection(
+                collection_name,
+                None,
+                Some(HashMap::from([(
+                    "foo".to_string(),
+                    MetadataValue::Str("bar".to_string()),
+                )])),
+            ))
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchCollectionData.as_str(),
+            json!({
+                "collectionName": collection_name
+            }),
+        );
+
+        assert!(res.is_ok(), "fetch_collection_data failed: {:?}", res.err());
+
+        // assert if res is Value
+        let res = res.unwrap().deserialize::<Value>();
+        assert!(
+            res.is_ok(),
+            "fetch_collection_data result is not Value: {:?}",
+            res.err()
+        );
+
+        let res = res.unwrap();
+        let expected = json!({
+            "id": collection.id(),
+            "metadata": collection.metadata(),
+            // TODO: need to get actual config
+            // "configuration": collection.configuration_json(),
+            "configuration": {},
+            "dimension": None::<u32>,
+        });
+
+        assert_eq!(
+            expected, res,
+            "fetch_collection_data result is not equal to expected"
+        );
+    }
+
+    #[test]
+    fn test_create_collection() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateCollection.as_str(),
+            json!({
+                "collectionName": "test_collection",
+                "metadata": {
+                    "foo": "bar"
+                }
+            }),
+        );
+
+        assert!(res.is_err(), "fetch_collection_data should fail");
+
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "fetch_collection_data failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        // Invalid metadata is an error rather than silently dropped.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateCollection.as_str(),
+            json!({
+                "collectionName": "test_collection_invalid",
+                "metadata": { "foo": "bar", "tags": [] }
+            }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Empty array for key tags, Chroma cannot store an empty list"
+        );
+
+        let collection_name: &str = "test_collection";
+        let metadata = json!({
+            "foo": "bar"
+        });
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateCollection.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "metadata": metadata
+            }),
+        );
+
+        assert!(res.is_ok(), "create_collection failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: format!("http://{}:{}", host, port)
+                .as_str()
+                .parse()
+                .unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+        let collection = rt.block_on(client.get_collection(collection_name)).unwrap();
+
+        assert_eq!(collection_name, collection.name());
+        assert_eq!(
+            &Some(HashMap::from([(
+                "foo".to_string(),
+                MetadataValue::Str("bar".to_string())
+            )])),
+            collection.metadata()
+        );
+        assert!(rt
+            .block_on(client.get_collection("test_collection_invalid"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_delete_collection() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::DeleteCollection.as_str(),
+            json!({
+                "collectionNames": vec!["test_collection"]
+            }),
+        );
+
+        assert!(res.is_err(), "delete_collection should fail");
+
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "delete_collection failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: format!("http://{}:{}", host, port)
+                .as_str()
+                .parse()
+                .unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name: &str = "test_collection";
+        rt.block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::DeleteCollection.as_str(),
+            json!({
+                "collectionNames": vec![collection_name]
+            }),
+        );
+
+        assert!(res.is_ok(), "delete_collection failed: {:?}", res.err());
+
+        let collection = rt.block_on(client.get_collection(collection_name));
+
+        assert!(collection.is_err(), "collection should not exist");
+    }
+
+    #[test]
+    fn test_update_record_metadata() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordMetadata.as_str(),
+            json!({
+                "collectionName": "test_collection_update",
+                "id": "doc1",
+                "metadata": {},
+                "removedKeys": Vec::<String>::new(),
+            }),
+        );
+
+        assert!(
+            res.is_err(),
+            "update_record_metadata should fail without a client"
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "update_record_metadata failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_update";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let seed_metadata: Metadata = [
+            ("keep".to_string(), MetadataValue::Str("original".to_string())),
+            ("drop".to_string(), MetadataValue::Str("gone".to_string())),
+            ("score".to_string(), MetadataValue::Int(1)),
+        ]
+        .into_iter()
+        .collect();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string()],
+            vec![vec![0.1_f32, 0.2_f32, 0.3_f32]],
+            Some(vec![Some("First document".to_string())]),
+            None,
+            Some(vec![Some(seed_metadata)]),
+        ))
+        .unwrap();
+
+        // Change a value, add a new key, and drop an existing one in one call.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordMetadata.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "metadata": {
+                    "keep": "updated",
+                    "score": 7,
+                    "added": true,
+                },
+                "removedKeys": vec!["drop".to_string()],
+            }),
+        );
+
+        assert!(
+            res.is_ok(),
+            "update_record_metadata failed: {:?}",
+            res.err()
+        );
+
+        let get_result = rt
+            .block_on(collection.get(
+                Some(vec!["doc1".to_string()]),
+                None,
+                Some(1u32),
+                None,
+                Some(IncludeList(vec![Include::Metadata, Include::Document])),
+            ))
+            .unwrap();
+
+        let metadata = get_result
+            .metadatas
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .flatten()
+            .expect("record should still have metadata");
+
+        assert_eq!(
+            metadata.get("keep"),
+            Some(&MetadataValue::Str("updated".to_string())),
+            "existing key was not updated"
+        );
+        assert_eq!(
+            metadata.get("score"),
+            Some(&MetadataValue::Int(7)),
+            "numeric key was not updated"
+        );
+        assert_eq!(
+            metadata.get("added"),
+            Some(&MetadataValue::Bool(true)),
+            "new key was not added"
+        );
+        assert_eq!(metadata.get("drop"), None, "removed key still present");
+
+        // The document must be left untouched by a metadata-only update.
+        let documents = get_result.documents.unwrap_or_default();
+        assert_eq!(
+            documents.into_iter().next().flatten(),
+            Some("First document".to_string()),
+            "document was modified by a metadata-only update"
+        );
+    }
+
+    #[test]
+    fn test_delete_records() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::DeleteRecords.as_str(),
+            json!({
+                "collectionName": "test_collection_delete_records",
+                "ids": vec!["doc1".to_string()],
+            }),
+        );
+
+        assert!(res.is_err(), "delete_records should fail without a client");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "delete_records failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_delete_records";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
+            vec![
+                vec![0.1_f32, 0.2_f32, 0.3_f32],
+                vec![0.4_f32, 0.5_f32, 0.6_f32],
+                vec![0.7_f32, 0.8_f32, 0.9_f32],
+            ],
+            Some(vec![
+                Some("First".to_string()),
+                Some("Second".to_string()),
+                Some("Third".to_string()),
+            ]),
+            None,
+            None,
+        ))
+        .unwrap();
+
+        // An empty id list must be refused rather than sent as "no filter".
+        let res = get_command_response(
+            &webview,
+            TauriCommand::DeleteRecords.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "ids": Vec::<String>::new(),
+            }),
+        );
+
+        assert!(res.is_err(), "delete_records should reject an empty id list");
+        assert_eq!(
+            res.err().unwrap(),
+            "No record ids provided",
+            "empty id list rejected with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::DeleteRecords.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "ids": vec!["doc1".to_string(), "doc3".to_string()],
+            }),
+        );
+
+        assert!(res.is_ok(), "delete_records failed: {:?}", res.err());
+
+        let get_result = rt
+            .block_on(collection.get(
+                None,
+                None,
+                Some(10u32),
+                None,
+                Some(IncludeList(vec![Include::Document])),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            get_result.ids,
+            vec!["doc2".to_string()],
+            "only the untouched record should remain"
+        );
+    }
+
+    #[test]
+    fn test_update_record_document() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordDocument.as_str(),
+            json!({
+                "collectionName": "test_collection_update_document",
+                "id": "doc1",
+                "document": "Fixed document",
+            }),
+        );
+
+        assert!(
+            res.is_err(),
+            "update_record_document should fail without a client"
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "update_record_document failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_update_document";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let seed_metadata: Metadata = [("keep".to_string(), MetadataValue::Int(1))]
+            .into_iter()
+            .collect();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string()],
+            vec![vec![0.1_f32, 0.2_f32, 0.3_f32]],
+            Some(vec![Some("Frist document".to_string())]),
+            None,
+            Some(vec![Some(seed_metadata)]),
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordDocument.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "document": "First document",
+            }),
+        );
+
+        assert!(
+            res.is_ok(),
+            "update_record_document failed: {:?}",
+            res.err()
+        );
+        let update = res.unwrap().deserialize::<DocumentUpdate>().unwrap();
+        assert!(!update.reembedded, "document should not be re-embedded");
+        assert!(
+            update.embedding_stale,
+            "kept embedding should be reported as stale"
+        );
+
+        let get_result = rt
+            .block_on(collection.get(
+                Some(vec!["doc1".to_string()]),
+                None,
+                Some(1u32),
+                None,
+                Some(IncludeList(vec![
+                    Include::Document,
+                    Include::Metadata,
+                    Include::Embedding,
+                ])),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            get_result
+                .documents
+                .unwrap_or_default()
+                .into_iter()
+                .next()
+                .flatten(),
+            Some("First document".to_string()),
+            "document was not updated"
+        );
+        assert_eq!(
+            get_result
+                .metadatas
+                .unwrap_or_default()
+                .into_iter()
+                .next()
+                .flatten()
+                .and_then(|m| m.get("keep").cloned()),
+            Some(MetadataValue::Int(1)),
+            "metadata was modified by a document-only update"
+        );
+        assert_eq!(
+            get_result.embeddings.unwrap_or_default().into_iter().next(),
+            Some(vec![0.1_f32, 0.2_f32, 0.3_f32]),
+            "embedding was modified without re-embedding"
+        );
+
+        // Re-embedding needs an embedding provider and none is configured.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordDocument.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "document": "First document",
+                "reembed": true,
+            }),
+        );
+
+        assert!(
+            res.is_err(),
+            "update_record_document should fail to re-embed without a provider"
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            format!(
+                "No embedding provider configured for collection: {}",
+                collection_name
+            ),
+            "re-embed rejected with different error"
+        );
+    }
+
+    #[test]
+    fn test_parse_vector_input() {
+        let parse = |input: Value| {
+            tauri::async_runtime::block_on(
+                serde_json::from_value::<VectorInput>(input)
+                    .unwrap()
+                    .parse(),
+            )
+        };
+
+        assert_eq!(
+            parse(json!({ "format": "json", "value": [0.5, -1.0, 2.0] })),
+            Ok(vec![0.5_f32, -1.0_f32, 2.0_f32])
+        );
+        assert_eq!(
+            parse(json!({ "format": "csv", "value": " [0.5, -1,2e0 ] " })),
+            Ok(vec![0.5_f32, -1.0_f32, 2.0_f32])
+        );
+
+        // Little-endian f32 bytes of [0.5, -1.0, 2.0].
+        assert_eq!(
+            parse(json!({ "format": "base64", "value": "AAAAPwAAgL8AAABA" })),
+            Ok(vec![0.5_f32, -1.0_f32, 2.0_f32])
+        );
+
+        assert!(parse(json!({ "format": "csv", "value": "0.5, abc" })).is_err());
+        assert!(parse(json!({ "format": "base64", "value": "AAA=" })).is_err());
+
+        assert!(validate_vector(&[0.1, f32::NAN], None).is_err());
+        assert!(validate_vector(&[0.1, f32::INFINITY], None).is_err());
+        assert!(validate_vector(&[0.1, 0.2], Some(3)).is_err());
+        assert!(validate_vector(&[0.1, 0.2, 0.3], Some(3)).is_ok());
+    }
+
+    #[test]
+    fn test_parse_vector_input_errors() {
+        let parse = |input: Value| {
+            tauri::async_runtime::block_on(
+                serde_json::from_value::<VectorInput>(input)
+                    .unwrap()
+                    .parse(),
+            )
+        };
+
+        assert_eq!(
+            parse(json!({ "format": "csv", "value": "1,,2" })),
+            Err("Empty value at position 1".to_string())
+        );
+        assert_eq!(
+            parse(json!({ "format": "csv", "value": "[1, 2,]" })),
+            Err("Empty value at position 2".to_string())
+        );
+        assert_eq!(
+            parse(json!({ "format": "csv", "value": ",1" })),
+            Err("Empty value at position 0".to_string())
+        );
+        assert!(parse(json!({ "format": "csv", "value": "1, 2, x" }))
+            .unwrap_err()
+            .starts_with("Invalid number at position 2: x"));
+        assert_eq!(
+            parse(json!({ "format": "csv", "value": " [ ] " })),
+            Ok(vec![])
+        );
+
+        assert!(parse(json!({ "format": "base64", "value": "not base64!" }))
+            .unwrap_err()
+            .starts_with("Invalid base64 vector:"));
+        // Five bytes are not a whole number of f32 values.
+        assert!(parse(json!({ "format": "base64", "value": "AAAAPwA=" }))
+            .unwrap_err()
+            .starts_with("Invalid base64 vector:"));
+
+        let missing = env::temp_dir().join("chromamind_missing.npy");
+        let _ = std::fs::remove_file(&missing);
+        assert!(
+            parse(json!({ "format": "npy", "value": missing.to_str().unwrap() }))
+                .unwrap_err()
+                .starts_with(&format!("Error reading {}:", missing.display()))
+        );
+
+        let npy_error = |name: &str, bytes: &[u8]| {
+            let path = env::temp_dir().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            let error =
+                parse(json!({ "format": "npy", "value": path.to_str().unwrap() })).unwrap_err();
+            std::fs::remove_file(&path).unwrap();
+            error
+                .strip_prefix(&format!("Invalid .npy file {}: ", path.display()))
+                .map(str::to_string)
+                .unwrap_or(error)
+        };
+        assert_eq!(
+            npy_error("chromamind_magic.npy", b"PK\x03\x04"),
+            "missing NUMPY magic string"
+        );
+        assert_eq!(
+            npy_error("chromamind_version.npy", b"\x93NUMPY\x04\x00\x00\x00"),
+            "unsupported format version 4"
+        );
+        assert_eq!(
+            npy_error(
+                "chromamind_truncated.npy",
+                b"\x93NUMPY\x01\x00\x40\x00{'descr'"
+            ),
+            "truncated header"
+        );
+        let header = b"{'descr': '<f4', 'fortran_order': True, 'shape': (1,), }";
+        let mut fortran = b"\x93NUMPY\x01\x00".to_vec();
+        fortran.extend((header.len() as u16).to_le_bytes());
+        fortran.extend(header);
+        fortran.extend(1.0_f32.to_le_bytes());
+        assert_eq!(
+            npy_error("chromamind_fortran.npy", &fortran),
+            "Fortran-ordered arrays are not supported"
+        );
+    }
+
+    #[test]
+    fn test_normalize_vector() {
+        // The squares of 1e20 overflow f32, but the norm itself does not.
+        let mut large = vec![1e20_f32, 1e20_f32];
+        assert!((vector::l2_norm(&large) - 2.0_f32.sqrt() * 1e20).abs() < 1e14);
+        assert_eq!(vector::normalize(&mut large), Ok(()));
+        assert!(large
+            .iter()
+            .all(|v| (v - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6));
+
+        let mut tiny = vec![3e-30_f32, 4e-30_f32];
+        assert_eq!(vector::normalize(&mut tiny), Ok(()));
+        assert_eq!(tiny, vec![0.6, 0.8]);
+
+        assert_eq!(
+            vector::normalize(&mut [0.0, 0.0]),
+            Err("Cannot normalise a zero vector".to_string())
+        );
+        assert_eq!(
+            vector::normalize(&mut [1.0, f32::INFINITY]),
+            Err("Cannot normalise a vector with non-finite values".to_string())
+        );
+        assert!(vector::normalize(&mut [1.0, f32::NAN]).is_err());
+    }
+
+    #[test]
+    fn test_update_record_embedding() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordEmbedding.as_str(),
+            json!({
+                "collectionName": "test_collection_update_embedding",
+                "id": "doc1",
+                "vector": { "format": "json", "value": [0.1, 0.2, 0.3] },
+            }),
+        );
+
+        assert!(
+            res.is_err(),
+            "update_record_embedding should fail without a client"
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "update_record_embedding failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_update_embedding";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string()],
+            vec![vec![0.1_f32, 0.2_f32, 0.3_f32]],
+            Some(vec![Some("First document".to_string())]),
+            None,
+            None,
+        ))
+        .unwrap();
+
+        let fetch_vector = || {
+            rt.block_on(collection.get(
+                Some(vec!["doc1".to_string()]),
+                None,
+                Some(1u32),
+                None,
+                Some(IncludeList(vec![Include::Embedding])),
+            ))
+            .unwrap()
+            .embeddings
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .unwrap()
+        };
+
+        // Wrong dimension is refused and the stored vector is left alone.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordEmbedding.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "vector": { "format": "csv", "value": "1.0, 2.0" },
+            }),
+        );
+
+        assert!(
+            res.is_err(),
+            "update_record_embedding should reject a short vector"
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Vector has dimension 2, but the collection expects 3",
+            "short vector rejected with different error"
+        );
+        assert_eq!(fetch_vector(), vec![0.1_f32, 0.2_f32, 0.3_f32]);
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordEmbedding.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "vector": { "format": "csv", "value": "3.0, 0.0, 4.0" },
+                "normalize": true,
+            }),
+        );
+
+        assert!(
+            res.is_ok(),
+            "update_record_embedding failed: {:?}",
+            res.err()
+        );
+        assert_eq!(fetch_vector(), vec![0.6_f32, 0.0_f32, 0.8_f32]);
+    }
+
+    #[test]
+    fn test_patch_metadata_where() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::PatchMetadataWhere.as_str(),
+            json!({
+                "collectionName": "test_collection_patch_metadata",
+                "set": { "source": "import" },
+                "remove": Vec::<String>::new(),
+            }),
+        );
+
+        assert!(
+            res.is_err(),
+            "patch_metadata_where should fail without a client"
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "patch_metadata_where failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_patch_metadata";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let metadata = |group: &str| -> Metadata {
+            [
+                ("group".to_string(), MetadataValue::Str(group.to_string())),
+                ("drop".to_string(), MetadataValue::Int(1)),
+            ]
+            .into_iter()
+            .collect()
+        };
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
+            vec![
+                vec![0.1_f32, 0.2_f32, 0.3_f32],
+                vec![0.4_f32, 0.5_f32, 0.6_f32],
+                vec![0.7_f32, 0.8_f32, 0.9_f32],
+            ],
+            Some(vec![
+                Some("apple pie".to_string()),
+                Some("apple tart".to_string()),
+                Some("banana bread".to_string()),
+            ]),
+            None,
+            Some(vec![
+                Some(metadata("a")),
+                Some(metadata("b")),
+                Some(metadata("a")),
+            ]),
+        ))
+        .unwrap();
+
+        let patch_body = |preview: bool| {
+            json!({
+                "collectionName": collection_name,
+                "whereFilter": { "group": "a" },
+                "set": { "source": "import" },
+                "remove": vec!["drop".to_string()],
+                "preview": preview,
+            })
+        };
+
+        // Preview reports the matches and the would-be metadata without writing.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::PatchMetadataWhere.as_str(),
+            patch_body(true),
+        );
+
+        assert!(res.is_ok(), "patch_metadata_where failed: {:?}", res.err());
+        let patch = res.unwrap().deserialize::<MetadataPatch>().unwrap();
+        assert!(patch.preview);
+        assert_eq!(patch.matched, 2);
+        assert_eq!(patch.updated, 0);
+        assert_eq!(patch.samples.len(), 2);
+        for sample in &patch.samples {
+            assert_eq!(sample.before.get("drop"), Some(&json!(1)));
+            assert_eq!(sample.after.get("drop"), None);
+            assert_eq!(sample.after.get("source"), Some(&json!("import")));
+            assert_eq!(sample.after.get("group"), Some(&json!("a")));
+        }
+
+        // The document filter narrows the metadata filter.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::PatchMetadataWhere.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "whereFilter": { "group": "a" },
+                "whereDocument": { "$contains": "apple" },
+                "set": { "source": "import" },
+                "remove": Vec::<String>::new(),
+                "preview": true,
+                "sampleSize": 5,
+            }),
+        );
+
+        assert!(res.is_ok(), "patch_metadata_where failed: {:?}", res.err());
+        let patch = res.unwrap().deserialize::<MetadataPatch>().unwrap();
+        assert_eq!(patch.matched, 1);
+        assert_eq!(patch.samples.len(), 1);
+        assert_eq!(patch.samples.first().unwrap().id, "doc1");
+
+        let sources = || -> HashMap<String, Option<MetadataValue>> {
+            let get_result = rt
+                .block_on(collection.get(
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(IncludeList(vec![Include::Metadata])),
+                ))
+                .unwrap();
+            get_result
+                .ids
+                .into_iter()
+                .zip(get_result.metadatas.unwrap_or_default())
+                .map(|(id, metadata)| {
+                    let metadata = metadata.unwrap_or_default();
+                    assert_eq!(
+                        metadata.contains_key("drop"),
+                        metadata.get("source").is_none(),
+                        "set and remove were not applied together for {}",
+                        id
+                    );
+                    (id, metadata.get("source").cloned())
+                })
+                .collect()
+        };
+
+        assert!(
+            sources().values().all(Option::is_none),
+            "preview wrote metadata"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::PatchMetadataWhere.as_str(),
+            patch_body(false),
+        );
+
+        assert!(res.is_ok(), "patch_metadata_where failed: {:?}", res.err());
+        let patch = res.unwrap().deserialize::<MetadataPatch>().unwrap();
+        assert!(!patch.preview);
+        assert_eq!(patch.matched, 2);
+        assert_eq!(patch.updated, 2);
+
+        let sources = sources();
+        let import = Some(MetadataValue::Str("import".to_string()));
+        assert_eq!(sources.get("doc1"), Some(&import));
+        assert_eq!(sources.get("doc2"), Some(&None));
+        assert_eq!(sources.get("doc3"), Some(&import));
+
+        // A patch with nothing to set or remove is refused.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::PatchMetadataWhere.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "set": {},
+                "remove": Vec::<String>::new(),
+            }),
+        );
+
+        assert!(
+            res.is_err(),
+            "patch_metadata_where should reject an empty patch"
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "No metadata changes provided",
+            "empty patch rejected with different error"
+        );
+    }
+
+    #[test]
+    fn test_delete_records_where() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::PreviewDeleteWhere.as_str(),
+            json!({
+                "collectionName": "test_collection_delete_where",
+                "whereFilter": { "group": "a" },
+            }),
+        );
+
+        assert!(
+            res.is_err(),
+            "preview_delete_where should fail without a client"
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "preview_delete_where failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_delete_where";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let group = |group: &str| -> Option<Metadata> {
+            Some(
+                [("group".to_string(), MetadataValue::Str(group.to_string()))]
+                    .into_iter()
+                    .collect(),
+            )
+        };
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
+            vec![
+                vec![0.1_f32, 0.2_f32, 0.3_f32],
+                vec![0.4_f32, 0.5_f32, 0.6_f32],
+                vec![0.7_f32, 0.8_f32, 0.9_f32],
+            ],
+            None,
+            None,
+            Some(vec![group("a"), group("b"), group("a")]),
+        ))
+        .unwrap();
+
+        // A delete without any filter is refused outright.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::PreviewDeleteWhere.as_str(),
+            json!({ "collectionName": collection_name }),
+        );
+
+        assert!(res.is_err(), "preview_delete_where should reject no filter");
+        assert_eq!(res.err().unwrap(), "No filter provided");
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::PreviewDeleteWhere.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "whereFilter": { "group": "a" },
+            }),
+        );
+
+        assert!(res.is_ok(), "preview_delete_where failed: {:?}", res.err());
+        let preview = res.unwrap().deserialize::<DeletePreview>().unwrap();
+        assert_eq!(preview.matched, 2);
+        let mut sample_ids = preview.sample_ids.clone();
+        sample_ids.sort();
+        assert_eq!(sample_ids, vec!["doc1".to_string(), "doc3".to_string()]);
+
+        // A new match after the preview invalidates the token.
+        rt.block_on(collection.add(
+            vec!["doc4".to_string()],
+            vec![vec![0.2_f32, 0.3_f32, 0.4_f32]],
+            None,
+            None,
+            Some(vec![group("a")]),
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::DeleteRecordsWhere.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "whereFilter": { "group": "a" },
+                "token": preview.token,
+            }),
+        );
+
+        assert!(
+            res.is_err(),
+            "delete_records_where should reject a stale token"
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Matching records changed since the preview (now 3), preview again before deleting",
+            "stale token rejected with different error"
+        );
+        assert_eq!(rt.block_on(collection.count()).unwrap(), 4);
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::PreviewDeleteWhere.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "whereFilter": { "group": "a" },
+            }),
+        );
+        let preview = res.unwrap().deserialize::<DeletePreview>().unwrap();
+        assert_eq!(preview.matched, 3);
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::DeleteRecordsWhere.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "whereFilter": { "group": "a" },
+                "token": preview.token,
+            }),
+        );
+
+        assert!(res.is_ok(), "delete_records_where failed: {:?}", res.err());
+        assert_eq!(res.unwrap().deserialize::<usize>().unwrap(), 3);
+
+        let get_result = rt
+            .block_on(collection.get(None, None, None, None, Some(IncludeList(vec![]))))
+            .unwrap();
+        assert_eq!(
+            get_result.ids,
+            vec!["doc2".to_string()],
+            "only the non-matching record should remain"
+        );
+    }
+
+    #[test]
+    fn test_validate_filter() {
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let validate = |body: Value| {
+            get_command_response(&webview, TauriCommand::ValidateFilter.as_str(), body)
+                .map(|_| ())
+                .map_err(|e| serde_json::from_value::<FilterError>(e).unwrap())
+        };
+
+        assert_eq!(validate(json!({})), Ok(()));
+        assert_eq!(
+            validate(json!({
+                "whereFilter": { "page": { "$gt": 4 } },
+                "whereDocument": { "$and": [
+                    { "$contains": "apple" },
+                    { "$not_regex": "^draft" }
+                ] }
+            })),
+            Ok(())
+        );
+
+        assert_eq!(
+            validate(json!({ "whereFilter": { "page": { "$bogus": 4 } } })),
+            Err(FilterError::Where("Invalid where clause".to_string()))
+        );
+        assert_eq!(
+            validate(json!({ "whereDocument": { "$contains": 4 } })),
+            Err(FilterError::WhereDocument(
+                "$contains expects a string".to_string()
+            ))
+        );
+        assert_eq!(
+            validate(json!({ "whereDocument": { "$or": { "$contains": "a" } } })),
+            Err(FilterError::WhereDocument(
+                "$or expects a list of conditions".to_string()
+            ))
+        );
+        assert_eq!(
+            validate(json!({ "whereDocument": { "$contains": "a", "$regex": "b" } })),
+            Err(FilterError::WhereDocument(
+                "Expected exactly one operator, found 2".to_string()
+            ))
+        );
+        assert!(matches!(
+            validate(json!({ "whereDocument": { "$like": "a" } })),
+            Err(FilterError::WhereDocument(message)) if message.starts_with("Unknown operator $like")
+        ));
+        assert!(matches!(
+            validate(json!({ "whereDocument": { "$regex": "(unclosed" } })),
+            Err(FilterError::WhereDocument(_))
+        ));
+    }
+
+    #[test]
+    fn test_fetch_embeddings_where_document() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_where_document";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let page = |page: i64| -> Option<Metadata> {
+            Some(
+                [("page".to_string(), MetadataValue::Int(page))]
+                    .into_iter()
+                    .collect(),
+            )
+        };
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
+            vec![
+                vec![0.1_f32, 0.2_f32, 0.3_f32],
+                vec![0.4_f32, 0.5_f32, 0.6_f32],
+                vec![0.7_f32, 0.8_f32, 0.9_f32],
+            ],
+            Some(vec![
+                Some("red apple".to_string()),
+                Some("green apple".to_string()),
+                Some("red pepper".to_string()),
+            ]),
+            None,
+            Some(vec![page(1), page(2), page(3)]),
+        ))
+        .unwrap();
+
+        let fetch_ids = |where_filter: Value, where_document: Value| -> Vec<String> {
+            let res = get_command_response(
+                &webview,
+                TauriCommand::FetchEmbeddings.as_str(),
+                json!({
+                    "collectionName": collection_name,
+                    "limit": 10,
+                    "offset": 0,
+                    "whereFilter": where_filter,
+                    "whereDocument": where_document,
+                }),
+            );
+            assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
+            let mut ids: Vec<String> = res
+                .unwrap()
+                .deserialize::<Vec<EmbeddingData>>()
+                .unwrap()
+                .into_iter()
+                .map(|e| e.id)
+                .collect();
+            ids.sort();
+            ids
+        };
+        let count = |where_filter: Value, where_document: Value| -> u32 {
+            get_command_response(
+                &webview,
+                TauriCommand::FetchRowCount.as_str(),
+                json!({
+                    "collectionName": collection_name,
+                    "whereFilter": where_filter,
+                    "whereDocument": where_document,
+                }),
+            )
+            .unwrap()
+            .deserialize::<u32>()
+            .unwrap()
+        };
+
+        assert_eq!(
+            fetch_ids(Value::Null, json!({ "$contains": "apple" })),
+            vec!["doc1".to_string(), "doc2".to_string()]
+        );
+        assert_eq!(count(Value::Null, json!({ "$contains": "apple" })), 2);
+
+        assert_eq!(
+            fetch_ids(Value::Null, json!({ "$not_contains": "apple" })),
+            vec!["doc3".to_string()]
+        );
+
+        assert_eq!(
+            fetch_ids(Value::Null, json!({ "$regex": "^red" })),
+            vec!["doc1".to_string(), "doc3".to_string()]
+        );
+
+        // Metadata and document filters combine with AND.
+        assert_eq!(
+            fetch_ids(json!({ "page": { "$gt": 1 } }), json!({ "$regex": "^red" })),
+            vec!["doc3".to_string()]
+        );
+        assert_eq!(
+            count(json!({ "page": { "$gt": 1 } }), json!({ "$regex": "^red" })),
+            1
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddings.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "limit": 10,
+                "offset": 0,
+                "whereDocument": { "$contains": 4 },
+            }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Invalid document filter: $contains expects a string",
+            "invalid document filter rejected with different error"
+        );
+    }
+
+    #[test]
+    fn test_query_similar() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QuerySimilar.as_str(),
+            json!({
+                "collectionName": "test_collection_query_similar",
+                "id": "doc1",
+            }),
+        );
+
+        assert!(res.is_err(), "query_similar should fail without a client");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "query_similar failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_query_similar";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let group = |group: &str| -> Option<Metadata> {
+            Some(
+                [("group".to_string(), MetadataValue::Str(group.to_string()))]
+                    .into_iter()
+                    .collect(),
+            )
+        };
+
+        rt.block_on(collection.add(
+            vec![
+                "doc1".to_string(),
+                "doc2".to_string(),
+                "doc3".to_string(),
+                "doc4".to_string(),
+            ],
+            vec![
+                vec![1.0_f32, 0.0_f32, 0.0_f32],
+                vec![0.9_f32, 0.1_f32, 0.0_f32],
+                vec![0.5_f32, 0.5_f32, 0.0_f32],
+                vec![0.0_f32, 0.0_f32, 1.0_f32],
+            ],
+            Some(vec![
+                Some("First".to_string()),
+                Some("Second".to_string()),
+                Some("Third".to_string()),
+                Some("Fourth".to_string()),
+            ]),
+            None,
+            Some(vec![group("a"), group("b"), group("a"), group("a")]),
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QuerySimilar.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "nResults": 2,
+            }),
+        );
+
+        assert!(res.is_ok(), "query_similar failed: {:?}", res.err());
+        let matches = res.unwrap().deserialize::<Vec<QueryMatch>>().unwrap();
+        assert_eq!(
+            matches.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+            vec!["doc2", "doc3"],
+            "source record should be excluded and neighbours ordered by distance"
+        );
+        let nearest = matches.first().unwrap();
+        assert_eq!(nearest.document, "Second");
+        assert_eq!(nearest.metadata.get("group"), Some(&json!("b")));
+        let distances: Vec<f32> = matches.iter().map(|m| m.distance.unwrap()).collect();
+        assert!(
+            distances.windows(2).all(|pair| pair.first() <= pair.last()),
+            "distances are not ascending"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QuerySimilar.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "nResults": 5,
+                "whereFilter": { "group": "a" },
+            }),
+        );
+
+        assert!(res.is_ok(), "query_similar failed: {:?}", res.err());
+        let matches = res.unwrap().deserialize::<Vec<QueryMatch>>().unwrap();
+        assert_eq!(
+            matches.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+            vec!["doc3", "doc4"],
+            "where filter was not applied"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QuerySimilar.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "missing",
+            }),
+        );
+
+        assert_eq!(
+            res.err().unwrap(),
+            "Embedding not found for id missing",
+            "missing record rejected with different error"
+        );
+    }
+
+    #[test]
+    fn test_parse_npy_vector() {
+        // np.save of np.array([0.5, -1.0, 2.0], dtype=...) with a version 1 header.
+        let npy = |descr: &str, shape: &str, data: Vec<u8>| -> Vec<u8> {
+            let mut header = format!(
+                "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+                descr, shape
+            );
+            while (10 + header.len() + 1) % 64 != 0 {
+                header.push(' ');
+            }
+            header.push('\n');
+            let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+            bytes.extend((header.len() as u16).to_le_bytes());
+            bytes.extend(header.into_bytes());
+            bytes.extend(data);
+            bytes
+        };
+        let f4: Vec<u8> = [0.5_f32, -1.0, 2.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let f8: Vec<u8> = [0.5_f64, -1.0, 2.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+
+        let dir = std::env::temp_dir();
+        let parse_file = |name: &str, bytes: Vec<u8>| {
+            let path = dir.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            tauri::async_runtime::block_on(
+                serde_json::from_value::<VectorInput>(
+                    json!({ "format": "npy", "value": path.to_str().unwrap() }),
+                )
+                .unwrap()
+                .parse(),
+            )
+        };
+
+        assert_eq!(
+            parse_file("chromamind_f4.npy", npy("<f4", "(3,)", f4.clone())),
+            Ok(vec![0.5_f32, -1.0_f32, 2.0_f32])
+        );
+        assert_eq!(
+            parse_file("chromamind_f8.npy", npy("<f8", "(1, 3)", f8)),
+            Ok(vec![0.5_f32, -1.0_f32, 2.0_f32])
+        );
+        assert!(parse_file("chromamind_2d.npy", npy("<f4", "(3, 1)", f4.clone())).is_err());
+        assert!(parse_file("chromamind_int.npy", npy("<i4", "(3,)", f4.clone())).is_err());
+        assert!(parse_file("chromamind_short.npy", npy("<f4", "(4,)", f4)).is_err());
+        assert!(parse_file("chromamind_bad.npy", b"not numpy".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_query_by_vector() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QueryByVector.as_str(),
+            json!({
+                "collectionName": "test_collection_query_by_vector",
+                "vector": { "format": "json", "value": [1.0, 0.0, 0.0] },
+            }),
+        );
+
+        assert!(res.is_err(), "query_by_vector should fail without a client");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "query_by_vector failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_query_by_vector";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
+            vec![
+                vec![1.0_f32, 0.0_f32, 0.0_f32],
+                vec![0.0_f32, 1.0_f32, 0.0_f32],
+                vec![0.0_f32, 0.0_f32, 1.0_f32],
+            ],
+            Some(vec![
+                Some("First".to_string()),
+                Some("Second".to_string()),
+                Some("Third".to_string()),
+            ]),
+            None,
+            None,
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QueryByVector.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "vector": { "format": "csv", "value": "0.1, 0.9, 0.0" },
+                "nResults": 2,
+            }),
+        );
+
+        assert!(res.is_ok(), "query_by_vector failed: {:?}", res.err());
+        let matches = res.unwrap().deserialize::<Vec<QueryMatch>>().unwrap();
+        assert_eq!(
+            matches.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+            vec!["doc2", "doc1"]
+        );
+        assert_eq!(matches.first().unwrap().document, "Second");
+        assert!(matches.iter().all(|m| m.distance.is_some()));
+
+        // An exact copy of a stored vector is at distance zero.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QueryByVector.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "vector": { "format": "json", "value": [0.0, 0.0, 1.0] },
+                "nResults": 1,
+            }),
+        );
+        let matches = res.unwrap().deserialize::<Vec<QueryMatch>>().unwrap();
+        let nearest = matches.first().unwrap();
+        assert_eq!(nearest.id, "doc3");
+        assert!(nearest.distance.unwrap().abs() < 1e-6);
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QueryByVector.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "vector": { "format": "json", "value": [1.0, 0.0] },
+            }),
+        );
+
+        assert_eq!(
+            res.err().unwrap(),
+            "Vector has dimension 2, but the collection expects 3",
+            "wrong dimension rejected with different error"
+        );
+    }
+
+    /// Serves a single HTTP request with `body` as a JSON response and returns
+    /// the raw request it received, for testing embedding providers offline.
+    fn spawn_mock_http_server(body: Value) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(buf.get(..n).unwrap());
+                let text = String::from_utf8_lossy(&request).to_lowercase();
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text
+                        .lines()
+                        .find_map(|l| l.strip_prefix("content-length:"))
+                        .map(|l| l.trim().parse::<usize>().unwrap())
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + content_length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+
+            let body = body.to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn test_embedding_providers() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let texts = vec!["first".to_string(), "second".to_string()];
+
+        // OpenAI-compatible: items are reordered by `index` and the key is sent as a bearer token.
+        let (url, server) = spawn_mock_http_server(json!({
+            "object": "list",
+            "data": [
+                { "object": "embedding", "index": 1, "embedding": [0.0, 1.0] },
+                { "object": "embedding", "index": 0, "embedding": [1.0, 0.0] }
+            ],
+            "model": "text-embedding-3-small"
+        }));
+        let provider: EmbeddingProvider = serde_json::from_value(json!({
+            "provider": "openai",
+            "url": format!("{}/v1", url),
+            "model": "text-embedding-3-small",
+            "apiKey": "sk-test",
+            "dimensions": 2,
+        }))
+        .unwrap();
+        let embeddings = rt.block_on(provider.embed(&texts)).unwrap();
+        assert_eq!(
+            embeddings,
+            vec![vec![1.0_f32, 0.0_f32], vec![0.0_f32, 1.0_f32]]
+        );
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /v1/embeddings "), "{}", request);
+        assert!(request
+            .to_lowercase()
+            .contains("authorization: bearer sk-test"));
+        let body: Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "model": "text-embedding-3-small",
+                "input": ["first", "second"],
+                "dimensions": 2,
+            })
+        );
+
+        // The API key is accepted but never serialised back out.
+        let serialized = serde_json::to_value(&provider).unwrap();
+        assert_eq!(serialized.get("apiKey"), None);
+
+        // Ollama-compatible.
+        let (url, server) = spawn_mock_http_server(json!({
+            "model": "nomic-embed-text",
+            "embeddings": [[0.5, 0.5], [0.25, 0.75]]
+        }));
+        let provider: EmbeddingProvider = serde_json::from_value(json!({
+            "provider": "ollama",
+            "url": url,
+            "model": "nomic-embed-text",
+        }))
+        .unwrap();
+        let embeddings = rt.block_on(provider.embed(&texts)).unwrap();
+        assert_eq!(
+            embeddings,
+            vec![vec![0.5_f32, 0.5_f32], vec![0.25_f32, 0.75_f32]]
+        );
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /api/embed "), "{}", request);
+        let body: Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({ "model": "nomic-embed-text", "input": ["first", "second"] })
+        );
+
+        // A response with the wrong number of vectors is an error.
+        let (url, server) = spawn_mock_http_server(json!({ "embeddings": [[0.5, 0.5]] }));
+        let provider: EmbeddingProvider = serde_json::from_value(json!({
+            "provider": "ollama",
+            "url": url,
+            "model": "nomic-embed-text",
+        }))
+        .unwrap();
+        assert_eq!(
+            rt.block_on(provider.embed(&texts)),
+            Err("Embedding provider returned 1 vector(s) for 2 input(s)".to_string())
+        );
+        server.join().unwrap();
+
+        // Hashing is deterministic, case-insensitive and unit length.
+        let provider: EmbeddingProvider =
+            serde_json::from_value(json!({ "provider": "hash", "dimension": 16 })).unwrap();
+        let embeddings = rt
+            .block_on(provider.embed(&[
+                "Red apple".to_string(),
+                "red APPLE".to_string(),
+                "green pepper".to_string(),
+            ]))
+            .unwrap();
+        let [red_apple, red_apple_upper, pepper] = embeddings.as_slice() else {
+            panic!("expected 3 embeddings, got {}", embeddings.len());
+        };
+        assert_eq!(red_apple, red_apple_upper);
+        assert_ne!(red_apple, pepper);
+        assert!((vector::l2_norm(red_apple) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_query_by_text() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QueryByText.as_str(),
+            json!({
+                "collectionName": "test_collection_query_by_text",
+                "text": "apple",
+            }),
+        );
+
+        assert!(res.is_err(), "query_by_text should fail without a client");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "query_by_text failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_query_by_text";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let provider = json!({ "provider": "hash", "dimension": 32 });
+        let hash_provider: EmbeddingProvider = serde_json::from_value(provider.clone()).unwrap();
+        let documents = vec![
+            "red apple pie".to_string(),
+            "green apple".to_string(),
+            "blue whale".to_string(),
+        ];
+        let embeddings = rt.block_on(hash_provider.embed(&documents)).unwrap();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
+            embeddings,
+            Some(documents.into_iter().map(Some).collect()),
+            None,
+            None,
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QueryByText.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "text": "blue whale",
+            }),
+        );
+
+        assert_eq!(
+            res.err().unwrap(),
+            format!(
+                "No embedding provider configured for collection: {}",
+                collection_name
+            ),
+            "query_by_text without a provider rejected with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::SetEmbeddingProvider.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "provider": provider,
+            }),
+        );
+        assert!(
+            res.is_ok(),
+            "set_embedding_provider failed: {:?}",
+            res.err()
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::GetEmbeddingProvider.as_str(),
+            json!({ "collectionName": collection_name }),
+        );
+        assert_eq!(
+            res.unwrap().deserialize::<Value>().unwrap(),
+            json!({ "provider": "hash", "dimension": 32 })
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QueryByText.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "text": "Blue whale",
+                "nResults": 1,
+            }),
+        );
+
+        assert!(res.is_ok(), "query_by_text failed: {:?}", res.err());
+        let matches = res.unwrap().deserialize::<Vec<QueryMatch>>().unwrap();
+        assert_eq!(matches.len(), 1);
+        let nearest = matches.first().unwrap();
+        assert_eq!(nearest.id, "doc3");
+        assert!(nearest.distance.unwrap().abs() < 1e-5);
+
+        // Re-embedding a document moves its vector to the new text.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordDocument.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "document": "blue whale song",
+                "reembed": true,
+            }),
+        );
+
+        assert!(
+            res.is_ok(),
+            "update_record_document failed: {:?}",
+            res.err()
+        );
+        let update = res.unwrap().deserialize::<DocumentUpdate>().unwrap();
+        assert!(update.reembedded);
+        assert!(!update.embedding_stale);
+
+        let expected = rt
+            .block_on(hash_provider.embed(&["blue whale song".to_string()]))
+            .unwrap();
+        let stored = rt
+            .block_on(collection.get(
+                Some(vec!["doc1".to_string()]),
+                None,
+                None,
+                None,
+                Some(IncludeList(vec![Include::Embedding])),
+            ))
+            .unwrap()
+            .embeddings
+            .unwrap_or_default();
+        assert_eq!(stored, expected, "document was not re-embedded");
+
+        // A provider producing the wrong dimension is caught before querying.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::SetEmbeddingProvider.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "provider": { "provider": "hash", "dimension": 8 },
+            }),
+        );
+        assert!(
+            res.is_ok(),
+            "set_embedding_provider failed: {:?}",
+            res.err()
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::QueryByText.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "text": "apple",
+            }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Error embedding text: Vector has dimension 8, but the collection expects 32",
+            "mismatched provider rejected with different error"
+        );
+
+        // Clearing the provider.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::SetEmbeddingProvider.as_str(),
+            json!({ "collectionName": collection_name }),
+        );
+        assert!(
+            res.is_ok(),
+            "set_embedding_provider failed: {:?}",
+            res.err()
+        );
+        let res = get_command_response(
+            &webview,
+            TauriCommand::GetEmbeddingProvider.as_str(),
+            json!({ "collectionName": collection_name }),
+        );
+        assert_eq!(res.unwrap().deserialize::<Value>().unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn test_search() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_recent_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Search.as_str(),
+            json!({
+                "collectionName": "test_collection_search",
+                "request": {},
+            }),
+        );
+
+        assert!(res.is_err(), "search should fail without a client");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "search failed with different error"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_search";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
+            vec![
+                vec![1.0_f32, 0.0_f32, 0.0_f32],
+                vec![0.0_f32, 1.0_f32, 0.0_f32],
+                vec![0.0_f32, 0.0_f32, 1.0_f32],
+            ],
+            Some(vec![
+                Some("First".to_string()),
+                Some("Second".to_string()),
+                Some("Third".to_string()),
+            ]),
+            None,
+            Some(vec![
+                Some(Metadata::from([(
+                    "lang".to_string(),
+                    MetadataValue::Str("en".to_string()),
+                )])),
+                Some(Metadata::from([(
+                    "lang".to_string(),
+                    MetadataValue::Str("en".to_string()),
+                )])),
+                Some(Metadata::from([(
+                    "lang".to_string(),
+                    MetadataValue::Str("de".to_string()),
+                )])),
+            ]),
+        ))
+        .unwrap();
+
+        // Mismatched sparse vectors and dense vectors of the wrong dimension
+        // are rejected before anything is sent.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Search.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "request": {
+                    "rank": [{
+                        "query": { "type": "sparse", "indices": [1, 2], "values": [0.5] },
+                        "key": "sparse_field",
+                    }],
+                },
+            }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Sparse vector indices and values differ in length"
+        );
+
+        let sparse_error = |indices: Value, values: Value| {
+            get_command_response(
+                &webview,
+                TauriCommand::Search.as_str(),
+                json!({
+                    "collectionName": collection_name,
+                    "request": {
+                        "rank": [{
+                            "query": { "type": "sparse", "indices": indices, "values": values },
+                            "key": "sparse_field",
+                        }],
+                    },
+                }),
+            )
+            .err()
+            .unwrap()
+        };
+        assert_eq!(
+            sparse_error(json!([2, 1]), json!([0.5, 0.5])),
+            "Sparse vector indices must be sorted in strictly ascending order (no duplicates)"
+        );
+        // 1e39 is out of f32 range, so it arrives as infinity.
+        assert_eq!(
+            sparse_error(json!([1, 2]), json!([0.5, 1e39])),
+            "Sparse vector has a non-finite value at position 1"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Search.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "request": {
+                    "rank": [{
+                        "query": { "type": "dense", "vector": { "format": "json", "value": [1.0, 0.0] } },
+                    }],
+                },
+            }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Vector has dimension 2, but the collection expects 3"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Search.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "request": {
+                    "rank": [{ "query": { "type": "text", "text": "hello" } }],
+                },
+            }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            format!(
+                "No embedding provider configured for collection: {}",
+                collection_name
+            )
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CheckSearchSupport.as_str(),
+            json!({ "collectionName": collection_name }),
+        );
+        assert!(res.is_ok(), "check_search_support failed: {:?}", res.err());
+        assert!(
+            res.unwrap().deserialize::<bool>().unwrap(),
+            "server should support the Search API"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Search.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "request": {
+                    "whereFilter": { "lang": "en" },
+                    "rank": [
+                        {
+                            "query": { "type": "dense", "vector": { "format": "json", "value": [0.1, 0.9, 0.0] } },
+                            "weight": 2.0,
+                        },
+                        {
+                            "query": { "type": "dense", "vector": { "format": "csv", "value": "1, 0, 0" } },
+                        },
+                    ],
+                    "combine": "rrf",
+                    "limit": 2,
+                },
+            }),
+        );
+
+        assert!(res.is_ok(), "search failed: {:?}", res.err());
+        let matches = res.unwrap().deserialize::<Vec<SearchMatch>>().unwrap();
+        assert_eq!(
+            matches.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+            vec!["doc2", "doc1"]
+        );
+        assert_eq!(
+            matches.first().and_then(|m| m.document.as_deref()),
+            Some("Second")
+        );
+        assert!(matches.iter().all(|m| m.score.is_some()));
+
+        // A single summed term ranks by distance, nearest first.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Search.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "request": {
+                    "rank": [{
+                        "query": { "type": "dense", "vector": { "format": "json", "value": [0.0, 0.2, 1.0] } },
+                    }],
+                    "limit": 3,
+                },
+            }),
+        );
+
+        assert!(res.is_ok(), "search failed: {:?}", res.err());
+        let matches = res.unwrap().deserialize::<Vec<SearchMatch>>().unwrap();
+        assert_eq!(
+            matches.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+            vec!["doc3", "doc2", "doc1"]
+        );
+        assert_eq!(
+            matches
+                .first()
+                .and_then(|m| m.metadata.as_ref())
+                .and_then(|m| m.get("lang")),
+            Some(&json!("de"))
+        );
+        let scores: Vec<f32> = matches.iter().filter_map(|m| m.score).collect();
+        assert_eq!(scores.len(), 3);
+        assert!(scores.windows(2).all(|pair| pair.first() <= pair.last()));
+
+        // Sparse terms need a sparse vector index on their metadata key.
+        let sparse_collection_name = "test_collection_search_sparse";
+        let schema = chroma_types::Schema::default()
+            .create_index(
+                Some("bm25"),
+                chroma_types::SparseVectorIndexConfig {
+                    embedding_function: None,
+                    source_key: None,
+                    bm25: None,
+                }
+                .into(),
+            )
+            .unwrap();
+        let sparse_collection = rt
+            .block_on(client.get_or_create_collection(sparse_collection_name, Some(schema), None))
+            .unwrap();
+        let bm25 = |indices: Vec<u32>, values: Vec<f32>| {
+            Some(Metadata::from([(
+                "bm25".to_string(),
+                MetadataValue::SparseVector(
+                    chroma_types::SparseVector::new(indices, values).unwrap(),
+                ),
+            )]))
+        };
+        rt.block_on(sparse_collection.add(
+            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
+            vec![
+                vec![1.0_f32, 0.0_f32, 0.0_f32],
+                vec![0.0_f32, 1.0_f32, 0.0_f32],
+                vec![0.0_f32, 0.0_f32, 1.0_f32],
+            ],
+            None,
+            None,
+            Some(vec![
+                bm25(vec![1, 2], vec![1.0, 0.5]),
+                bm25(vec![2, 3], vec![0.2, 2.0]),
+                bm25(vec![4], vec![1.0]),
+            ]),
+        ))
+        .unwrap();
+
+        let sparse_query = json!({ "type": "sparse", "indices": [1, 2], "values": [1.0, 1.0] });
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Search.as_str(),
+            json!({
+                "collectionName": sparse_collection_name,
+                "request": {
+                    "rank": [{ "query": sparse_query, "key": "bm25" }],
+                    "limit": 3,
+                },
+            }),
+        );
+
+        assert!(res.is_ok(), "sparse search failed: {:?}", res.err());
+        let matches = res.unwrap().deserialize::<Vec<SearchMatch>>().unwrap();
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| m.id.as_str())
+                .take(2)
+                .collect::<Vec<_>>(),
+            vec!["doc1", "doc2"],
+            "the larger dot product should rank first"
+        );
+
+        // Dense ranks doc3 first and sparse ranks doc1 first; doubling the
+        // sparse term's weight lets it win the fusion.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Search.as_str(),
+            json!({
+                "collectionName": sparse_collection_name,
+                "request": {
+                    "rank": [
+                        {
+                            "query": { "type": "dense", "vector": { "format": "json", "value": [0.0, 0.2, 1.0] } },
+                        },
+                        { "query": sparse_query, "key": "bm25", "weight": 2.0 },
+                    ],
+                    "combine": "rrf",
+                    "limit": 2,
+                },
+            }),
+        );
+
+        assert!(res.is_ok(), "hybrid search failed: {:?}", res.err());
+        let matches = res.unwrap().deserialize::<Vec<SearchMatch>>().unwrap();
+        assert_eq!(matches.first().map(|m| m.id.as_str()), Some("doc1"));
+        assert!(matches.iter().all(|m| m.score.is_some()));
+    }
+
+    #[test]
+    fn test_search_unsupported_server() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_search_unsupported";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+        rt.block_on(collection.add(
+            vec!["doc1".to_string()],
+            vec![vec![1.0_f32, 0.0_f32, 0.0_f32]],
+            None,
+            None,
+            None,
+        ))
+        .unwrap();
+
+        // 1.0.16 predates the Search API.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CheckSearchSupport.as_str(),
+            json!({ "collectionName": collection_name }),
+        );
+        assert!(res.is_ok(), "check_search_support failed: {:?}", res.err());
+        assert!(!res.unwrap().deserialize::<bool>().unwrap());
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::Search.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "request": {
+                    "rank": [{
+                        "query": { "type": "dense", "vector": { "format": "json", "value": [1.0, 0.0, 0.0] } },
+                    }],
+                },
+            }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "This Chroma server does not support the Search API"
+        );
+    }
+
+    #[test]
+    fn test_array_metadata() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_recent_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_array_metadata";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let seed_metadata: Metadata = [
+            (
+                "tags".to_string(),
+                MetadataValue::StringArray(vec!["news".to_string(), "sport".to_string()]),
+            ),
+            ("pages".to_string(), MetadataValue::IntArray(vec![1, 2, 3])),
+            (
+                "weights".to_string(),
+                MetadataValue::FloatArray(vec![0.5, 1.5]),
+            ),
+            (
+                "flags".to_string(),
+                MetadataValue::BoolArray(vec![true, false]),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string()],
+            vec![
+                vec![0.1_f32, 0.2_f32, 0.3_f32],
+                vec![0.3_f32, 0.2_f32, 0.1_f32],
+            ],
+            Some(vec![
+                Some("First document".to_string()),
+                Some("Second document".to_string()),
+            ]),
+            None,
+            Some(vec![
+                Some(seed_metadata),
+                Some(Metadata::from([(
+                    "tags".to_string(),
+                    MetadataValue::StringArray(vec!["weather".to_string()]),
+                )])),
+            ]),
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddings.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "limit": 10,
+                "offset": 0,
+                "whereFilter": { "tags": { "$contains": "news" } },
+            }),
+        );
+
+        assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
+        let records = res.unwrap().deserialize::<Vec<EmbeddingData>>().unwrap();
+        assert_eq!(records.len(), 1, "$contains should match only doc1");
+        let record = records.first().unwrap();
+        assert_eq!(record.id, "doc1");
+        assert_eq!(record.metadata.get("tags"), Some(&json!(["news", "sport"])));
+        assert_eq!(record.metadata.get("pages"), Some(&json!([1, 2, 3])));
+        assert_eq!(record.metadata.get("weights"), Some(&json!([0.5, 1.5])));
+        assert_eq!(record.metadata.get("flags"), Some(&json!([true, false])));
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddings.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "limit": 10,
+                "offset": 0,
+                "whereFilter": { "tags": { "$not_contains": "news" } },
+            }),
+        );
+        let records = res.unwrap().deserialize::<Vec<EmbeddingData>>().unwrap();
+        assert_eq!(
+            records.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(),
+            vec!["doc2"]
+        );
+
+        // Edited arrays are written back with their element type.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordMetadata.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc2",
+                "metadata": {
+                    "tags": ["weather", "news"],
+                    "pages": [4, 5.5],
+                },
+                "removedKeys": Vec::<String>::new(),
+            }),
+        );
+        assert!(
+            res.is_ok(),
+            "update_record_metadata failed: {:?}",
+            res.err()
+        );
+
+        let metadata = rt
+            .block_on(collection.get(
+                Some(vec!["doc2".to_string()]),
+                None,
+                Some(1u32),
+                None,
+                Some(IncludeList(vec![Include::Metadata])),
+            ))
+            .unwrap()
+            .metadatas
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .flatten()
+            .expect("record should still have metadata");
+        assert_eq!(
+            metadata.get("tags"),
+            Some(&MetadataValue::StringArray(vec![
+                "weather".to_string(),
+                "news".to_string()
+            ]))
+        );
+        assert_eq!(
+            metadata.get("pages"),
+            Some(&MetadataValue::FloatArray(vec![4.0, 5.5])),
+            "mixed integers and floats should be stored as floats"
+        );
+
+        for (value, error) in [
+            (
+                json!([]),
+                "Empty array for key tags, Chroma cannot store an empty list",
+            ),
+            (
+                json!(["a", 1]),
+                "Array values for key tags must all have the same type",
+            ),
+            (
+                json!([["a"]]),
+                "Unsupported array value for key tags, expected strings, numbers or booleans",
+            ),
+        ] {
+            let res = get_command_response(
+                &webview,
+                TauriCommand::UpdateRecordMetadata.as_str(),
+                json!({
+                    "collectionName": collection_name,
+                    "id": "doc2",
+                    "metadata": { "tags": value },
+                    "removedKeys": Vec::<String>::new(),
+                }),
+            );
+            assert_eq!(res.err().unwrap(), error);
+        }
+
+        // Collection metadata accepts arrays too.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateCollection.as_str(),
+            json!({
+                "collectionName": "test_collection_array_metadata_created",
+                "metadata": { "owners": ["alice", "bob"] },
+            }),
+        );
+        assert!(res.is_ok(), "create_collection failed: {:?}", res.err());
+
+        let created = rt
+            .block_on(client.get_collection("test_collection_array_metadata_created"))
+            .unwrap();
+        assert_eq!(
+            created.metadata().as_ref().and_then(|m| m.get("owners")),
+            Some(&MetadataValue::StringArray(vec![
+                "alice".to_string(),
+                "bob".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_sparse_vector_from_json() {
+        let parse = |value: Value| sparse_vector_from_json(serde_json::from_value(value).unwrap());
+
+        let vector = parse(json!({
+            "indices": [1, 4],
+            "values": [0.5, -2.0],
+            "tokens": ["a", "b"],
+            "stats": { "nnz": 2 },
+        }))
+        .unwrap();
+        assert_eq!(vector.indices, vec![1, 4]);
+        assert_eq!(vector.values, vec![0.5, -2.0]);
+        assert_eq!(vector.tokens, Some(vec!["a".to_string(), "b".to_string()]));
+
+        for indices in [json!([5, 1]), json!([2, 2])] {
+            assert!(
+                parse(json!({ "indices": indices, "values": [0.1, 0.2] })).is_err(),
+                "indices {} should be rejected",
+                indices
+            );
+        }
+
+        // JSON has no NaN or infinity, but a value too large for f32 becomes one.
+        assert_eq!(
+            parse(json!({ "indices": [1, 2], "values": [0.1, 1e39] })).unwrap_err(),
+            "non-finite value at position 1"
+        );
+        assert_eq!(
+            parse(json!({ "indices": [1], "values": [0.1], "weights": [1] })).unwrap_err(),
+            "unexpected field weights"
+        );
+        assert_eq!(
+            parse(json!({ "indices": [-1], "values": [0.1] })).unwrap_err(),
+            "indices must be a list of non-negative integers"
+        );
+    }
+
+    #[test]
+    fn test_sparse_vector_metadata() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let container = create_recent_chroma_container();
+
+        let host = container.get_host().unwrap();
+        let port = container.get_host_port_ipv4(8000).unwrap();
+
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_sparse_metadata";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let sparse =
+            chroma_types::SparseVector::new(vec![3, 17, 42], vec![0.2, 1.5, -0.7]).unwrap();
+        rt.block_on(collection.add(
+            vec!["doc1".to_string()],
+            vec![vec![0.1_f32, 0.2_f32, 0.3_f32]],
+            Some(vec![Some("First document".to_string())]),
+            None,
+            Some(vec![Some(Metadata::from([(
+                "bm25".to_string(),
+                MetadataValue::SparseVector(sparse),
+            )]))]),
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddings.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "limit": 10,
+                "offset": 0,
+            }),
+        );
+
+        assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
+        let records = res.unwrap().deserialize::<Vec<EmbeddingData>>().unwrap();
+        let bm25 = records
+            .first()
+            .and_then(|record| record.metadata.get("bm25"))
+            .unwrap();
+        assert_eq!(bm25.get("indices"), Some(&json!([3, 17, 42])));
+        assert_eq!(bm25.pointer("/stats/nnz"), Some(&json!(3)));
+        assert_eq!(
+            bm25.pointer("/stats/top")
+                .and_then(Value::as_array)
+                .unwrap()
+                .iter()
+                .map(|t| t.get("index").and_then(Value::as_u64).unwrap())
+                .collect::<Vec<_>>(),
+            vec![17, 42, 3],
+            "top weights should be ordered by magnitude"
+        );
+
+        // Profiling does not choke on sparse vectors.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ProfileMetadata.as_str(),
+            json!({ "collectionName": collection_name }),
+        );
+        assert!(res.is_ok(), "profile_metadata failed: {:?}", res.err());
+        let profile = res.unwrap().deserialize::<MetadataProfile>().unwrap();
+        let bm25_profile = profile.keys.iter().find(|k| k.key == "bm25").unwrap();
+        assert_eq!(bm25_profile.types, vec!["sparse_vector"]);
+        assert_eq!(bm25_profile.distinct, 1);
+
+        // The fetched shape, stats included, can be edited and sent back.
+        let mut edited = bm25.clone();
+        let fields = edited.as_object_mut().unwrap();
+        fields.insert("indices".to_string(), json!([3, 17, 50]));
+        fields.insert("values".to_string(), json!([0.2, 1.5, 0.9]));
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordMetadata.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "metadata": { "bm25": edited },
+                "removedKeys": Vec::<String>::new(),
+            }),
+        );
+        assert!(
+            res.is_ok(),
+            "update_record_metadata failed: {:?}",
+            res.err()
+        );
+
+        let metadata = rt
+            .block_on(collection.get(
+                Some(vec!["doc1".to_string()]),
+                None,
+                Some(1u32),
+                None,
+                Some(IncludeList(vec![Include::Metadata])),
+            ))
+            .unwrap()
+            .metadatas
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .flatten()
+            .expect("record should still have metadata");
+        assert_eq!(
+            metadata.get("bm25"),
+            Some(&MetadataValue::SparseVector(
+                chroma_types::SparseVector::new(vec![3, 17, 50], vec![0.2, 1.5, 0.9]).unwrap()
+            ))
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordMetadata.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "metadata": { "bm25": { "indices": [5, 1], "values": [0.1, 0.2] } },
+                "removedKeys": Vec::<String>::new(),
+            }),
+        );
+        assert!(
+            res.err()
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid sparse vector for key bm25: "),
+            "unsorted indices should be rejected"
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::UpdateRecordMetadata.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "id": "doc1",
+                "metadata": { "bm25": { "indices": [1, 2], "values": [0.1] } },
+                "removedKeys": Vec::<String>::new(),
+            }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Invalid sparse vector for key bm25: Sparse vector indices, values, and tokens (when present) must have the same length"
+        );
     }
 
-    // fn crate_chroma_container_with_auth(
-    //     auth_method: chromadb::v1::client::ChromaAuthMethod,
-    // ) -> Container<GenericImage> {
-    //     let image = GenericImage::new("chromadb/chroma", "0.5.5")
-    //         .with_exposed_port(8000.tcp())
-    //         .with_wait_for(WaitFor::message_on_stdout("Application startup complete."));
-    //
-    //     let image = match auth_method {
-    //         chromadb::v1::client::ChromaAuthMethod::BasicAuth { username, password } => image
-    //             .with_env_var(
-    //                 "CHROMA_SERVER_AUTHN_PROVIDER",
-    //                 "chromadb.auth.basic_authn.BasicAuthenticationServerProvider",
-    //             )
-    //             .with_env_var(
-    //                 "CHROMA_SERVER_AUTHN_CREDENTIALS",
-    //                 format!("{}:{}", username, password),
-    //             ),
-    //         chromadb::v1::client::ChromaAuthMethod::TokenAuth { header, token } => {
-    //             let header = match header {
-    //                 chromadb::v1::client::ChromaTokenHeader::Authorization => "Authorization",
-    //                 chromadb::v1::client::ChromaTokenHeader::XChromaToken => "X-Chroma-Token",
-    //             };
-    //
-    //             image
-    //                 .with_env_var(
-    //                     "CHROMA_SERVER_AUTHN_PROVIDER",
-    //                     "chromadb.auth.token_authn.TokenAuthenticationServerProvider",
-    //                 )
-    //                 .with_env_var("CHROMA_SERVER_AUTHN_CREDENTIALS", token)
-    //                 .with_env_var("CHROMA_AUTH_TOKEN_TRANSPORT_HEADER", header)
-    //         }
-    //         chromadb::v1::client::ChromaAuthMethod::None => image.with_env_var("name", "value"),
-    //     };
-    //
-    //     image.start().expect("ChromaDB started")
-    // }
-
     #[test]
-    fn test_chroma_container() {
+    fn test_load_uri_thumbnail() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
         let host = container.get_host().unwrap();
         let port = container.get_host_port_ipv4(8000).unwrap();
 
-        println!("ChromaDB running at {}:{}", host, port);
+        let connect_url = format!("http://{}:{}", host, port);
+
+        let app = before_each(mock_builder());
+        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
+            .build()
+            .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CreateClient.as_str(),
+            json!({
+                "config": {
+                    "mode": "local",
+                    "url": connect_url,
+                    "tenant": "default_tenant",
+                    "database": "default_database"
+                }
+            }),
+        );
+
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
         let client = ChromaHttpClient::new(ChromaHttpClientOptions {
-            endpoint: format!("http://{}:{}", host, port)
-                .as_str()
-                .parse()
-                .unwrap(),
+            endpoint: connect_url.as_str().parse().unwrap(),
             auth_method: ChromaAuthMethod::None,
             ..Default::default()
         });
 
-        let health_check_result = rt.block_on(client.heartbeat());
-        assert!(health_check_result.is_ok(), "ChromaDB not running");
+        let image_path = env::temp_dir().join("chromamind_test_thumbnail.png");
+        image::RgbImage::from_pixel(600, 300, image::Rgb([200, 30, 30]))
+            .save(&image_path)
+            .unwrap();
+        let image_uri = reqwest::Url::from_file_path(&image_path)
+            .unwrap()
+            .to_string();
+
+        let collection_name = "test_collection_uris";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        rt.block_on(collection.add(
+            vec!["img1".to_string(), "doc1".to_string()],
+            vec![
+                vec![0.1_f32, 0.2_f32, 0.3_f32],
+                vec![0.3_f32, 0.2_f32, 0.1_f32],
+            ],
+            Some(vec![None, Some("Plain text".to_string())]),
+            Some(vec![Some(image_uri.clone()), None]),
+            None,
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddings.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "limit": 10,
+                "offset": 0,
+                "ids": ["img1", "doc1"],
+            }),
+        );
+
+        assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
+        let records = res.unwrap().deserialize::<Vec<EmbeddingData>>().unwrap();
+        let uri_of = |id: &str| records.iter().find(|r| r.id == id).unwrap().uri.clone();
+        assert_eq!(uri_of("img1"), Some(image_uri.clone()));
+        assert_eq!(uri_of("doc1"), None);
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::LoadUriThumbnail.as_str(),
+            json!({ "uri": image_uri }),
+        );
+
+        assert!(res.is_ok(), "load_uri_thumbnail failed: {:?}", res.err());
+        let decode = |data_url: String, mime: &str| {
+            let encoded = data_url
+                .strip_prefix(&format!("data:{};base64,", mime))
+                .unwrap_or_else(|| panic!("expected a {} data url", mime));
+            image::load_from_memory(&BASE64_STANDARD.decode(encoded).unwrap()).unwrap()
+        };
+        let thumbnail = decode(res.unwrap().deserialize::<String>().unwrap(), "image/jpeg");
+        assert_eq!(
+            (thumbnail.width(), thumbnail.height()),
+            (256, 128),
+            "thumbnail should fit the box and keep the aspect ratio"
+        );
+
+        // Small images are not scaled up, and transparency is kept as PNG.
+        let icon_path = env::temp_dir().join("chromamind_test_thumbnail_icon.png");
+        image::RgbaImage::from_pixel(10, 20, image::Rgba([0, 0, 255, 128]))
+            .save(&icon_path)
+            .unwrap();
+        let res = get_command_response(
+            &webview,
+            TauriCommand::LoadUriThumbnail.as_str(),
+            json!({ "uri": reqwest::Url::from_file_path(&icon_path).unwrap().to_string() }),
+        );
+        assert!(res.is_ok(), "load_uri_thumbnail failed: {:?}", res.err());
+        let thumbnail = decode(res.unwrap().deserialize::<String>().unwrap(), "image/png");
+        assert_eq!((thumbnail.width(), thumbnail.height()), (10, 20));
+        assert!(thumbnail.color().has_alpha());
+
+        let broken_path = env::temp_dir().join("chromamind_test_thumbnail_broken.png");
+        std::fs::write(&broken_path, b"\x89PNG\r\n\x1a\nnot really an image").unwrap();
+        let res = get_command_response(
+            &webview,
+            TauriCommand::LoadUriThumbnail.as_str(),
+            json!({ "uri": reqwest::Url::from_file_path(&broken_path).unwrap().to_string() }),
+        );
+        assert!(res
+            .err()
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .starts_with(&format!("Error decoding {}:", broken_path.display())));
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::LoadUriThumbnail.as_str(),
+            json!({ "uri": "https://example.com/cat.png" }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Only file:// uris can be previewed, got https://example.com/cat.png"
+        );
+
+        let text_path = env::temp_dir().join("chromamind_test_thumbnail.txt");
+        std::fs::write(&text_path, "hello").unwrap();
+        let res = get_command_response(
+            &webview,
+            TauriCommand::LoadUriThumbnail.as_str(),
+            json!({ "uri": reqwest::Url::from_file_path(&text_path).unwrap().to_string() }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            format!("Unsupported image type: {}", text_path.display())
+        );
+
+        std::fs::remove_file(image_path).unwrap();
+        std::fs::remove_file(icon_path).unwrap();
+        std::fs::remove_file(broken_path).unwrap();
+        std::fs::remove_file(text_path).unwrap();
     }
 
     #[test]
-    fn test_create_client_local() {
+    fn test_count_records() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
         let host = container.get_host().unwrap();
@@ -1180,18 +8485,19 @@ mod tests {
             .build()
             .unwrap();
 
-        // Invalid config (missing required fields) should fail deserialization
         let res = get_command_response(
             &webview,
-            TauriCommand::CreateClient.as_str(),
-            json!({ "config": { "mode": "local" } }),
+            TauriCommand::CountRecords.as_str(),
+            json!({ "collectionName": "test_collection_count_records" }),
         );
-        assert!(
-            res.is_err(),
-            "create_client should fail with incomplete config"
+
+        assert!(res.is_err(), "count_records should fail without a client");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "count_records failed with different error"
         );
 
-        // Valid local config should succeed
         let res = get_command_response(
             &webview,
             TauriCommand::CreateClient.as_str(),
@@ -1204,56 +8510,199 @@ mod tests {
                 }
             }),
         );
+
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
-        // Client is initialized: health_check should succeed against the real container
-        let res = get_command_response(&webview, TauriCommand::HealthCheck.as_str(), json!({}));
-        assert!(
-            res.is_ok(),
-            "health_check failed after local create_client: {:?}",
-            res.err()
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_count_records";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        let ids: Vec<String> = (0..5).map(|i| format!("doc{}", i)).collect();
+        rt.block_on(
+            collection.add(
+                ids.clone(),
+                (0..5).map(|i| vec![i as f32, 1.0_f32]).collect(),
+                None,
+                None,
+                Some(
+                    (0..5)
+                        .map(|i| {
+                            Some(Metadata::from([(
+                                "group".to_string(),
+                                MetadataValue::Str(if i < 4 { "a" } else { "b" }.to_string()),
+                            )]))
+                        })
+                        .collect(),
+                ),
+            ),
+        )
+        .unwrap();
+
+        let count = |args: Value| -> RowCount {
+            let res = get_command_response(&webview, TauriCommand::CountRecords.as_str(), args);
+            assert!(res.is_ok(), "count_records failed: {:?}", res.err());
+            res.unwrap().deserialize::<RowCount>().unwrap()
+        };
+
+        // Filtered counts stop at the cap; reaching it exactly is not capped.
+        let group_a =
+            json!({ "collectionName": collection_name, "whereFilter": { "group": "a" }, "cap": 3 });
+        assert_eq!(
+            count(group_a.clone()),
+            RowCount {
+                count: 3,
+                capped: true,
+                cached: false
+            }
+        );
+        assert_eq!(
+            count(
+                json!({ "collectionName": collection_name, "whereFilter": { "group": "a" }, "cap": 4 })
+            ),
+            RowCount {
+                count: 4,
+                capped: false,
+                cached: false
+            }
+        );
+        assert_eq!(
+            count(json!({ "collectionName": collection_name, "cap": 2 })),
+            RowCount {
+                count: 2,
+                capped: true,
+                cached: false
+            }
         );
-    }
 
-    #[test]
-    fn test_create_client_cloud() {
-        let app = before_each(mock_builder());
-        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
-            .build()
+        // A repeated filtered count is served from the cache, even though a
+        // write behind the app's back has changed the real answer. Unfiltered
+        // counts are a single request and always live.
+        let unfiltered = json!({ "collectionName": collection_name });
+        assert_eq!(count(unfiltered.clone()).count, 5);
+        rt.block_on(collection.delete(Some(vec!["doc0".to_string()]), None, None))
             .unwrap();
+        assert_eq!(
+            count(unfiltered.clone()),
+            RowCount {
+                count: 4,
+                capped: false,
+                cached: false
+            }
+        );
+        assert_eq!(
+            count(group_a.clone()),
+            RowCount {
+                count: 3,
+                capped: true,
+                cached: true
+            }
+        );
 
-        // Valid cloud config with a fake URL — create_client should succeed (no network call yet)
+        // `refresh` recounts and replaces the cached entry.
+        let mut refreshed = group_a.clone();
+        refreshed
+            .as_object_mut()
+            .unwrap()
+            .insert("refresh".to_string(), json!(true));
+        assert_eq!(
+            count(refreshed),
+            RowCount {
+                count: 3,
+                capped: false,
+                cached: false
+            }
+        );
+        assert_eq!(
+            count(group_a.clone()),
+            RowCount {
+                count: 3,
+                capped: false,
+                cached: true
+            }
+        );
+
+        // A write through the app invalidates the collection's counts.
         let res = get_command_response(
             &webview,
-            TauriCommand::CreateClient.as_str(),
+            TauriCommand::DeleteRecords.as_str(),
+            json!({ "collectionName": collection_name, "ids": ["doc1"] }),
+        );
+        assert!(res.is_ok(), "delete_records failed: {:?}", res.err());
+        assert_eq!(
+            count(unfiltered),
+            RowCount {
+                count: 3,
+                capped: false,
+                cached: false
+            }
+        );
+        assert_eq!(
+            count(group_a),
+            RowCount {
+                count: 2,
+                capped: false,
+                cached: false
+            }
+        );
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchRowCount.as_str(),
             json!({
-                "config": {
-                    "mode": "cloud",
-                    "url": "https://fake.cloud.example.invalid",
-                    "apiKey": "test-api-key",
-                    "database": "test-db"
-                }
+                "collectionName": collection_name,
+                "whereFilter": { "group": "b" },
+                "countId": "count-1",
             }),
         );
-        assert!(
-            res.is_ok(),
-            "create_client failed for cloud config: {:?}",
-            res.err()
-        );
+        assert_eq!(res.unwrap().deserialize::<u32>().unwrap(), 1);
 
-        // health_check must fail with a connection error, NOT "ChromaDB client not initialized"
-        // — this proves the client was set up and auth was configured
-        let res = get_command_response(&webview, TauriCommand::HealthCheck.as_str(), json!({}));
-        assert!(res.is_err(), "health_check should fail against fake URL");
-        assert_ne!(
+        // Finished counts are no longer registered, so there is nothing to cancel.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CancelRowCount.as_str(),
+            json!({ "countId": "count-1" }),
+        );
+        assert!(!res.unwrap().deserialize::<bool>().unwrap());
+
+        // An id that is still running is refused and stays registered.
+        let running = Arc::new(AtomicBool::new(false));
+        webview
+            .state::<AppState>()
+            .active_counts
+            .lock()
+            .insert("count-2".to_string(), running.clone());
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchRowCount.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "whereFilter": { "group": "b" },
+                "countId": "count-2",
+            }),
+        );
+        assert_eq!(
             res.err().unwrap(),
-            "ChromaDB client not initialized",
-            "cloud client should be initialized; expected a connection error"
+            "A row count with id count-2 is already running"
         );
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CancelRowCount.as_str(),
+            json!({ "countId": "count-2" }),
+        );
+        assert!(res.unwrap().deserialize::<bool>().unwrap());
+        assert!(running.load(Ordering::Relaxed));
     }
 
     #[test]
-    fn test_greet() {
+    fn test_stream_embeddings() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
         let host = container.get_host().unwrap();
@@ -1261,43 +8710,41 @@ mod tests {
 
         let connect_url = format!("http://{}:{}", host, port);
 
-        let app = before_each(mock_builder());
+        let chunks = std::sync::Arc::new(Mutex::new(Vec::<RecordChunk>::new()));
+        let received = chunks.clone();
+        let app = before_each(mock_builder().channel_interceptor(
+            move |_webview, _callback, _index, body| {
+                let chunk = match body {
+                    tauri::ipc::InvokeResponseBody::Json(json) => serde_json::from_str(json),
+                    tauri::ipc::InvokeResponseBody::Raw(bytes) => serde_json::from_slice(bytes),
+                };
+                received.lock().push(chunk.unwrap());
+                true
+            },
+        ));
         let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
             .build()
             .unwrap();
 
         let res = get_command_response(
             &webview,
-            TauriCommand::Greet.as_str(),
+            TauriCommand::StreamEmbeddings.as_str(),
             json!({
-                "name": connect_url
+                "collectionName": "test_collection_stream",
+                "limit": 10,
+                "offset": 0,
+                "onChunk": "__CHANNEL__:7",
             }),
-        )
-        .map(|res| res.deserialize::<String>().unwrap());
-
-        assert!(res.is_ok(), "greet failed: {:?}", res.err());
-    }
-
-    #[test]
-    fn test_health_check() {
-        let container = create_chroma_container();
-
-        let host = container.get_host().unwrap();
-        let port = container.get_host_port_ipv4(8000).unwrap();
-
-        let connect_url = format!("http://{}:{}", host, port);
-
-        let app = before_each(mock_builder());
-        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
-            .build()
-            .unwrap();
+        );
 
-        let res = get_command_response(&webview, TauriCommand::HealthCheck.as_str(), json!({}));
-        assert!(res.is_err(), "health_check should fail");
+        assert!(
+            res.is_err(),
+            "stream_embeddings should fail without a client"
+        );
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "health_check failed with different error"
+            "stream_embeddings failed with different error"
         );
 
         let res = get_command_response(
@@ -1315,87 +8762,88 @@ mod tests {
 
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
-        let res = get_command_response(&webview, TauriCommand::HealthCheck.as_str(), json!({}));
-
-        assert!(res.is_ok(), "health_check failed: {:?}", res.err());
-        // assert if res is u64
-        let res = res.unwrap().deserialize::<u64>();
-        assert!(
-            res.is_ok(),
-            "health_check result is not u64: {:?}",
-            res.err()
-        );
-    }
-
-    #[test]
-    fn test_check_tenant_and_database() {
-        let container = create_chroma_container();
-
-        let host = container.get_host().unwrap();
-        let port = container.get_host_port_ipv4(8000).unwrap();
-
-        let connect_url = format!("http://{}:{}", host, port);
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
 
-        let app = before_each(mock_builder());
-        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
-            .build()
+        let collection_name = "test_collection_stream";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
+        let ids: Vec<String> = (0..7).map(|i| format!("doc{}", i)).collect();
+        rt.block_on(
+            collection.add(
+                ids.clone(),
+                (0..7).map(|i| vec![i as f32, 1.0_f32]).collect(),
+                Some(
+                    (0..7)
+                        .map(|i| Some(format!("Dokument nummer {} – ünïcödé", i)))
+                        .collect(),
+                ),
+                None,
+                None,
+            ),
+        )
+        .unwrap();
+
         let res = get_command_response(
             &webview,
-            TauriCommand::CheckTenantAndDatabase.as_str(),
+            TauriCommand::StreamEmbeddings.as_str(),
             json!({
-                "database": "default_database"
+                "collectionName": collection_name,
+                "limit": 6,
+                "offset": 1,
+                "chunkSize": 4,
+                "truncateDocuments": 10,
+                "onChunk": "__CHANNEL__:7",
             }),
         );
 
-        assert!(res.is_err(), "check_tenant_and_database should fail");
+        assert!(res.is_ok(), "stream_embeddings failed: {:?}", res.err());
+        assert_eq!(res.unwrap().deserialize::<usize>().unwrap(), 6);
+
+        let chunks = chunks.lock().clone();
         assert_eq!(
-            res.err().unwrap(),
-            "ChromaDB client not initialized",
-            "check_tenant_and_database failed with different error"
+            chunks
+                .iter()
+                .map(|c| (c.offset, c.records.len()))
+                .collect::<Vec<_>>(),
+            vec![(1, 4), (5, 2)]
         );
+        let records: Vec<&EmbeddingData> = chunks.iter().flat_map(|c| &c.records).collect();
+        assert!(records.iter().all(|r| r.document_truncated));
+        assert!(records.iter().all(|r| r.document.chars().count() == 10));
+        let first = records.first().unwrap();
 
         let res = get_command_response(
             &webview,
-            TauriCommand::CreateClient.as_str(),
+            TauriCommand::FetchDocument.as_str(),
             json!({
-                "config": {
-                    "mode": "local",
-                    "url": connect_url,
-                    "tenant": "default_tenant",
-                    "database": "default_database"
-                }
+                "collectionName": collection_name,
+                "id": first.id,
             }),
         );
-
-        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+        let full = res.unwrap().deserialize::<String>().unwrap();
+        assert!(full.starts_with(&first.document));
+        assert!(full.ends_with("ünïcödé"));
 
         let res = get_command_response(
             &webview,
-            TauriCommand::CheckTenantAndDatabase.as_str(),
+            TauriCommand::FetchDocument.as_str(),
             json!({
-                "database": "default_database"
+                "collectionName": collection_name,
+                "id": "missing",
             }),
         );
-
-        assert!(
-            res.is_ok(),
-            "check_tenant_and_database failed: {:?}",
-            res.err()
-        );
-        // assert if res is bool
-        let res = res.unwrap().deserialize::<bool>();
-        assert!(
-            res.is_ok(),
-            "check_tenant_and_database result is not bool: {:?}",
-            res.err()
-        );
-        assert!(res.unwrap(), "check_tenant_and_database result is not true");
+        assert_eq!(res.err().unwrap(), "Record not found for id missing");
     }
 
     #[test]
-    fn test_get_chroma_version() {
+    fn test_fetch_embeddings_vectors() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
         let host = container.get_host().unwrap();
@@ -1408,14 +8856,23 @@ mod tests {
             .build()
             .unwrap();
 
-        let res =
-            get_command_response(&webview, TauriCommand::GetChromaVersion.as_str(), json!({}));
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddingsVectors.as_str(),
+            json!({
+                "collectionName": "test_collection_vectors",
+                "ids": ["doc1"],
+            }),
+        );
 
-        assert!(res.is_err(), "get_chroma_version should fail");
+        assert!(
+            res.is_err(),
+            "fetch_embeddings_vectors should fail without a client"
+        );
         assert_eq!(
             res.err().unwrap(),
-            "No http context",
-            "get_chroma_version failed with different error"
+            "ChromaDB client not initialized",
+            "fetch_embeddings_vectors failed with different error"
         );
 
         let res = get_command_response(
@@ -1433,78 +8890,84 @@ mod tests {
 
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
-        let res =
-            get_command_response(&webview, TauriCommand::GetChromaVersion.as_str(), json!({}));
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
 
-        assert!(res.is_ok(), "get_chroma_version failed: {:?}", res.err());
-        // assert if res is string
-        let res = res.unwrap().deserialize::<String>();
-        assert!(
-            res.is_ok(),
-            "get_chroma_version result is not string: {:?}",
-            res.err()
+        let collection_name = "test_collection_vectors";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        rt.block_on(collection.add(
+            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
+            vec![
+                vec![0.1_f32, 0.2_f32, 0.3_f32],
+                vec![1.0_f32, -2.0_f32, 3.5_f32],
+                vec![f32::MIN_POSITIVE, 0.0_f32, 1e-7_f32],
+            ],
+            None,
+            None,
+            None,
+        ))
+        .unwrap();
+
+        // Vectors come back in the requested order, bit for bit.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FetchEmbeddingsVectors.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "ids": ["doc3", "doc1", "doc2"],
+            }),
         );
 
-        let actual = res.unwrap();
         assert!(
-            actual.split('.').count() >= 2 && actual.chars().any(|c| c.is_ascii_digit()),
-            "version is not semver-shaped: {actual:?}"
+            res.is_ok(),
+            "fetch_embeddings_vectors failed: {:?}",
+            res.err()
         );
-    }
-
-    #[test]
-    #[should_panic] // FIXME: this test panics because the ChromaDB container does not support the reset command
-    fn test_reset_chroma() {
-        let container = create_chroma_container();
-
-        let host = container.get_host().unwrap();
-        let port = container.get_host_port_ipv4(8000).unwrap();
-
-        let connect_url = format!("http://{}:{}", host, port);
-
-        let app = before_each(mock_builder());
-        let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
-            .build()
-            .unwrap();
-
-        let res = get_command_response(&webview, TauriCommand::ResetChroma.as_str(), json!({}));
-
-        assert!(res.is_err(), "reset_chroma should fail");
+        let InvokeResponseBody::Raw(bytes) = res.unwrap() else {
+            panic!("expected a binary response");
+        };
+        let (header, body) = bytes.split_at(4);
+        assert_eq!(u32::from_le_bytes(header.try_into().unwrap()), 3);
+        let values = decode_f32_le(InvokeResponseBody::Raw(body.to_vec()));
         assert_eq!(
-            res.err().unwrap(),
-            "No client found",
-            "reset_chroma failed with different error"
+            values,
+            vec![f32::MIN_POSITIVE, 0.0, 1e-7, 0.1, 0.2, 0.3, 1.0, -2.0, 3.5]
         );
 
         let res = get_command_response(
             &webview,
-            TauriCommand::CreateClient.as_str(),
+            TauriCommand::FetchEmbeddingsVectors.as_str(),
             json!({
-                "config": {
-                    "mode": "local",
-                    "url": connect_url,
-                    "tenant": "default_tenant",
-                    "database": "default_database"
-                }
+                "collectionName": collection_name,
+                "ids": ["doc1", "missing"],
             }),
         );
+        assert_eq!(res.err().unwrap(), "Embeddings not found for ids missing");
+    }
 
-        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
-
-        let res = get_command_response(&webview, TauriCommand::ResetChroma.as_str(), json!({}));
-
-        assert!(res.is_ok(), "reset_chroma failed: {:?}", res.err());
-        // assert if res is bool
-        let res = res.unwrap().deserialize::<bool>();
-        assert!(
-            res.is_ok(),
-            "reset_chroma result is not bool: {:?}",
-            res.err()
+    #[test]
+    fn test_common_dimension() {
+        // A collection cannot hold mixed lengths, so this is checked directly.
+        let ids = ["doc1", "doc2", "doc3"].map(String::from);
+        assert_eq!(
+            common_dimension(&ids, &[vec![0.1, 0.2], vec![0.3, 0.4], vec![0.5, 0.6]]),
+            Ok(2)
+        );
+        assert_eq!(
+            common_dimension(&ids, &[vec![0.1, 0.2], vec![0.3, 0.4, 0.5], vec![0.6]]),
+            Err("Embedding of doc2 has dimension 3, but the first has 2".to_string())
         );
+        assert_eq!(common_dimension(&[], &[]), Ok(0));
     }
 
     #[test]
-    fn test_fetch_collections() {
+    fn test_profile_metadata() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
@@ -1518,14 +8981,20 @@ mod tests {
             .build()
             .unwrap();
 
-        let res =
-            get_command_response(&webview, TauriCommand::FetchCollections.as_str(), json!({}));
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ProfileMetadata.as_str(),
+            json!({ "collectionName": "test_collection_profile" }),
+        );
 
-        assert!(res.is_err(), "fetch_collections should fail");
+        assert!(
+            res.is_err(),
+            "profile_metadata should fail without a client"
+        );
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "fetch_collections failed with different error"
+            "profile_metadata failed with different error"
         );
 
         let res = get_command_response(
@@ -1544,41 +9013,117 @@ mod tests {
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
         let client = ChromaHttpClient::new(ChromaHttpClientOptions {
-            endpoint: format!("http://{}:{}", host, port)
-                .as_str()
-                .parse()
-                .unwrap(),
+            endpoint: connect_url.as_str().parse().unwrap(),
             auth_method: ChromaAuthMethod::None,
             ..Default::default()
         });
 
-        let collection_name = "test_collection";
-        rt.block_on(client.get_or_create_collection(collection_name, None, None))
+        let collection_name = "test_collection_profile";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
-        let res =
-            get_command_response(&webview, TauriCommand::FetchCollections.as_str(), json!({}));
+        // page: int on every record; source: "web" x5, "pdf" x3, missing x2;
+        // score: a float on odd records and an int on one even record.
+        let metadatas: Vec<Option<Metadata>> = (0..10)
+            .map(|i| {
+                let mut metadata = Metadata::new();
+                metadata.insert("page".to_string(), MetadataValue::Int(i + 1));
+                match i {
+                    0..=4 => {
+                        metadata
+                            .insert("source".to_string(), MetadataValue::Str("web".to_string()));
+                    }
+                    5..=7 => {
+                        metadata
+                            .insert("source".to_string(), MetadataValue::Str("pdf".to_string()));
+                    }
+                    _ => {}
+                }
+                if i % 2 == 1 {
+                    metadata.insert("score".to_string(), MetadataValue::Float(i as f64 / 10.0));
+                } else if i == 4 {
+                    metadata.insert("score".to_string(), MetadataValue::Int(-3));
+                }
+                Some(metadata)
+            })
+            .collect();
+        rt.block_on(collection.add(
+            (0..10).map(|i| format!("doc{}", i)).collect(),
+            (0..10).map(|i| vec![i as f32, 1.0_f32]).collect(),
+            None,
+            None,
+            Some(metadatas),
+        ))
+        .unwrap();
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ProfileMetadata.as_str(),
+            json!({ "collectionName": collection_name }),
+        );
 
-        assert!(res.is_ok(), "fetch_collections failed: {:?}", res.err());
-        // assert if res is Vec<Value>
-        let res = res.unwrap().deserialize::<Vec<Value>>();
-        assert!(
-            res.is_ok(),
-            "fetch_collections result is not Vec<Value>: {:?}",
-            res.err()
+        assert!(res.is_ok(), "profile_metadata failed: {:?}", res.err());
+        let profile = res.unwrap().deserialize::<MetadataProfile>().unwrap();
+        assert_eq!(profile.total, 10);
+        assert_eq!(profile.scanned, 10);
+        assert_eq!(
+            profile
+                .keys
+                .iter()
+                .map(|k| k.key.as_str())
+                .collect::<Vec<_>>(),
+            vec!["page", "source", "score"],
+            "keys should be ordered by presence"
         );
-        let res = res.unwrap();
-        assert!(!res.is_empty(), "fetch_collections result is empty");
-        let collection_data = res.first().unwrap();
-        let expected = collection_data.get("name").unwrap().as_str().unwrap();
+
+        let [page, source, score, ..] = profile.keys.as_slice() else {
+            panic!("expected at least three profiled keys");
+        };
+        assert_eq!(page.types, vec!["int"]);
+        assert_eq!(page.presence, 1.0);
+        assert_eq!(page.distinct, 10);
+        assert_eq!((page.min, page.max), (Some(1.0), Some(10.0)));
+        assert!(page.top_values.is_empty());
+
+        assert_eq!(source.types, vec!["string"]);
+        assert_eq!(source.count, 8);
+        assert_eq!(source.presence, 0.8);
+        assert_eq!(source.distinct, 2);
         assert_eq!(
-            expected, collection_name,
-            "fetch_collections result is not equal to expected"
+            source.top_values,
+            vec![
+                structs::ValueCount {
+                    value: json!("web"),
+                    count: 5
+                },
+                structs::ValueCount {
+                    value: json!("pdf"),
+                    count: 3
+                },
+            ]
+        );
+
+        assert_eq!(score.types, vec!["float", "int"]);
+        assert_eq!(score.count, 6);
+        assert_eq!((score.min, score.max), (Some(-3.0), Some(0.9)));
+
+        // A sample reads only part of the collection.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ProfileMetadata.as_str(),
+            json!({ "collectionName": collection_name, "sampleSize": 4 }),
         );
+        let profile = res.unwrap().deserialize::<MetadataProfile>().unwrap();
+        assert_eq!(profile.total, 10);
+        assert_eq!(profile.scanned, 4);
+        let page = profile.keys.first().unwrap();
+        assert_eq!(page.key, "page");
+        assert_eq!(page.count, 4);
     }
 
     #[test]
-    fn test_fetch_row_count() {
+    fn test_facet_counts() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
@@ -1587,24 +9132,37 @@ mod tests {
 
         let connect_url = format!("http://{}:{}", host, port);
 
-        let app = before_each(mock_builder());
+        let progress = std::sync::Arc::new(Mutex::new(Vec::<ScanProgress>::new()));
+        let received = progress.clone();
+        let app = before_each(mock_builder().channel_interceptor(
+            move |_webview, _callback, _index, body| {
+                let message = match body {
+                    tauri::ipc::InvokeResponseBody::Json(json) => serde_json::from_str(json),
+                    tauri::ipc::InvokeResponseBody::Raw(bytes) => serde_json::from_slice(bytes),
+                };
+                received.lock().push(message.unwrap());
+                true
+            },
+        ));
         let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
             .build()
             .unwrap();
 
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchRowCount.as_str(),
+            TauriCommand::FacetCounts.as_str(),
             json!({
-                "collectionName": "test_collection"
+                "collectionName": "test_collection_facets",
+                "key": "source",
+                "onProgress": "__CHANNEL__:7",
             }),
         );
 
-        assert!(res.is_err(), "fetch_row_count should fail");
+        assert!(res.is_err(), "facet_counts should fail without a client");
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "fetch_row_count failed with different error"
+            "facet_counts failed with different error"
         );
 
         let res = get_command_response(
@@ -1623,53 +9181,141 @@ mod tests {
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
         let client = ChromaHttpClient::new(ChromaHttpClientOptions {
-            endpoint: format!("http://{}:{}", host, port)
-                .as_str()
-                .parse()
-                .unwrap(),
+            endpoint: connect_url.as_str().parse().unwrap(),
             auth_method: ChromaAuthMethod::None,
             ..Default::default()
         });
 
-        let collecton_name = "test_collection";
+        let collection_name = "test_collection_facets";
         let collection = rt
-            .block_on(client.get_or_create_collection(collecton_name, None, None))
+            .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
+        // source: "web" x5, "pdf" x3, "csv" x1, missing x1.
+        let sources = [
+            "web", "web", "web", "web", "web", "pdf", "pdf", "pdf", "csv",
+        ];
+        let metadatas: Vec<Option<Metadata>> = (0..10)
+            .map(|i| {
+                let mut metadata = Metadata::new();
+                metadata.insert("page".to_string(), MetadataValue::Int(i as i64));
+                if let Some(source) = sources.get(i) {
+                    metadata.insert("source".to_string(), MetadataValue::Str(source.to_string()));
+                }
+                Some(metadata)
+            })
+            .collect();
         rt.block_on(collection.add(
-            vec!["doc1".to_string(), "doc2".to_string()],
-            vec![vec![0.1, 0.2], vec![0.3, 0.4]],
-            Some(vec![
-                Some("First document".to_string()),
-                Some("Second document".to_string()),
-            ]),
+            (0..10).map(|i| format!("doc{}", i)).collect(),
+            (0..10).map(|i| vec![i as f32, 1.0_f32]).collect(),
             None,
             None,
+            Some(metadatas),
         ))
         .unwrap();
 
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchRowCount.as_str(),
+            TauriCommand::FacetCounts.as_str(),
             json!({
-                "collectionName": collecton_name
+                "collectionName": collection_name,
+                "key": "source",
+                "topN": 2,
+                "onProgress": "__CHANNEL__:7",
             }),
         );
 
-        assert!(res.is_ok(), "fetch_row_count failed: {:?}", res.err());
+        assert!(res.is_ok(), "facet_counts failed: {:?}", res.err());
+        let facets = res.unwrap().deserialize::<FacetCounts>().unwrap();
+        assert_eq!(facets.key, "source");
+        assert_eq!(facets.scanned, 10);
+        assert_eq!(facets.missing, 1);
+        assert_eq!(facets.distinct, 3);
+        assert_eq!(
+            facets.buckets,
+            vec![
+                ValueCount {
+                    value: json!("web"),
+                    count: 5
+                },
+                ValueCount {
+                    value: json!("pdf"),
+                    count: 3
+                },
+            ]
+        );
+        assert_eq!(facets.other, 1, "csv should be folded into other");
 
-        let res = res.unwrap().deserialize::<usize>();
-        assert!(
-            res.is_ok(),
-            "fetch_row_count result is not usize: {:?}",
-            res.err()
+        let last = progress.lock().last().cloned().unwrap();
+        assert_eq!(last.scanned, 10);
+        assert_eq!(last.total, Some(10));
+
+        // The filter narrows the scan. Counting it up front would walk the
+        // matches twice, so the progress total is left unknown.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FacetCounts.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "key": "source",
+                "whereFilter": { "page": { "$lt": 6 } },
+                "onProgress": "__CHANNEL__:7",
+            }),
+        );
+        let facets = res.unwrap().deserialize::<FacetCounts>().unwrap();
+        assert_eq!(facets.scanned, 6);
+        assert_eq!(facets.other, 0);
+        assert_eq!(
+            facets
+                .buckets
+                .iter()
+                .map(|b| (b.value.clone(), b.count))
+                .collect::<Vec<_>>(),
+            vec![(json!("web"), 5), (json!("pdf"), 1)]
+        );
+        assert_eq!(progress.lock().last().unwrap().total, None);
+
+        // Array values are counted per element, on servers that store them.
+        let mut metadata = Metadata::new();
+        metadata.insert(
+            "tags".to_string(),
+            MetadataValue::StringArray(vec!["a".to_string(), "b".to_string()]),
+        );
+        let added = rt.block_on(collection.add(
+            vec!["tagged".to_string()],
+            vec![vec![0.0_f32, 0.0]],
+            None,
+            None,
+            Some(vec![Some(metadata)]),
+        ));
+        if added.is_err() {
+            return;
+        }
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::FacetCounts.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "key": "tags",
+                "onProgress": "__CHANNEL__:7",
+            }),
+        );
+        let facets = res.unwrap().deserialize::<FacetCounts>().unwrap();
+        assert_eq!(facets.scanned, 11);
+        assert_eq!(facets.missing, 10);
+        assert_eq!(
+            facets
+                .buckets
+                .iter()
+                .map(|b| (b.value.clone(), b.count))
+                .collect::<Vec<_>>(),
+            vec![(json!("a"), 1), (json!("b"), 1)]
         );
-        let res = res.unwrap();
-        assert_eq!(res, 2, "fetch_row_count result is not equal to expected");
     }
 
     #[test]
-    fn test_fetch_embeddings() {
+    fn test_metadata_histogram() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
@@ -1678,26 +9324,40 @@ mod tests {
 
         let connect_url = format!("http://{}:{}", host, port);
 
-        let app = before_each(mock_builder());
+        let progress = std::sync::Arc::new(Mutex::new(Vec::<ScanProgress>::new()));
+        let received = progress.clone();
+        let app = before_each(mock_builder().channel_interceptor(
+            move |_webview, _callback, _index, body| {
+                let message = match body {
+                    tauri::ipc::InvokeResponseBody::Json(json) => serde_json::from_str(json),
+                    tauri::ipc::InvokeResponseBody::Raw(bytes) => serde_json::from_slice(bytes),
+                };
+                received.lock().push(message.unwrap());
+                true
+            },
+        ));
         let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
             .build()
             .unwrap();
 
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchEmbeddings.as_str(),
+            TauriCommand::MetadataHistogram.as_str(),
             json!({
-                "collectionName": "test_collection",
-                "limit": 0,
-                "offset": 0
+                "collectionName": "test_collection_histogram",
+                "key": "page",
+                "onProgress": "__CHANNEL__:7",
             }),
         );
 
-        assert!(res.is_err(), "fetch_embeddings should fail");
+        assert!(
+            res.is_err(),
+            "metadata_histogram should fail without a client"
+        );
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "fetch_embeddings failed with different error"
+            "metadata_histogram failed with different error"
         );
 
         let res = get_command_response(
@@ -1716,92 +9376,211 @@ mod tests {
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
         let client = ChromaHttpClient::new(ChromaHttpClientOptions {
-            endpoint: format!("http://{}:{}", host, port)
-                .as_str()
-                .parse()
-                .unwrap(),
+            endpoint: connect_url.as_str().parse().unwrap(),
             auth_method: ChromaAuthMethod::None,
             ..Default::default()
         });
 
-        let collecton_name = "test_collection";
+        let collection_name = "test_collection_histogram";
         let collection = rt
-            .block_on(client.get_or_create_collection(collecton_name, None, None))
+            .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
-        let ids = vec!["doc1".to_string(), "doc2".to_string()];
+        // published: three dates in January, two in February, four in March
+        // and one that is not a date; timestamp: one day apart from 2024-01-01.
+        let published = [
+            "2024-01-15",
+            "2024-01-20T10:00:00Z",
+            "2024-01-31 23:59:59",
+            "2024-02-03",
+            "2024-02-28T12:00:00+00:00",
+            "2024-03-01",
+            "2024-03-10",
+            "2024-03-15T08:30",
+            "2024-03-31",
+            "soon",
+        ];
+        let metadatas: Vec<Option<Metadata>> = published
+            .iter()
+            .enumerate()
+            .map(|(i, published)| {
+                let mut metadata = Metadata::new();
+                metadata.insert("page".to_string(), MetadataValue::Int(i as i64 + 1));
+                metadata.insert(
+                    "published".to_string(),
+                    MetadataValue::Str(published.to_string()),
+                );
+                metadata.insert(
+                    "timestamp".to_string(),
+                    MetadataValue::Int(1_704_067_200 + i as i64 * 86_400),
+                );
+                Some(metadata)
+            })
+            .collect();
         rt.block_on(collection.add(
-            ids.clone(),
-            vec![vec![0.1, 0.2], vec![0.3, 0.4]],
-            Some(vec![
-                Some("First document".to_string()),
-                Some("Second document".to_string()),
-            ]),
+            (0..10).map(|i| format!("doc{}", i)).collect(),
+            (0..10).map(|i| vec![i as f32, 1.0_f32]).collect(),
             None,
             None,
+            Some(metadatas),
         ))
         .unwrap();
 
-        let res = get_command_response(
-            &webview,
-            TauriCommand::FetchEmbeddings.as_str(),
-            json!({
-                "collectionName": collecton_name,
-                "limit": 5,
-                "offset": 0
-            }),
-        );
+        let histogram = |key: &str, extra: Value| {
+            let mut args = json!({
+                "collectionName": collection_name,
+                "key": key,
+                "onProgress": "__CHANNEL__:7",
+            });
+            args.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            get_command_response(&webview, TauriCommand::MetadataHistogram.as_str(), args)
+        };
+        let counts = |histogram: &Histogram| {
+            histogram
+                .bins
+                .iter()
+                .map(|bin| bin.count)
+                .collect::<Vec<_>>()
+        };
 
-        assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
-        // assert if res is Vec<EmbeddingData>
-        let res = res.unwrap().deserialize::<Vec<EmbeddingData>>();
-        assert!(
-            res.is_ok(),
-            "fetch_embeddings result is not Vec<EmbeddingData>: {:?}",
-            res.err()
+        // Few distinct integers get one bin each.
+        let res = histogram("page", json!({}));
+        assert!(res.is_ok(), "metadata_histogram failed: {:?}", res.err());
+        let page = res.unwrap().deserialize::<Histogram>().unwrap();
+        assert_eq!(page.kind, structs::HistogramKind::Numeric);
+        assert_eq!(page.unit, None);
+        assert_eq!((page.min, page.max), (Some(1.0), Some(10.0)));
+        assert_eq!(counts(&page), vec![1; 10]);
+        assert_eq!(page.bins.first().map(|bin| bin.start), Some(1.0));
+        assert_eq!(page.bins.last().map(|bin| bin.end), Some(11.0));
+        let last = progress.lock().last().cloned().unwrap();
+        assert_eq!((last.scanned, last.total), (10, Some(10)));
+
+        let res = histogram("page", json!({ "options": { "bins": 3 } }));
+        let page = res.unwrap().deserialize::<Histogram>().unwrap();
+        assert_eq!(counts(&page), vec![3, 3, 4]);
+        assert_eq!(
+            page.bins.last().map(|bin| bin.end),
+            Some(10.0),
+            "the last bin ends at the max"
         );
 
-        let res = res.unwrap();
-        let expected = ids.clone();
-
+        let res = histogram(
+            "page",
+            json!({ "options": { "bins": 2, "min": 3, "max": 6.5 } }),
+        );
+        let page = res.unwrap().deserialize::<Histogram>().unwrap();
+        assert_eq!(counts(&page), vec![2, 2]);
+        assert_eq!(page.outside, 6);
+
+        // The filter narrows the scan.
+        let res = histogram("page", json!({ "whereFilter": { "page": { "$lte": 5 } } }));
+        let page = res.unwrap().deserialize::<Histogram>().unwrap();
+        assert_eq!(page.scanned, 5);
+        assert_eq!(counts(&page), vec![1; 5]);
+        assert_eq!(progress.lock().last().unwrap().total, None);
+
+        // ISO-8601 strings are dates, bucketed by the finest unit that fits.
+        let res = histogram("published", json!({}));
+        let published = res.unwrap().deserialize::<Histogram>().unwrap();
+        assert_eq!(published.kind, structs::HistogramKind::Date);
+        assert_eq!(published.unit, Some(structs::DateUnit::Month));
+        assert_eq!(published.invalid, 1);
+        assert_eq!(counts(&published), vec![3, 2, 4]);
         assert_eq!(
-            expected,
-            res.iter().map(|x| x.id.clone()).collect::<Vec<_>>()
+            published
+                .bins
+                .iter()
+                .map(|bin| bin.label.clone().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["2024-01", "2024-02", "2024-03"]
+        );
+        assert_eq!(
+            published.bins.first().map(|bin| bin.start),
+            Some(1_704_067_200.0)
+        );
+        assert_eq!(
+            published.bins.last().map(|bin| bin.end),
+            Some(1_711_929_600.0)
         );
 
-        let res = get_command_response(
-            &webview,
-            TauriCommand::FetchEmbeddings.as_str(),
-            json!({
-                "collectionName": collecton_name,
-                "limit": 0,
-                "offset": 0
-            }),
+        // Epoch seconds are detected as dates too.
+        let res = histogram("timestamp", json!({}));
+        let timestamp = res.unwrap().deserialize::<Histogram>().unwrap();
+        assert_eq!(timestamp.kind, structs::HistogramKind::Date);
+        assert_eq!(timestamp.unit, Some(structs::DateUnit::Day));
+        assert_eq!(counts(&timestamp), vec![1; 10]);
+        assert_eq!(
+            timestamp.bins.first().and_then(|bin| bin.label.as_deref()),
+            Some("2024-01-01")
         );
 
-        assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
-        let res = res.unwrap().deserialize::<Vec<EmbeddingData>>();
-        let res = res.unwrap();
-        assert!(
-            res.is_empty(),
-            "fetch_collection_data should return empty vec since limit is 0"
+        let res = histogram(
+            "timestamp",
+            json!({ "options": { "kind": "numeric", "bins": 1 } }),
         );
+        let timestamp = res.unwrap().deserialize::<Histogram>().unwrap();
+        assert_eq!(timestamp.kind, structs::HistogramKind::Numeric);
+        assert_eq!(counts(&timestamp), vec![10]);
+
+        let res = histogram("timestamp", json!({ "options": { "unit": "month" } }));
+        let timestamp = res.unwrap().deserialize::<Histogram>().unwrap();
+        assert_eq!(counts(&timestamp), vec![10]);
+
+        let res = histogram("absent", json!({}));
+        let absent = res.unwrap().deserialize::<Histogram>().unwrap();
+        assert_eq!(absent.missing, 10);
+        assert!(absent.bins.is_empty());
+        assert_eq!(absent.min, None);
+
+        let res = histogram("page", json!({ "options": { "bins": 0 } }));
+        assert_eq!(res.err().unwrap(), "Bin count must be between 1 and 1000");
     }
 
     #[test]
-    fn test_fetch_embeddings_and_row_count_filters() {
+    fn test_scan_embeddings() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
         let host = container.get_host().unwrap();
         let port = container.get_host_port_ipv4(8000).unwrap();
+
         let connect_url = format!("http://{}:{}", host, port);
 
-        let app = before_each(mock_builder());
+        let progress = std::sync::Arc::new(Mutex::new(Vec::<ScanProgress>::new()));
+        let received = progress.clone();
+        let app = before_each(mock_builder().channel_interceptor(
+            move |_webview, _callback, _index, body| {
+                let message = match body {
+                    tauri::ipc::InvokeResponseBody::Json(json) => serde_json::from_str(json),
+                    tauri::ipc::InvokeResponseBody::Raw(bytes) => serde_json::from_slice(bytes),
+                };
+                received.lock().push(message.unwrap());
+                true
+            },
+        ));
         let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
             .build()
             .unwrap();
 
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ScanEmbeddings.as_str(),
+            json!({
+                "collectionName": "test_collection_health",
+                "onProgress": "__CHANNEL__:7",
+            }),
+        );
+
+        assert!(res.is_err(), "scan_embeddings should fail without a client");
+        assert_eq!(
+            res.err().unwrap(),
+            "ChromaDB client not initialized",
+            "scan_embeddings failed with different error"
+        );
+
         let res = get_command_response(
             &webview,
             TauriCommand::CreateClient.as_str(),
@@ -1814,6 +9593,7 @@ mod tests {
                 }
             }),
         );
+
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
         let client = ChromaHttpClient::new(ChromaHttpClientOptions {
@@ -1822,124 +9602,97 @@ mod tests {
             ..Default::default()
         });
 
-        let collection_name = "filter_collection";
+        let collection_name = "test_collection_health";
         let collection = rt
             .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
-        let ids = vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()];
-        let metadatas: Vec<Option<Metadata>> = vec![
-            Some(HashMap::from([
-                ("page".to_string(), MetadataValue::Int(1)),
-                ("category".to_string(), MetadataValue::Str("a".to_string())),
-            ])),
-            Some(HashMap::from([
-                ("page".to_string(), MetadataValue::Int(5)),
-                ("category".to_string(), MetadataValue::Str("b".to_string())),
-            ])),
-            Some(HashMap::from([
-                ("page".to_string(), MetadataValue::Int(10)),
-                ("category".to_string(), MetadataValue::Str("a".to_string())),
-            ])),
+        // Three unit vectors, a zero vector and vectors of norm 2 and 5.
+        let embeddings = vec![
+            vec![1.0_f32, 0.0],
+            vec![0.0, 1.0],
+            vec![0.6, 0.8],
+            vec![0.0, 0.0],
+            vec![2.0, 0.0],
+            vec![3.0, 4.0],
         ];
+        let metadatas: Vec<Option<Metadata>> = (0..6)
+            .map(|i| {
+                let mut metadata = Metadata::new();
+                metadata.insert("unit".to_string(), MetadataValue::Bool(i < 3));
+                Some(metadata)
+            })
+            .collect();
         rt.block_on(collection.add(
-            ids.clone(),
-            vec![vec![0.1, 0.2], vec![0.3, 0.4], vec![0.5, 0.6]],
-            Some(vec![
-                Some("apple pie".to_string()),
-                Some("banana bread".to_string()),
-                Some("cherry cake".to_string()),
-            ]),
+            (0..6).map(|i| format!("doc{}", i)).collect(),
+            embeddings,
+            None,
             None,
             Some(metadatas),
         ))
         .unwrap();
 
-        // Helper to fetch ids for a given fetch_embeddings payload.
-        let fetch_ids = |payload: serde_json::Value| -> Vec<String> {
-            let res =
-                get_command_response(&webview, TauriCommand::FetchEmbeddings.as_str(), payload);
-            assert!(res.is_ok(), "fetch_embeddings failed: {:?}", res.err());
-            let mut ids = res
-                .unwrap()
-                .deserialize::<Vec<EmbeddingData>>()
-                .unwrap()
-                .into_iter()
-                .map(|x| x.id)
-                .collect::<Vec<_>>();
-            ids.sort();
-            ids
-        };
-
-        // Metadata filter: page > 4 -> doc2, doc3
-        assert_eq!(
-            fetch_ids(json!({
-                "collectionName": collection_name,
-                "limit": 100,
-                "offset": 0,
-                "whereFilter": { "page": { "$gt": 4 } }
-            })),
-            vec!["doc2".to_string(), "doc3".to_string()]
-        );
-
-        // Metadata filter: category == "a" -> doc1, doc3
-        assert_eq!(
-            fetch_ids(json!({
-                "collectionName": collection_name,
-                "limit": 100,
-                "offset": 0,
-                "whereFilter": { "category": { "$eq": "a" } }
-            })),
-            vec!["doc1".to_string(), "doc3".to_string()]
-        );
-
-        // Composite $and: page >= 5 AND category == "a" -> doc3
-        assert_eq!(
-            fetch_ids(json!({
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ScanEmbeddings.as_str(),
+            json!({
                 "collectionName": collection_name,
-                "limit": 100,
-                "offset": 0,
-                "whereFilter": { "$and": [
-                    { "page": { "$gte": 5 } },
-                    { "category": { "$eq": "a" } }
-                ]}
-            })),
-            vec!["doc3".to_string()]
+                "bins": 4,
+                "onProgress": "__CHANNEL__:7",
+            }),
         );
 
-        // ID lookup -> doc1
+        assert!(res.is_ok(), "scan_embeddings failed: {:?}", res.err());
+        let health = res.unwrap().deserialize::<EmbeddingHealth>().unwrap();
+        assert_eq!(health.scanned, 6);
+        assert_eq!(health.dimension, Some(2));
+        assert_eq!(health.dimension_mismatches.count, 0);
+        assert_eq!(health.non_finite.count, 0);
+        assert_eq!(health.zero.count, 1);
+        assert_eq!(health.zero.ids, vec!["doc3"]);
+        assert_eq!(health.normalized, 3);
+        assert!(!health.looks_normalized);
+
+        let norms = health.norms.unwrap();
+        assert!((norms.min - 1.0).abs() < 1e-6);
+        assert!((norms.mean - 2.0).abs() < 1e-6);
+        assert!((norms.max - 5.0).abs() < 1e-6);
         assert_eq!(
-            fetch_ids(json!({
-                "collectionName": collection_name,
-                "limit": 100,
-                "offset": 0,
-                "ids": ["doc1"]
-            })),
-            vec!["doc1".to_string()]
+            norms.bins.iter().map(|bin| bin.count).collect::<Vec<_>>(),
+            vec![3, 1, 0, 1]
         );
 
-        // Unfiltered row count -> 3
+        let last = progress.lock().last().cloned().unwrap();
+        assert_eq!((last.scanned, last.total), (6, Some(6)));
+
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchRowCount.as_str(),
-            json!({ "collectionName": collection_name }),
+            TauriCommand::ScanEmbeddings.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "whereFilter": { "unit": true },
+                "onProgress": "__CHANNEL__:7",
+            }),
         );
-        assert_eq!(res.unwrap().deserialize::<u32>().unwrap(), 3);
+        let health = res.unwrap().deserialize::<EmbeddingHealth>().unwrap();
+        assert_eq!(health.scanned, 3);
+        assert_eq!(health.zero.count, 0);
+        assert!(health.looks_normalized);
 
-        // Filtered row count: page > 4 -> 2
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchRowCount.as_str(),
+            TauriCommand::ScanEmbeddings.as_str(),
             json!({
                 "collectionName": collection_name,
-                "whereFilter": { "page": { "$gt": 4 } }
+                "bins": 0,
+                "onProgress": "__CHANNEL__:7",
             }),
         );
-        assert_eq!(res.unwrap().deserialize::<u32>().unwrap(), 2);
+        assert_eq!(res.err().unwrap(), "Bin count must be between 1 and 1000");
     }
 
     #[test]
-    fn test_fetch_embedding() {
+    fn test_find_duplicates() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
@@ -1948,25 +9701,28 @@ mod tests {
 
         let connect_url = format!("http://{}:{}", host, port);
 
-        let app = before_each(mock_builder());
+        let app = before_each(
+            mock_builder().channel_interceptor(|_webview, _callback, _index, _body| true),
+        );
         let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
             .build()
             .unwrap();
 
-        // Should fail without client initialized
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchEmbedding.as_str(),
+            TauriCommand::FindDuplicates.as_str(),
             json!({
-                "collectionName": "test_collection",
-                "id": "doc1"
+                "collectionName": "test_collection_duplicates",
+                "mode": { "type": "document" },
+                "onProgress": "__CHANNEL__:7",
             }),
         );
-        assert!(res.is_err(), "fetch_embedding should fail without client");
+
+        assert!(res.is_err(), "find_duplicates should fail without a client");
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "fetch_embedding failed with different error"
+            "find_duplicates failed with different error"
         );
 
         let res = get_command_response(
@@ -1981,88 +9737,157 @@ mod tests {
                 }
             }),
         );
+
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
         let client = ChromaHttpClient::new(ChromaHttpClientOptions {
-            endpoint: format!("http://{}:{}", host, port)
-                .as_str()
-                .parse()
-                .unwrap(),
+            endpoint: connect_url.as_str().parse().unwrap(),
             auth_method: ChromaAuthMethod::None,
             ..Default::default()
         });
 
-        let collection_name = "test_collection_embed";
+        let collection_name = "test_collection_duplicates";
         let collection = rt
             .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
-        let ids = vec!["doc1".to_string(), "doc2".to_string()];
-        let embeddings = vec![
-            vec![0.1_f32, 0.2_f32, 0.3_f32],
-            vec![0.4_f32, 0.5_f32, 0.6_f32],
-        ];
-        rt.block_on(collection.add(
-            ids.clone(),
-            embeddings.clone(),
-            Some(vec![
-                Some("First document".to_string()),
-                Some("Second document".to_string()),
-            ]),
-            None,
-            None,
-        ))
+        // doc2 copies doc0 exactly and doc3 nearly; doc5 repeats doc1's text
+        // with a vector pointing the same way but twice as long.
+        rt.block_on(
+            collection.add(
+                (0..6).map(|i| format!("doc{}", i)).collect(),
+                vec![
+                    vec![1.0_f32, 0.0, 0.0],
+                    vec![0.0, 1.0, 0.0],
+                    vec![1.0, 0.0, 0.0],
+                    vec![0.999, 0.01, 0.0],
+                    vec![0.0, 0.0, 1.0],
+                    vec![0.0, 2.0, 0.0],
+                ],
+                Some(
+                    ["alpha", "beta", "alpha", "alpha", "gamma", "beta"]
+                        .iter()
+                        .map(|d| Some(d.to_string()))
+                        .collect(),
+                ),
+                None,
+                None,
+            ),
+        )
         .unwrap();
 
-        // Fetch embedding for doc1
+        let find = |mode: Value| {
+            let res = get_command_response(
+                &webview,
+                TauriCommand::FindDuplicates.as_str(),
+                json!({
+                    "collectionName": collection_name,
+                    "mode": mode,
+                    "onProgress": "__CHANNEL__:7",
+                }),
+            );
+            assert!(res.is_ok(), "find_duplicates failed: {:?}", res.err());
+            res.unwrap().deserialize::<DuplicateReport>().unwrap()
+        };
+        let group = |keep: &str, duplicates: &[&str]| structs::DuplicateGroup {
+            keep: keep.to_string(),
+            duplicates: duplicates.iter().map(|id| id.to_string()).collect(),
+        };
+
+        let report = find(json!({ "type": "document" }));
+        assert_eq!(report.scanned, 6);
+        assert_eq!(report.skipped, 0);
+        assert_eq!(
+            report.groups,
+            vec![group("doc0", &["doc2", "doc3"]), group("doc1", &["doc5"])]
+        );
+        assert_eq!(report.delete_ids, vec!["doc2", "doc3", "doc5"]);
+
+        let report = find(json!({ "type": "vector" }));
+        assert_eq!(report.groups, vec![group("doc0", &["doc2"])]);
+        assert_eq!(report.delete_ids, vec!["doc2"]);
+
+        let report = find(json!({ "type": "near" }));
+        assert_eq!(
+            report.groups,
+            vec![group("doc0", &["doc2", "doc3"]), group("doc1", &["doc5"])]
+        );
+
+        let report = find(json!({ "type": "near", "threshold": 0.0 }));
+        assert_eq!(
+            report.groups,
+            vec![group("doc0", &["doc2"]), group("doc1", &["doc5"])]
+        );
+
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchEmbedding.as_str(),
+            TauriCommand::FindDuplicates.as_str(),
             json!({
                 "collectionName": collection_name,
-                "id": "doc1"
+                "mode": { "type": "near", "threshold": 3.0 },
+                "onProgress": "__CHANNEL__:7",
             }),
         );
-        assert!(res.is_ok(), "fetch_embedding failed: {:?}", res.err());
-        let embedding = res.unwrap().deserialize::<Vec<f32>>();
-        assert!(
-            embedding.is_ok(),
-            "fetch_embedding result is not Vec<f32>: {:?}",
-            embedding.err()
-        );
-        let embedding = embedding.unwrap();
         assert_eq!(
-            embedding.len(),
-            3,
-            "fetch_embedding returned wrong dimension count"
-        );
-        assert!(
-            (embedding[0] - 0.1_f32).abs() < 1e-5_f32,
-            "embedding[0] mismatch"
-        );
-        assert!(
-            (embedding[1] - 0.2_f32).abs() < 1e-5_f32,
-            "embedding[1] mismatch"
-        );
-        assert!(
-            (embedding[2] - 0.3_f32).abs() < 1e-5_f32,
-            "embedding[2] mismatch"
+            res.err().unwrap(),
+            "Threshold must be a cosine distance between 0 and 2, got 3"
         );
 
-        // Fetch embedding for unknown id should error
+        // The plan feeds delete_records and leaves one record per group.
+        let report = find(json!({ "type": "document" }));
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchEmbedding.as_str(),
+            TauriCommand::DeleteRecords.as_str(),
             json!({
                 "collectionName": collection_name,
-                "id": "nonexistent_id"
+                "ids": report.delete_ids,
             }),
         );
-        assert!(res.is_err(), "fetch_embedding should fail for unknown id");
+        assert!(res.is_ok(), "delete_records failed: {:?}", res.err());
+
+        let report = find(json!({ "type": "document" }));
+        assert_eq!(report.scanned, 3);
+        assert!(report.groups.is_empty());
+        assert!(report.delete_ids.is_empty());
     }
 
     #[test]
-    fn test_fetch_collection_data() {
+    fn test_histogram_one_sided_bound() {
+        let histogram = |options: Value| {
+            let options: HistogramOptions = serde_json::from_value(options).unwrap();
+            let mut histogrammer = Histogrammer::new("page", options)?;
+            for page in 1..=5 {
+                histogrammer.add(Some(&Metadata::from([(
+                    "page".to_string(),
+                    MetadataValue::Int(page),
+                )])));
+            }
+            histogrammer.finish()
+        };
+
+        // A min above every value has nothing in range, which is not an error.
+        let above = histogram(json!({ "min": 10 })).unwrap();
+        assert_eq!((above.min, above.max), (Some(10.0), Some(5.0)));
+        assert!(above.bins.is_empty());
+        assert_eq!(above.outside, 5);
+
+        let below = histogram(json!({ "max": -1 })).unwrap();
+        assert!(below.bins.is_empty());
+        assert_eq!(below.outside, 5);
+
+        let within = histogram(json!({ "min": 3 })).unwrap();
+        assert_eq!(within.bins.iter().map(|bin| bin.count).sum::<usize>(), 3);
+        assert_eq!(within.outside, 2);
+
+        // Bounds given together must still be in order.
+        assert_eq!(
+            histogram(json!({ "min": 10, "max": 5 })).err().unwrap(),
+            "Histogram min 10 is greater than its max 5"
+        );
+    }
+
+    #[test]
+    fn test_project_embeddings() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
@@ -2078,18 +9903,18 @@ mod tests {
 
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchCollectionData.as_str(),
-            json!({
-                "collectionName": "test_collection"
-            }),
+            TauriCommand::ProjectEmbeddings.as_str(),
+            json!({ "collectionName": "test_collection_projection" }),
         );
 
-        assert!(res.is_err(), "fetch_collection_data should fail");
-
+        assert!(
+            res.is_err(),
+            "project_embeddings should fail without a client"
+        );
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "fetch_collection_data failed with different error"
+            "project_embeddings failed with different error"
         );
 
         let res = get_command_response(
@@ -2108,62 +9933,141 @@ mod tests {
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
         let client = ChromaHttpClient::new(ChromaHttpClientOptions {
-            endpoint: format!("http://{}:{}", host, port)
-                .as_str()
-                .parse()
-                .unwrap(),
+            endpoint: connect_url.as_str().parse().unwrap(),
             auth_method: ChromaAuthMethod::None,
             ..Default::default()
         });
 
-        let collection_name = "test_collection";
+        let collection_name = "test_collection_projection";
         let collection = rt
-            .block_on(client.get_or_create_collection(
-                collection_name,
-                None,
-                Some(HashMap::from([(
-                    "foo".to_string(),
-                    MetadataValue::Str("bar".to_string()),
-                )])),
-            ))
+            .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
+        // Points along the (1, 1, 0) diagonal with a little alternating noise.
+        let metadatas: Vec<Option<Metadata>> = (0..10)
+            .map(|i| {
+                let mut metadata = Metadata::new();
+                let half = if i < 5 { "low" } else { "high" };
+                metadata.insert("half".to_string(), MetadataValue::Str(half.to_string()));
+                Some(metadata)
+            })
+            .collect();
+        rt.block_on(
+            collection.add(
+                (0..10).map(|i| format!("doc{}", i)).collect(),
+                (0..10)
+                    .map(|i| {
+                        let noise = if i % 2 == 0 { 0.1 } else { -0.1 };
+                        vec![i as f32, i as f32, noise]
+                    })
+                    .collect(),
+                None,
+                None,
+                Some(metadatas),
+            ),
+        )
+        .unwrap();
+
         let res = get_command_response(
             &webview,
-            TauriCommand::FetchCollectionData.as_str(),
-            json!({
-                "collectionName": collection_name
-            }),
+            TauriCommand::ProjectEmbeddings.as_str(),
+            json!({ "collectionName": collection_name, "colorKey": "half" }),
         );
 
-        assert!(res.is_ok(), "fetch_collection_data failed: {:?}", res.err());
-
-        // assert if res is Value
-        let res = res.unwrap().deserialize::<Value>();
+        assert!(res.is_ok(), "project_embeddings failed: {:?}", res.err());
+        let projection = res.unwrap().deserialize::<Projection>().unwrap();
+        assert_eq!(projection.total, 10);
+        assert_eq!(projection.skipped, 0);
+        assert_eq!(projection.points.len(), 10);
+        assert!(projection.points.iter().all(|p| p.coordinates.len() == 2));
+
+        let explained = projection.explained_variance.unwrap();
+        assert_eq!(explained.len(), 2);
+        let [major, minor] = explained.as_slice() else {
+            panic!("expected two explained variance ratios");
+        };
+        assert!(*major > 0.99, "the diagonal should dominate");
+        assert!((major + minor - 1.0).abs() < 1e-6);
+
+        let first_axis: Vec<f32> = projection
+            .points
+            .iter()
+            .filter_map(|p| p.coordinates.first().copied())
+            .collect();
+        assert_eq!(first_axis.len(), 10);
         assert!(
-            res.is_ok(),
-            "fetch_collection_data result is not Value: {:?}",
-            res.err()
+            first_axis.windows(2).all(|w| w.first() < w.last()),
+            "the first axis should follow the diagonal: {:?}",
+            first_axis
+        );
+        assert!(
+            (first_axis.last().unwrap() - first_axis.first().unwrap() - 9.0 * 2.0_f32.sqrt()).abs()
+                < 1e-2,
+            "PCA should keep distances along the diagonal"
+        );
+        let (first, last) = (
+            projection.points.first().unwrap(),
+            projection.points.last().unwrap(),
+        );
+        assert_eq!(first.id, "doc0");
+        assert_eq!(first.value, Some(json!("low")));
+        assert_eq!(last.value, Some(json!("high")));
+
+        let random = |seed: u64| {
+            let res = get_command_response(
+                &webview,
+                TauriCommand::ProjectEmbeddings.as_str(),
+                json!({
+                    "collectionName": collection_name,
+                    "method": { "type": "random", "seed": seed },
+                    "dimensions": 3,
+                    "whereFilter": { "half": "low" },
+                }),
+            );
+            assert!(res.is_ok(), "project_embeddings failed: {:?}", res.err());
+            res.unwrap().deserialize::<Projection>().unwrap()
+        };
+        let projection = random(7);
+        assert_eq!(projection.total, 5);
+        assert_eq!(projection.points.len(), 5);
+        assert!(projection.explained_variance.is_none());
+        assert!(projection.points.iter().all(|p| p.coordinates.len() == 3));
+        assert!(projection.points.iter().all(|p| p.value.is_none()));
+        assert_eq!(
+            projection
+                .points
+                .iter()
+                .map(|p| p.coordinates.clone())
+                .collect::<Vec<_>>(),
+            random(7)
+                .points
+                .iter()
+                .map(|p| p.coordinates.clone())
+                .collect::<Vec<_>>(),
+            "the same seed should give the same layout"
         );
 
-        let res = res.unwrap();
-        let expected = json!({
-            "id": collection.id(),
-            "metadata": collection.metadata(),
-            // TODO: need to get actual config
-            // "configuration": collection.configuration_json(),
-            "configuration": {},
-            "dimension": None::<u32>,
-        });
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ProjectEmbeddings.as_str(),
+            json!({ "collectionName": collection_name, "sampleSize": 4 }),
+        );
+        let projection = res.unwrap().deserialize::<Projection>().unwrap();
+        assert_eq!(projection.points.len(), 4);
 
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ProjectEmbeddings.as_str(),
+            json!({ "collectionName": collection_name, "dimensions": 4 }),
+        );
         assert_eq!(
-            expected, res,
-            "fetch_collection_data result is not equal to expected"
+            res.err().unwrap(),
+            "Projections have 2 or 3 dimensions, got 4"
         );
     }
 
     #[test]
-    fn test_create_collection() {
+    fn test_cluster_embeddings() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
@@ -2179,21 +10083,18 @@ mod tests {
 
         let res = get_command_response(
             &webview,
-            TauriCommand::CreateCollection.as_str(),
-            json!({
-                "collectionName": "test_collection",
-                "metadata": {
-                    "foo": "bar"
-                }
-            }),
+            TauriCommand::ClusterEmbeddings.as_str(),
+            json!({ "collectionName": "test_collection_clusters" }),
         );
 
-        assert!(res.is_err(), "fetch_collection_data should fail");
-
+        assert!(
+            res.is_err(),
+            "cluster_embeddings should fail without a client"
+        );
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "fetch_collection_data failed with different error"
+            "cluster_embeddings failed with different error"
         );
 
         let res = get_command_response(
@@ -2209,45 +10110,161 @@ mod tests {
             }),
         );
 
-        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+        assert!(res.is_ok(), "create_client failed: {:?}", res.err());
+
+        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
+            endpoint: connect_url.as_str().parse().unwrap(),
+            auth_method: ChromaAuthMethod::None,
+            ..Default::default()
+        });
+
+        let collection_name = "test_collection_clusters";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
+            .unwrap();
+
+        // Three tight blobs: doc{i} belongs to blob i % 3.
+        let blobs = [(0.0_f32, 0.0_f32), (10.0, 0.0), (0.0, 10.0)];
+        rt.block_on(
+            collection.add(
+                (0..12).map(|i| format!("doc{}", i)).collect(),
+                (0..12)
+                    .map(|i| {
+                        let (x, y) = blobs.get(i % 3).copied().unwrap();
+                        vec![x + i as f32 * 0.01, y - i as f32 * 0.01]
+                    })
+                    .collect(),
+                None,
+                None,
+                None,
+            ),
+        )
+        .unwrap();
+        let blob_of = |id: &str| id.trim_start_matches("doc").parse::<usize>().unwrap() % 3;
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ClusterEmbeddings.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "options": { "k": 3, "seed": 1, "nearest": 2 },
+            }),
+        );
+
+        assert!(res.is_ok(), "cluster_embeddings failed: {:?}", res.err());
+        let clustering = res.unwrap().deserialize::<Clustering>().unwrap();
+        assert_eq!(clustering.total, 12);
+        assert_eq!(clustering.sampled, 12);
+        assert_eq!(clustering.skipped, 0);
+        assert!(clustering.converged);
+        assert!(clustering.inertia < 1.0);
+        assert_eq!(clustering.clusters.len(), 3);
+        for cluster in &clustering.clusters {
+            assert_eq!(cluster.size, 4);
+            assert_eq!(cluster.nearest.len(), 2);
+            let [closest, next] = cluster.nearest.as_slice() else {
+                panic!("expected two nearest members");
+            };
+            assert!(closest.distance <= next.distance);
+            assert_eq!(
+                blob_of(&closest.id),
+                blob_of(&next.id),
+                "a cluster should not mix blobs"
+            );
+        }
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::ClusterEmbeddings.as_str(),
+            json!({ "collectionName": collection_name, "options": { "k": 20 } }),
+        );
+        assert_eq!(
+            res.err().unwrap(),
+            "Cannot make 20 clusters from 12 record(s)"
+        );
+
+        let centroids: Vec<Vec<f32>> = clustering
+            .clusters
+            .iter()
+            .map(|cluster| cluster.centroid.clone())
+            .collect();
+
+        // The preview writes nothing.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::WriteClusterIds.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "centroids": centroids,
+                "preview": true,
+                "sampleSize": 3,
+            }),
+        );
+        assert!(res.is_ok(), "write_cluster_ids failed: {:?}", res.err());
+        let patch = res.unwrap().deserialize::<MetadataPatch>().unwrap();
+        assert!(patch.preview);
+        assert_eq!((patch.matched, patch.updated), (12, 0));
+        assert_eq!(patch.samples.len(), 3);
+        let sample = patch.samples.first().unwrap();
+        assert!(sample.before.get("cluster_id").is_none());
+        assert!(sample.after.get("cluster_id").is_some());
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::WriteClusterIds.as_str(),
+            json!({ "collectionName": collection_name, "centroids": centroids }),
+        );
+        assert!(res.is_ok(), "write_cluster_ids failed: {:?}", res.err());
+        let patch = res.unwrap().deserialize::<MetadataPatch>().unwrap();
+        assert_eq!((patch.matched, patch.updated), (12, 12));
+
+        let records = rt
+            .block_on(collection.get(
+                None,
+                None,
+                None,
+                None,
+                Some(IncludeList(vec![Include::Metadata])),
+            ))
+            .unwrap();
+        let mut blob_clusters: HashMap<usize, i64> = HashMap::new();
+        for (id, metadata) in records.ids.iter().zip(records.metadatas.unwrap()) {
+            let cluster = match metadata.unwrap().get("cluster_id") {
+                Some(MetadataValue::Int(cluster)) => *cluster,
+                other => panic!("{} has no cluster_id: {:?}", id, other),
+            };
+            assert_eq!(
+                *blob_clusters.entry(blob_of(id)).or_insert(cluster),
+                cluster,
+                "every record of a blob should share a cluster"
+            );
+        }
+        assert_eq!(blob_clusters.len(), 3);
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::WriteClusterIds.as_str(),
+            json!({ "collectionName": collection_name, "centroids": [] }),
+        );
+        assert_eq!(res.err().unwrap(), "No centroids provided");
 
-        let collection_name: &str = "test_collection";
-        let metadata = json!({
-            "foo": "bar"
-        });
         let res = get_command_response(
             &webview,
-            TauriCommand::CreateCollection.as_str(),
+            TauriCommand::WriteClusterIds.as_str(),
             json!({
                 "collectionName": collection_name,
-                "metadata": metadata
+                "centroids": [[0.0, 0.0, 0.0]],
+                "preview": true,
             }),
         );
-
-        assert!(res.is_ok(), "create_collection failed: {:?}", res.err());
-
-        let client = ChromaHttpClient::new(ChromaHttpClientOptions {
-            endpoint: format!("http://{}:{}", host, port)
-                .as_str()
-                .parse()
-                .unwrap(),
-            auth_method: ChromaAuthMethod::None,
-            ..Default::default()
-        });
-        let collection = rt.block_on(client.get_collection(collection_name)).unwrap();
-
-        assert_eq!(collection_name, collection.name());
         assert_eq!(
-            &Some(HashMap::from([(
-                "foo".to_string(),
-                MetadataValue::Str("bar".to_string())
-            )])),
-            collection.metadata()
+            res.err().unwrap(),
+            "Centroids have dimension 3, but the collection expects 2"
         );
     }
 
     #[test]
-    fn test_delete_collection() {
+    fn test_compare_embeddings() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
@@ -2263,18 +10280,18 @@ mod tests {
 
         let res = get_command_response(
             &webview,
-            TauriCommand::DeleteCollection.as_str(),
-            json!({
-                "collectionNames": vec!["test_collection"]
-            }),
+            TauriCommand::CompareEmbeddings.as_str(),
+            json!({ "collectionName": "test_collection_compare", "ids": ["doc0"] }),
         );
 
-        assert!(res.is_err(), "delete_collection should fail");
-
+        assert!(
+            res.is_err(),
+            "compare_embeddings should fail without a client"
+        );
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "delete_collection failed with different error"
+            "compare_embeddings failed with different error"
         );
 
         let res = get_command_response(
@@ -2293,35 +10310,81 @@ mod tests {
         assert!(res.is_ok(), "create_client failed: {:?}", res.err());
 
         let client = ChromaHttpClient::new(ChromaHttpClientOptions {
-            endpoint: format!("http://{}:{}", host, port)
-                .as_str()
-                .parse()
-                .unwrap(),
+            endpoint: connect_url.as_str().parse().unwrap(),
             auth_method: ChromaAuthMethod::None,
             ..Default::default()
         });
 
-        let collection_name: &str = "test_collection";
-        rt.block_on(client.get_or_create_collection(collection_name, None, None))
+        let collection_name = "test_collection_compare";
+        let collection = rt
+            .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
+        rt.block_on(collection.add(
+            vec!["doc0".to_string(), "doc1".to_string(), "doc2".to_string()],
+            vec![vec![3.0_f32, 4.0], vec![6.0, 8.0], vec![0.0, 0.0]],
+            None,
+            None,
+            None,
+        ))
+        .unwrap();
+
+        // Rows follow the order of the ids given.
         let res = get_command_response(
             &webview,
-            TauriCommand::DeleteCollection.as_str(),
+            TauriCommand::CompareEmbeddings.as_str(),
             json!({
-                "collectionNames": vec![collection_name]
+                "collectionName": collection_name,
+                "ids": ["doc1", "doc0", "doc2"],
             }),
         );
 
-        assert!(res.is_ok(), "delete_collection failed: {:?}", res.err());
+        assert!(res.is_ok(), "compare_embeddings failed: {:?}", res.err());
+        let comparison = res.unwrap().deserialize::<EmbeddingComparison>().unwrap();
+        assert_eq!(comparison.ids, vec!["doc1", "doc0", "doc2"]);
+        assert_eq!(comparison.norms, vec![10.0, 5.0, 0.0]);
+        assert_eq!(
+            comparison.cosine_similarity,
+            vec![
+                vec![Some(1.0), Some(1.0), None],
+                vec![Some(1.0), Some(1.0), None],
+                vec![None, None, None],
+            ]
+        );
+        assert_eq!(
+            comparison.l2_distance,
+            vec![
+                vec![0.0, 5.0, 10.0],
+                vec![5.0, 0.0, 5.0],
+                vec![10.0, 5.0, 0.0],
+            ]
+        );
+        assert_eq!(
+            comparison.inner_product,
+            vec![
+                vec![100.0, 50.0, 0.0],
+                vec![50.0, 25.0, 0.0],
+                vec![0.0, 0.0, 0.0],
+            ]
+        );
 
-        let collection = rt.block_on(client.get_collection(collection_name));
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CompareEmbeddings.as_str(),
+            json!({ "collectionName": collection_name, "ids": ["doc0", "missing"] }),
+        );
+        assert_eq!(res.err().unwrap(), "Embeddings not found for ids missing");
 
-        assert!(collection.is_err(), "collection should not exist");
+        let res = get_command_response(
+            &webview,
+            TauriCommand::CompareEmbeddings.as_str(),
+            json!({ "collectionName": collection_name, "ids": [] }),
+        );
+        assert_eq!(res.err().unwrap(), "No record ids provided");
     }
 
     #[test]
-    fn test_update_record_metadata() {
+    fn test_measure_recall() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
@@ -2330,30 +10393,36 @@ mod tests {
 
         let connect_url = format!("http://{}:{}", host, port);
 
-        let app = before_each(mock_builder());
+        let progress = std::sync::Arc::new(Mutex::new(Vec::<ScanProgress>::new()));
+        let received = progress.clone();
+        let app = before_each(mock_builder().channel_interceptor(
+            move |_webview, _callback, _index, body| {
+                let message = match body {
+                    tauri::ipc::InvokeResponseBody::Json(json) => serde_json::from_str(json),
+                    tauri::ipc::InvokeResponseBody::Raw(bytes) => serde_json::from_slice(bytes),
+                };
+                received.lock().push(message.unwrap());
+                true
+            },
+        ));
         let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
             .build()
             .unwrap();
 
         let res = get_command_response(
             &webview,
-            TauriCommand::UpdateRecordMetadata.as_str(),
+            TauriCommand::MeasureRecall.as_str(),
             json!({
-                "collectionName": "test_collection_update",
-                "id": "doc1",
-                "metadata": {},
-                "removedKeys": Vec::<String>::new(),
+                "collectionName": "test_collection_recall",
+                "onProgress": "__CHANNEL__:7",
             }),
         );
 
-        assert!(
-            res.is_err(),
-            "update_record_metadata should fail without a client"
-        );
+        assert!(res.is_err(), "measure_recall should fail without a client");
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "update_record_metadata failed with different error"
+            "measure_recall failed with different error"
         );
 
         let res = get_command_response(
@@ -2377,96 +10446,87 @@ mod tests {
             ..Default::default()
         });
 
-        let collection_name = "test_collection_update";
+        let collection_name = "test_collection_recall";
         let collection = rt
             .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
-        let seed_metadata: Metadata = [
-            ("keep".to_string(), MetadataValue::Str("original".to_string())),
-            ("drop".to_string(), MetadataValue::Str("gone".to_string())),
-            ("score".to_string(), MetadataValue::Int(1)),
-        ]
-        .into_iter()
-        .collect();
-
-        rt.block_on(collection.add(
-            vec!["doc1".to_string()],
-            vec![vec![0.1_f32, 0.2_f32, 0.3_f32]],
-            Some(vec![Some("First document".to_string())]),
-            None,
-            Some(vec![Some(seed_metadata)]),
-        ))
+        rt.block_on(
+            collection.add(
+                (0..30).map(|i| format!("doc{}", i)).collect(),
+                (0..30)
+                    .map(|i| vec![i as f32, ((i * 7) % 11) as f32 * 1.3, 1.0])
+                    .collect(),
+                None,
+                None,
+                None,
+            ),
+        )
         .unwrap();
 
-        // Change a value, add a new key, and drop an existing one in one call.
         let res = get_command_response(
             &webview,
-            TauriCommand::UpdateRecordMetadata.as_str(),
+            TauriCommand::MeasureRecall.as_str(),
             json!({
                 "collectionName": collection_name,
-                "id": "doc1",
-                "metadata": {
-                    "keep": "updated",
-                    "score": 7,
-                    "added": true,
-                },
-                "removedKeys": vec!["drop".to_string()],
+                "queryCount": 10,
+                "k": 5,
+                "seed": 3,
+                "onProgress": "__CHANNEL__:7",
             }),
         );
 
-        assert!(
-            res.is_ok(),
-            "update_record_metadata failed: {:?}",
-            res.err()
-        );
-
-        let get_result = rt
-            .block_on(collection.get(
-                Some(vec!["doc1".to_string()]),
-                None,
-                Some(1u32),
-                None,
-                Some(IncludeList(vec![Include::Metadata, Include::Document])),
-            ))
-            .unwrap();
-
-        let metadata = get_result
-            .metadatas
-            .unwrap_or_default()
-            .into_iter()
-            .next()
-            .flatten()
-            .expect("record should still have metadata");
-
-        assert_eq!(
-            metadata.get("keep"),
-            Some(&MetadataValue::Str("updated".to_string())),
-            "existing key was not updated"
-        );
-        assert_eq!(
-            metadata.get("score"),
-            Some(&MetadataValue::Int(7)),
-            "numeric key was not updated"
-        );
-        assert_eq!(
-            metadata.get("added"),
-            Some(&MetadataValue::Bool(true)),
-            "new key was not added"
+        assert!(res.is_ok(), "measure_recall failed: {:?}", res.err());
+        let report = res.unwrap().deserialize::<RecallReport>().unwrap();
+        assert_eq!(report.records, 30);
+        assert_eq!(report.skipped, 0);
+        assert_eq!(report.queries, 10);
+        assert_eq!(report.k, 5);
+        assert_eq!(report.space, DistanceSpace::L2);
+        assert!(report.space_detected);
+        // A collection this small is searched exhaustively.
+        assert!(report.recall > 0.99, "recall: {}", report.recall);
+        assert!(report.overlap > 0.99, "overlap: {}", report.overlap);
+        assert_eq!(report.perfect, 10);
+        assert!(report.worst.is_empty());
+        assert!(report.latency.p50_ms > 0.0);
+        assert!(report.latency.p50_ms <= report.latency.p90_ms);
+        assert!(report.latency.p90_ms <= report.latency.max_ms);
+
+        let last = progress.lock().last().cloned().unwrap();
+        assert_eq!((last.scanned, last.total), (30, Some(30)));
+
+        // A given space is used as is; k is capped at the collection size.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::MeasureRecall.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "queryCount": 50,
+                "k": 40,
+                "space": "l2",
+                "onProgress": "__CHANNEL__:7",
+            }),
         );
-        assert_eq!(metadata.get("drop"), None, "removed key still present");
+        let report = res.unwrap().deserialize::<RecallReport>().unwrap();
+        assert!(!report.space_detected);
+        assert_eq!(report.queries, 30);
+        assert_eq!(report.k, 30);
 
-        // The document must be left untouched by a metadata-only update.
-        let documents = get_result.documents.unwrap_or_default();
-        assert_eq!(
-            documents.into_iter().next().flatten(),
-            Some("First document".to_string()),
-            "document was modified by a metadata-only update"
+        let res = get_command_response(
+            &webview,
+            TauriCommand::MeasureRecall.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "k": 0,
+                "onProgress": "__CHANNEL__:7",
+            }),
         );
+        assert_eq!(res.err().unwrap(), "k must be between 1 and 100");
     }
 
     #[test]
-    fn test_delete_records() {
+    fn test_evaluate_retrieval() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let container = create_chroma_container();
 
@@ -2475,25 +10535,40 @@ mod tests {
 
         let connect_url = format!("http://{}:{}", host, port);
 
-        let app = before_each(mock_builder());
+        let progress = std::sync::Arc::new(Mutex::new(Vec::<ScanProgress>::new()));
+        let received = progress.clone();
+        let app = before_each(mock_builder().channel_interceptor(
+            move |_webview, _callback, _index, body| {
+                let message = match body {
+                    tauri::ipc::InvokeResponseBody::Json(json) => serde_json::from_str(json),
+                    tauri::ipc::InvokeResponseBody::Raw(bytes) => serde_json::from_slice(bytes),
+                };
+                received.lock().push(message.unwrap());
+                true
+            },
+        ));
         let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default())
             .build()
             .unwrap();
 
         let res = get_command_response(
             &webview,
-            TauriCommand::DeleteRecords.as_str(),
+            TauriCommand::EvaluateRetrieval.as_str(),
             json!({
-                "collectionName": "test_collection_delete_records",
-                "ids": vec!["doc1".to_string()],
+                "collectionName": "test_collection_evaluation",
+                "goldenSet": { "format": "jsonl", "value": "" },
+                "onProgress": "__CHANNEL__:7",
             }),
         );
 
-        assert!(res.is_err(), "delete_records should fail without a client");
+        assert!(
+            res.is_err(),
+            "evaluate_retrieval should fail without a client"
+        );
         assert_eq!(
             res.err().unwrap(),
             "ChromaDB client not initialized",
-            "delete_records failed with different error"
+            "evaluate_retrieval failed with different error"
         );
 
         let res = get_command_response(
@@ -2517,70 +10592,145 @@ mod tests {
             ..Default::default()
         });
 
-        let collection_name = "test_collection_delete_records";
+        let collection_name = "test_collection_evaluation";
         let collection = rt
             .block_on(client.get_or_create_collection(collection_name, None, None))
             .unwrap();
 
+        let provider = json!({ "provider": "hash", "dimension": 32 });
+        let hash_provider: EmbeddingProvider = serde_json::from_value(provider.clone()).unwrap();
+        let documents = vec![
+            "red apple pie".to_string(),
+            "green apple".to_string(),
+            "blue whale".to_string(),
+            "grey whale shark".to_string(),
+        ];
+        let embeddings = rt.block_on(hash_provider.embed(&documents)).unwrap();
         rt.block_on(collection.add(
-            vec!["doc1".to_string(), "doc2".to_string(), "doc3".to_string()],
-            vec![
-                vec![0.1_f32, 0.2_f32, 0.3_f32],
-                vec![0.4_f32, 0.5_f32, 0.6_f32],
-                vec![0.7_f32, 0.8_f32, 0.9_f32],
-            ],
-            Some(vec![
-                Some("First".to_string()),
-                Some("Second".to_string()),
-                Some("Third".to_string()),
-            ]),
+            (1..=4).map(|i| format!("doc{}", i)).collect(),
+            embeddings.clone(),
+            Some(documents.into_iter().map(Some).collect()),
             None,
             None,
         ))
         .unwrap();
 
-        // An empty id list must be refused rather than sent as "no filter".
+        // q2 brings its own vector; q3 expects a record that does not exist.
+        let golden_set = [
+            json!({ "id": "q1", "query": "blue whale", "expected_ids": ["doc3"] }),
+            json!({ "id": "q2", "embedding": embeddings.get(1), "expected_ids": ["doc2"] }),
+            json!({ "question": "red apple pie", "expected": ["doc_missing"] }),
+        ]
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+        let golden_path = env::temp_dir().join("chromamind_test_golden_set.jsonl");
+        std::fs::write(&golden_path, &golden_set).unwrap();
+
         let res = get_command_response(
             &webview,
-            TauriCommand::DeleteRecords.as_str(),
+            TauriCommand::EvaluateRetrieval.as_str(),
             json!({
                 "collectionName": collection_name,
-                "ids": Vec::<String>::new(),
+                "goldenSet": { "format": "jsonl", "value": golden_set },
+                "onProgress": "__CHANNEL__:7",
             }),
         );
-
-        assert!(res.is_err(), "delete_records should reject an empty id list");
         assert_eq!(
             res.err().unwrap(),
-            "No record ids provided",
-            "empty id list rejected with different error"
+            format!(
+                "No embedding provider configured for collection: {}",
+                collection_name
+            )
         );
 
         let res = get_command_response(
             &webview,
-            TauriCommand::DeleteRecords.as_str(),
+            TauriCommand::SetEmbeddingProvider.as_str(),
             json!({
                 "collectionName": collection_name,
-                "ids": vec!["doc1".to_string(), "doc3".to_string()],
+                "provider": provider,
             }),
         );
+        assert!(
+            res.is_ok(),
+            "set_embedding_provider failed: {:?}",
+            res.err()
+        );
 
-        assert!(res.is_ok(), "delete_records failed: {:?}", res.err());
+        let save_path = env::temp_dir().join("chromamind_test_evaluation.json");
+        let res = get_command_response(
+            &webview,
+            TauriCommand::EvaluateRetrieval.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "goldenSet": { "format": "path", "value": golden_path },
+                "k": 2,
+                "savePath": save_path,
+                "onProgress": "__CHANNEL__:7",
+            }),
+        );
 
-        let get_result = rt
-            .block_on(collection.get(
-                None,
-                None,
-                Some(10u32),
-                None,
-                Some(IncludeList(vec![Include::Document])),
-            ))
-            .unwrap();
+        assert!(res.is_ok(), "evaluate_retrieval failed: {:?}", res.err());
+        let report = res.unwrap().deserialize::<RetrievalEvaluation>().unwrap();
+        assert_eq!(report.collection, collection_name);
+        assert_eq!(report.k, 2);
+        assert_eq!(report.queries, 3);
+        assert_eq!(report.provider, Some(provider.clone()));
+        for metric in [report.hit_rate, report.mrr, report.ndcg, report.recall] {
+            assert!((metric - 2.0 / 3.0).abs() < 1e-9, "metric: {}", metric);
+        }
 
-        assert_eq!(
-            get_result.ids,
-            vec!["doc2".to_string()],
-            "only the untouched record should remain"
+        let [q1, q2, q3] = report.cases.as_slice() else {
+            panic!("expected three evaluated cases");
+        };
+        assert_eq!(q1.id, "q1");
+        assert_eq!(q1.rank, Some(1));
+        assert_eq!(q1.retrieved.len(), 2);
+        assert_eq!(q1.retrieved.first().map(String::as_str), Some("doc3"));
+        assert!(q1.missed.is_empty());
+
+        assert_eq!(q2.query, None);
+        assert_eq!(q2.rank, Some(1));
+
+        assert_eq!(q3.id, "line 3");
+        assert_eq!(q3.rank, None);
+        assert_eq!(q3.reciprocal_rank, 0.0);
+        assert_eq!(q3.missed, vec!["doc_missing"]);
+
+        let last = progress.lock().last().cloned().unwrap();
+        assert_eq!((last.scanned, last.total), (3, Some(3)));
+
+        // A saved run reads back unchanged.
+        let res = get_command_response(
+            &webview,
+            TauriCommand::LoadEvaluation.as_str(),
+            json!({ "path": save_path }),
+        );
+        assert!(res.is_ok(), "load_evaluation failed: {:?}", res.err());
+        let loaded = res.unwrap().deserialize::<RetrievalEvaluation>().unwrap();
+        assert_eq!(loaded.created_at, report.created_at);
+        assert_eq!(loaded.mrr, report.mrr);
+        assert_eq!(loaded.cases.len(), 3);
+
+        let res = get_command_response(
+            &webview,
+            TauriCommand::EvaluateRetrieval.as_str(),
+            json!({
+                "collectionName": collection_name,
+                "goldenSet": { "format": "jsonl", "value": "{\"query\": \"x\"}" },
+                "onProgress": "__CHANNEL__:7",
+            }),
         );
+        assert!(res
+            .err()
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid golden set line 1:"));
+
+        std::fs::remove_file(golden_path).unwrap();
+        std::fs::remove_file(save_path).unwrap();
     }
 }
@@ -0,0 +1,135 @@
+use crate::structs::{MetadataKeyProfile, MetadataProfile, ValueCount};
+use chroma::types::{Metadata, MetadataValue};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Distinct values tracked per key before the count is reported as a lower bound.
+const MAX_TRACKED_VALUES: usize = 10_000;
+
+/// String values listed per key in `top_values`.
+const TOP_VALUES: usize = 10;
+
+/// Accumulates per-key statistics over metadata pages for `profile_metadata`.
+#[derive(Default)]
+pub(crate) struct MetadataProfiler {
+    records: usize,
+    keys: BTreeMap<String, KeyAccumulator>,
+}
+
+#[derive(Default)]
+struct KeyAccumulator {
+    types: BTreeSet<&'static str>,
+    count: usize,
+    distinct: HashSet<String>,
+    distinct_capped: bool,
+    min: Option<f64>,
+    max: Option<f64>,
+    strings: HashMap<String, usize>,
+}
+
+impl MetadataProfiler {
+    pub(crate) fn add(&mut self, metadata: Option<&Metadata>) {
+        self.records += 1;
+        for (key, value) in metadata.into_iter().flatten() {
+            self.keys.entry(key.clone()).or_default().add(value);
+        }
+    }
+
+    pub(crate) fn finish(self, total: u32) -> MetadataProfile {
+        let records = self.records;
+        let mut keys: Vec<MetadataKeyProfile> = self
+            .keys
+            .into_iter()
+            .map(|(key, acc)| acc.finish(key, records))
+            .collect();
+        keys.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+
+        MetadataProfile {
+            total,
+            scanned: records,
+            keys,
+        }
+    }
+}
+
+impl KeyAccumulator {
+    fn add(&mut self, value: &MetadataValue) {
+        self.count += 1;
+        self.types.insert(type_name(value));
+
+        let repr = crate::metadata_value_to_json(value.clone()).to_string();
+        if self.distinct.len() < MAX_TRACKED_VALUES {
+            self.distinct.insert(repr);
+        } else if !self.distinct.contains(&repr) {
+            self.distinct_capped = true;
+        }
+
+        match value {
+            MetadataValue::Int(i) => self.add_number(*i as f64),
+            MetadataValue::Float(f) => self.add_number(*f),
+            MetadataValue::Str(s) => self.add_string(s),
+            MetadataValue::StringArray(values) => values.iter().for_each(|s| self.add_string(s)),
+            MetadataValue::Bool(_)
+            | MetadataValue::SparseVector(_)
+            | MetadataValue::BoolArray(_)
+            | MetadataValue::IntArray(_)
+            | MetadataValue::FloatArray(_) => {}
+        }
+    }
+
+    fn add_number(&mut self, value: f64) {
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+    }
+
+    fn add_string(&mut self, value: &str) {
+        if let Some(count) = self.strings.get_mut(value) {
+            *count += 1;
+        } else if self.strings.len() < MAX_TRACKED_VALUES {
+            self.strings.insert(value.to_string(), 1);
+        }
+    }
+
+    fn finish(self, key: String, records: usize) -> MetadataKeyProfile {
+        let mut top_values: Vec<(String, usize)> = self.strings.into_iter().collect();
+        top_values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        top_values.truncate(TOP_VALUES);
+
+        MetadataKeyProfile {
+            key,
+            types: self.types.into_iter().map(str::to_string).collect(),
+            count: self.count,
+            presence: if records == 0 {
+                0.0
+            } else {
+                self.count as f64 / records as f64
+            },
+            distinct: self.distinct.len(),
+            distinct_capped: self.distinct_capped,
+            min: self.min,
+            max: self.max,
+            top_values: top_values
+                .into_iter()
+                .map(|(value, count)| ValueCount {
+                    value: Value::String(value),
+                    count,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The name `profile_metadata` reports for a value's type.
+pub(crate) fn type_name(value: &MetadataValue) -> &'static str {
+    match value {
+        MetadataValue::Bool(_) => "bool",
+        MetadataValue::Int(_) => "int",
+        MetadataValue::Float(_) => "float",
+        MetadataValue::Str(_) => "string",
+        MetadataValue::SparseVector(_) => "sparse_vector",
+        MetadataValue::BoolArray(_) => "bool_array",
+        MetadataValue::IntArray(_) => "int_array",
+        MetadataValue::FloatArray(_) => "float_array",
+        MetadataValue::StringArray(_) => "string_array",
+    }
+}
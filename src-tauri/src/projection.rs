@@ -0,0 +1,161 @@
+use crate::rng::Rng;
+
+/// Power iterations per principal component before giving up on convergence.
+const MAX_ITERATIONS: usize = 100;
+
+/// Seed of the random projection when none is given.
+const DEFAULT_SEED: u64 = 42;
+
+/// How `project_embeddings` reduces vectors to two or three dimensions.
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum ProjectionMethod {
+    /// Principal component analysis: the axes of greatest variance.
+    #[default]
+    Pca,
+    /// A seeded Gaussian random projection. Cheaper than PCA and keeps
+    /// distances roughly, but the axes carry no meaning.
+    Random { seed: Option<u64> },
+}
+
+/// The coordinates of each vector and, for PCA, the share of the total
+/// variance each axis explains. All vectors must have the same dimension.
+pub(crate) fn project(
+    vectors: &[Vec<f32>],
+    dimensions: usize,
+    method: ProjectionMethod,
+) -> (Vec<Vec<f32>>, Option<Vec<f64>>) {
+    let width = vectors.first().map_or(0, Vec::len);
+    let mut mean = vec![0.0; width];
+    for vector in vectors {
+        for (m, &c) in mean.iter_mut().zip(vector) {
+            *m += f64::from(c);
+        }
+    }
+    for m in &mut mean {
+        *m /= vectors.len().max(1) as f64;
+    }
+
+    let (axes, explained) = match method {
+        ProjectionMethod::Pca => {
+            let (axes, explained) = principal_components(vectors, &mean, dimensions);
+            (axes, Some(explained))
+        }
+        ProjectionMethod::Random { seed } => {
+            let mut rng = Rng::new(seed.unwrap_or(DEFAULT_SEED));
+            let scale = 1.0 / (dimensions as f64).sqrt();
+            let axes = (0..dimensions)
+                .map(|_| (0..width).map(|_| rng.next_gaussian() * scale).collect())
+                .collect();
+            (axes, None)
+        }
+    };
+
+    let coordinates = vectors
+        .iter()
+        .map(|vector| {
+            axes.iter()
+                .map(|axis| centered_dot(vector, &mean, axis) as f32)
+                .collect()
+        })
+        .collect();
+    (coordinates, explained)
+}
+
+/// The top `count` eigenvectors of the covariance of `vectors`, found by power
+/// iteration with deflation, and their explained variance ratios. Each axis
+/// is signed so its largest component is positive, keeping layouts stable.
+/// Vectors are centred on `mean` as they are read rather than copied.
+fn principal_components(
+    vectors: &[Vec<f32>],
+    mean: &[f64],
+    count: usize,
+) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let width = mean.len();
+    let records = vectors.len().max(1) as f64;
+    let total_variance: f64 = vectors
+        .iter()
+        .map(|vector| centered(vector, mean).map(|c| c * c).sum::<f64>())
+        .sum::<f64>()
+        / records;
+
+    let mut rng = Rng::new(DEFAULT_SEED);
+    let mut axes: Vec<Vec<f64>> = Vec::with_capacity(count);
+    let mut explained = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut axis: Vec<f64> = (0..width).map(|_| rng.next_gaussian()).collect();
+        normalize(&mut axis);
+        for _ in 0..MAX_ITERATIONS {
+            // Covariance times axis, without forming the covariance matrix.
+            let mut next = vec![0.0; width];
+            for vector in vectors {
+                let score = centered_dot(vector, mean, &axis);
+                for (n, c) in next.iter_mut().zip(centered(vector, mean)) {
+                    *n += c * score;
+                }
+            }
+            for previous in &axes {
+                let overlap = dot(&next, previous);
+                for (n, p) in next.iter_mut().zip(previous) {
+                    *n -= overlap * p;
+                }
+            }
+            if !normalize(&mut next) {
+                axis = next;
+                break;
+            }
+            let converged = 1.0 - dot(&next, &axis).abs() < 1e-12;
+            axis = next;
+            if converged {
+                break;
+            }
+        }
+
+        let largest = axis
+            .iter()
+            .copied()
+            .reduce(|a, b| if b.abs() > a.abs() { b } else { a })
+            .unwrap_or(0.0);
+        if largest < 0.0 {
+            axis.iter_mut().for_each(|c| *c = -*c);
+        }
+        let variance: f64 = vectors
+            .iter()
+            .map(|vector| centered_dot(vector, mean, &axis).powi(2))
+            .sum::<f64>()
+            / records;
+        explained.push(if total_variance > 0.0 {
+            variance / total_variance
+        } else {
+            0.0
+        });
+        axes.push(axis);
+    }
+    (axes, explained)
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// The components of `vector` minus `mean`.
+fn centered<'a>(vector: &'a [f32], mean: &'a [f64]) -> impl Iterator<Item = f64> + 'a {
+    vector.iter().zip(mean).map(|(&c, m)| f64::from(c) - m)
+}
+
+/// `dot(vector - mean, axis)` without materialising the centred vector.
+fn centered_dot(vector: &[f32], mean: &[f64], axis: &[f64]) -> f64 {
+    centered(vector, mean).zip(axis).map(|(c, a)| c * a).sum()
+}
+
+/// Scales `vector` to unit length, or zeroes it and returns false when it has
+/// no length to scale.
+fn normalize(vector: &mut [f64]) -> bool {
+    let norm = dot(vector, vector).sqrt();
+    if norm <= f64::EPSILON {
+        vector.iter_mut().for_each(|c| *c = 0.0);
+        return false;
+    }
+    vector.iter_mut().for_each(|c| *c /= norm);
+    true
+}
@@ -0,0 +1,209 @@
+use crate::rng::Rng;
+use crate::structs::{DistanceSpace, LatencyStats, RecallQuery, RecallReport};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Queries listed in `worst`.
+const WORST_QUERIES: usize = 10;
+
+/// An index query of `measure_recall`: the position of the query vector and
+/// the ids and distances the index returned for it.
+pub(crate) struct IndexResult {
+    pub(crate) query: usize,
+    pub(crate) ids: Vec<String>,
+    pub(crate) distances: Vec<Option<f32>>,
+    pub(crate) latency: Duration,
+}
+
+/// `count` distinct positions below `len`, drawn with `seed`.
+pub(crate) fn sample_indices(len: usize, count: usize, seed: u64) -> Vec<usize> {
+    let mut rng = Rng::new(seed);
+    let mut indices: Vec<usize> = (0..len).collect();
+    let count = count.min(len);
+    for i in 0..count {
+        let j = i + (rng.next_u64() % (len - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    indices.truncate(count);
+    indices
+}
+
+/// Compares each index result with the exact neighbours of its query among
+/// `vectors`. Without `space`, the space whose distances best match those the
+/// index returned is used.
+pub(crate) fn evaluate(
+    ids: &[String],
+    vectors: &[Vec<f32>],
+    results: &[IndexResult],
+    k: usize,
+    space: Option<DistanceSpace>,
+    skipped: usize,
+) -> RecallReport {
+    let positions: HashMap<&str, usize> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+    let space_detected = space.is_none();
+    let space = space.unwrap_or_else(|| detect_space(vectors, results, &positions));
+
+    let mut queries: Vec<RecallQuery> = Vec::with_capacity(results.len());
+    let mut overlap = 0.0;
+    for result in results {
+        let Some(query) = vectors.get(result.query) else {
+            continue;
+        };
+        let exact = exact_neighbours(space, vectors, query, k);
+        let cutoff = exact
+            .last()
+            .map_or(f32::INFINITY, |(_, distance)| *distance);
+        let tolerance = 1e-5 * cutoff.abs().max(1.0);
+        let exact_ids: HashSet<usize> = exact.iter().map(|(i, _)| *i).collect();
+        let returned: Vec<usize> = result
+            .ids
+            .iter()
+            .filter_map(|id| positions.get(id.as_str()).copied())
+            .collect();
+
+        let ties = returned
+            .iter()
+            .filter_map(|&i| vectors.get(i))
+            .filter(|vector| distance(space, query, vector) <= cutoff + tolerance)
+            .count();
+        let found = returned.iter().filter(|i| exact_ids.contains(i)).count();
+        let expected = exact.len().max(1) as f64;
+        overlap += found as f64 / expected;
+        queries.push(RecallQuery {
+            id: ids.get(result.query).cloned().unwrap_or_default(),
+            recall: ties.min(exact.len()) as f64 / expected,
+            missed: exact
+                .iter()
+                .filter(|(i, _)| !returned.contains(i))
+                .filter_map(|(i, _)| ids.get(*i).cloned())
+                .collect(),
+        });
+    }
+
+    let count = queries.len().max(1) as f64;
+    let recall = queries.iter().map(|q| q.recall).sum::<f64>() / count;
+    let min_recall = queries
+        .iter()
+        .map(|q| q.recall)
+        .reduce(f64::min)
+        .unwrap_or(0.0);
+    let perfect = queries.iter().filter(|q| q.recall >= 1.0).count();
+    let latency = latency_stats(results.iter().map(|r| r.latency).collect());
+    let total = queries.len();
+    queries.sort_by(|a, b| a.recall.total_cmp(&b.recall).then_with(|| a.id.cmp(&b.id)));
+    queries.retain(|q| q.recall < 1.0);
+    queries.truncate(WORST_QUERIES);
+
+    RecallReport {
+        records: vectors.len(),
+        skipped,
+        queries: total,
+        k,
+        space,
+        space_detected,
+        recall,
+        min_recall,
+        overlap: overlap / count,
+        perfect,
+        latency,
+        worst: queries,
+    }
+}
+
+/// The distance Chroma reports in `space`: squared Euclidean for `l2`, and one
+/// minus the cosine similarity or inner product otherwise.
+fn distance(space: DistanceSpace, a: &[f32], b: &[f32]) -> f32 {
+    let (mut dot, mut squared, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64, 0.0f64);
+    for (&x, &y) in a.iter().zip(b) {
+        let (x, y) = (f64::from(x), f64::from(y));
+        dot += x * y;
+        squared += (x - y) * (x - y);
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    let distance = match space {
+        DistanceSpace::L2 => squared,
+        DistanceSpace::Ip => 1.0 - dot,
+        DistanceSpace::Cosine => {
+            let norms = (norm_a * norm_b).sqrt();
+            if norms > 0.0 {
+                1.0 - dot / norms
+            } else {
+                1.0
+            }
+        }
+    };
+    distance as f32
+}
+
+/// The `k` vectors closest to `query`, nearest first.
+fn exact_neighbours(
+    space: DistanceSpace,
+    vectors: &[Vec<f32>],
+    query: &[f32],
+    k: usize,
+) -> Vec<(usize, f32)> {
+    let mut distances: Vec<(usize, f32)> = vectors
+        .iter()
+        .map(|vector| distance(space, query, vector))
+        .enumerate()
+        .collect();
+    if k < distances.len() {
+        distances.select_nth_unstable_by(k, |a, b| a.1.total_cmp(&b.1));
+        distances.truncate(k);
+    }
+    distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+    distances
+}
+
+/// The space whose distances for the returned ids come closest to those the
+/// index reported.
+fn detect_space(
+    vectors: &[Vec<f32>],
+    results: &[IndexResult],
+    positions: &HashMap<&str, usize>,
+) -> DistanceSpace {
+    let error = |space: DistanceSpace| -> f64 {
+        results
+            .iter()
+            .filter_map(|result| Some((result, vectors.get(result.query)?)))
+            .flat_map(|(result, query)| {
+                result
+                    .ids
+                    .iter()
+                    .zip(&result.distances)
+                    .filter_map(|(id, reported)| {
+                        let vector = vectors.get(*positions.get(id.as_str())?)?;
+                        Some(f64::from(
+                            (distance(space, query, vector) - (*reported)?).abs(),
+                        ))
+                    })
+            })
+            .sum()
+    };
+    [DistanceSpace::L2, DistanceSpace::Cosine, DistanceSpace::Ip]
+        .into_iter()
+        .map(|space| (space, error(space)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(DistanceSpace::L2, |(space, _)| space)
+}
+
+fn latency_stats(mut latencies: Vec<Duration>) -> LatencyStats {
+    latencies.sort_unstable();
+    let ms = |d: &Duration| d.as_secs_f64() * 1000.0;
+    let percentile = |p: f64| {
+        let rank = ((p * latencies.len() as f64).ceil() as usize).max(1);
+        latencies.get(rank - 1).map_or(0.0, ms)
+    };
+    LatencyStats {
+        mean_ms: latencies.iter().map(ms).sum::<f64>() / latencies.len().max(1) as f64,
+        p50_ms: percentile(0.5),
+        p90_ms: percentile(0.9),
+        p99_ms: percentile(0.99),
+        max_ms: latencies.last().map_or(0.0, ms),
+    }
+}
@@ -0,0 +1,224 @@
+use crate::embedding::EmbeddingProvider;
+use crate::vector::{validate_vector, VectorInput};
+use chroma::client::ChromaHttpClientError;
+use chroma_types::operator::{rrf, Key, QueryVector, RankExpr};
+use chroma_types::plan::SearchPayload;
+use chroma_types::SparseVector;
+use serde_json::Value;
+
+/// A search as built in the app's search panel. It maps one-to-one onto a
+/// Chroma `SearchPayload`, except that ranking is given as a list of terms
+/// and how to combine them rather than as a raw rank expression.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SearchRequest {
+    #[serde(default)]
+    pub(crate) where_filter: Option<Value>,
+    #[serde(default)]
+    pub(crate) where_document: Option<Value>,
+    #[serde(default)]
+    pub(crate) rank: Vec<RankTerm>,
+    #[serde(default)]
+    pub(crate) combine: Combine,
+    /// The `k` constant of reciprocal rank fusion. Chroma defaults to 60.
+    pub(crate) rrf_k: Option<u32>,
+    pub(crate) limit: Option<u32>,
+    #[serde(default)]
+    pub(crate) offset: u32,
+    /// Keys to return, e.g. `#document`, `#score` or a metadata field name.
+    /// Defaults to the document, metadata and score.
+    pub(crate) select: Option<Vec<String>>,
+}
+
+/// One nearest-neighbour ranking over `key`, which is `#embedding` for the
+/// collection's dense vectors or the name of a sparse vector metadata field.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct RankTerm {
+    pub(crate) query: SearchQuery,
+    pub(crate) key: Option<String>,
+    /// Multiplies the term's score when terms are summed, or its rank
+    /// contribution with reciprocal rank fusion.
+    pub(crate) weight: Option<f32>,
+    /// How many neighbours the term considers. Chroma defaults to 16.
+    pub(crate) limit: Option<u32>,
+    /// Score given to records outside this term's neighbours. Without it they
+    /// are dropped from the results.
+    pub(crate) default: Option<f32>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum SearchQuery {
+    /// A dense vector in any of the formats `query_by_vector` accepts.
+    Dense {
+        vector: VectorInput,
+    },
+    Sparse {
+        indices: Vec<u32>,
+        values: Vec<f32>,
+    },
+    /// Text embedded with the collection's embedding provider.
+    Text {
+        text: String,
+    },
+}
+
+#[derive(Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Combine {
+    /// Weighted sum of the terms' distances.
+    #[default]
+    Sum,
+    /// Reciprocal rank fusion, which ignores the scale of each term's distances.
+    Rrf,
+}
+
+impl SearchRequest {
+    pub(crate) fn needs_provider(&self) -> bool {
+        self.rank
+            .iter()
+            .any(|term| matches!(term.query, SearchQuery::Text { .. }))
+    }
+
+    /// Resolves every rank term to a query vector and builds the payload.
+    /// `dimension` is the collection's, used to check dense queries against
+    /// `#embedding`.
+    pub(crate) async fn into_payload(
+        self,
+        provider: Option<&EmbeddingProvider>,
+        dimension: Option<u32>,
+    ) -> Result<SearchPayload, String> {
+        let mut payload = SearchPayload::default()
+            .limit(Some(self.limit.unwrap_or(10)), self.offset)
+            .select(
+                self.select
+                    .unwrap_or_else(|| {
+                        vec![
+                            "#document".to_string(),
+                            "#metadata".to_string(),
+                            "#score".to_string(),
+                        ]
+                    })
+                    .into_iter()
+                    .map(Key::from),
+            );
+
+        if let Some(where_clause) =
+            crate::build_where_filter(self.where_filter, self.where_document)?
+        {
+            payload = payload.r#where(where_clause);
+        }
+
+        let use_rrf = self.combine == Combine::Rrf;
+        let mut ranks = Vec::with_capacity(self.rank.len());
+        let mut weights = Vec::with_capacity(self.rank.len());
+        for term in self.rank {
+            let key = term.key.map(Key::from).unwrap_or(Key::Embedding);
+            let query = resolve_query(term.query, &key, provider, dimension).await?;
+            ranks.push(RankExpr::Knn {
+                query,
+                key,
+                limit: term.limit.unwrap_or_else(RankExpr::default_knn_limit),
+                default: term.default,
+                return_rank: use_rrf,
+            });
+            weights.push(term.weight);
+        }
+
+        let rank = if use_rrf {
+            let weights = weights
+                .iter()
+                .any(Option::is_some)
+                .then(|| weights.iter().map(|w| w.unwrap_or(1.0)).collect());
+            Some(
+                rrf(ranks, self.rrf_k, weights, false)
+                    .map_err(|e| format!("Invalid rank: {}", e))?,
+            )
+        } else {
+            let mut terms: Vec<RankExpr> = ranks
+                .into_iter()
+                .zip(weights)
+                .map(|(rank, weight)| match weight {
+                    Some(weight) => rank * weight,
+                    None => rank,
+                })
+                .collect();
+            match terms.len() {
+                0 => None,
+                1 => terms.pop(),
+                _ => Some(RankExpr::Summation(terms)),
+            }
+        };
+        if let Some(rank) = rank {
+            payload = payload.rank(rank);
+        }
+
+        Ok(payload)
+    }
+}
+
+async fn resolve_query(
+    query: SearchQuery,
+    key: &Key,
+    provider: Option<&EmbeddingProvider>,
+    dimension: Option<u32>,
+) -> Result<QueryVector, String> {
+    // Only the collection's own embeddings have a known dimension; a metadata
+    // key holds sparse vectors of any length.
+    let dimension = if *key == Key::Embedding {
+        dimension
+    } else {
+        None
+    };
+
+    match query {
+        SearchQuery::Dense { vector } => {
+            let vector = vector.parse().await?;
+            validate_vector(&vector, dimension)?;
+            Ok(QueryVector::Dense(vector))
+        }
+        SearchQuery::Sparse { indices, values } => {
+            if let Some(i) = values.iter().position(|v| !v.is_finite()) {
+                return Err(format!(
+                    "Sparse vector has a non-finite value at position {}",
+                    i
+                ));
+            }
+            let vector = SparseVector::new(indices, values)
+                .map_err(|_| "Sparse vector indices and values differ in length".to_string())?;
+            vector.validate().map_err(|e| e.to_string())?;
+            Ok(QueryVector::Sparse(vector))
+        }
+        SearchQuery::Text { text } => {
+            let provider =
+                provider.ok_or_else(|| "No embedding provider configured".to_string())?;
+            let vector = provider
+                .embed(&[text])
+                .await
+                .map_err(|e| format!("Error embedding text: {}", e))?
+                .into_iter()
+                .next()
+                .ok_or_else(|| "Embedding provider returned no vector".to_string())?;
+            validate_vector(&vector, dimension)?;
+            Ok(QueryVector::Dense(vector))
+        }
+    }
+}
+
+/// Servers older than the Search API answer its endpoint with "not found"
+/// (or "method not allowed" behind some proxies) rather than a Chroma error.
+pub(crate) fn is_unsupported(error: &ChromaHttpClientError) -> bool {
+    match error {
+        ChromaHttpClientError::ApiError(_, status) => matches!(
+            *status,
+            reqwest::StatusCode::NOT_FOUND
+                | reqwest::StatusCode::METHOD_NOT_ALLOWED
+                | reqwest::StatusCode::NOT_IMPLEMENTED
+        ),
+        ChromaHttpClientError::RequestError(_)
+        | ChromaHttpClientError::CouldNotResolveDatabaseId(_)
+        | ChromaHttpClientError::SerdeError(_)
+        | ChromaHttpClientError::ValidationError(_)
+        | ChromaHttpClientError::InvalidWhere => false,
+    }
+}
@@ -5,4 +5,407 @@ pub struct EmbeddingData {
     pub id: String,
     pub metadata: Map<String, Value>,
     pub document: String,
+    /// Set for multimodal records, e.g. `file:///data/cat.png`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// Set when `document` was cut short; `fetch_document` returns the full text.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub document_truncated: bool,
+}
+
+/// Outcome of `update_record_document`.
+///
+/// `embedding_stale` is set when the document text changed but the stored
+/// vector was kept as-is, so similarity results for this record no longer
+/// reflect its text until it is re-embedded.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct DocumentUpdate {
+    pub id: String,
+    pub reembedded: bool,
+    pub embedding_stale: bool,
+}
+
+/// One record's metadata before and after a bulk patch, for previews.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct MetadataPatchSample {
+    pub id: String,
+    pub before: Map<String, Value>,
+    pub after: Map<String, Value>,
+}
+
+/// Outcome of `patch_metadata_where` and `write_cluster_ids`. In preview mode
+/// `updated` is always 0.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct MetadataPatch {
+    pub matched: usize,
+    pub updated: usize,
+    pub preview: bool,
+    pub samples: Vec<MetadataPatchSample>,
+}
+
+/// Result of `preview_delete_where`: how many records match, a few of their
+/// ids, and the token `delete_records_where` must be called with.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct DeletePreview {
+    pub matched: usize,
+    pub sample_ids: Vec<String>,
+    pub token: String,
+}
+
+/// A filter payload that failed to parse, tagged with the half it came from
+/// so the filter builder can show the message next to the right input.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+#[serde(tag = "field", content = "message", rename_all = "snake_case")]
+pub enum FilterError {
+    Where(String),
+    WhereDocument(String),
+}
+
+impl std::fmt::Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterError::Where(message) => write!(f, "Invalid metadata filter: {}", message),
+            FilterError::WhereDocument(message) => {
+                write!(f, "Invalid document filter: {}", message)
+            }
+        }
+    }
+}
+
+/// A single nearest-neighbour result. `distance` uses the collection's space,
+/// so smaller is closer.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct QueryMatch {
+    pub id: String,
+    pub metadata: Map<String, Value>,
+    pub document: String,
+    pub distance: Option<f32>,
+}
+
+/// A single result of `search`. Only the fields that were selected are set.
+/// With the default summed ranking `score` is a distance, so smaller is closer.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct SearchMatch {
+    pub id: String,
+    pub document: Option<String>,
+    pub metadata: Option<Map<String, Value>>,
+    pub score: Option<f32>,
+}
+
+/// Result of `count_records`. When `capped` is set, at least `count` records
+/// match and the UI shows "`count`+". `cached` counts were not re-run.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub struct RowCount {
+    pub count: u32,
+    pub capped: bool,
+    pub cached: bool,
+}
+
+/// One message of `stream_embeddings`. `offset` is the position of the first
+/// record within the collection (or filter), so chunks can be placed in order.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct RecordChunk {
+    pub offset: usize,
+    pub records: Vec<EmbeddingData>,
+}
+
+/// A value and how many records hold it.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub struct ValueCount {
+    pub value: Value,
+    pub count: usize,
+}
+
+/// What `profile_metadata` observed for one metadata key.
+///
+/// `presence` is the share of scanned records that have the key. `distinct`
+/// is a lower bound when `distinct_capped` is set. `min`/`max` cover numeric
+/// values, and `top_values` the most common strings, including array elements.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct MetadataKeyProfile {
+    pub key: String,
+    pub types: Vec<String>,
+    pub count: usize,
+    pub presence: f64,
+    pub distinct: usize,
+    pub distinct_capped: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub top_values: Vec<ValueCount>,
+}
+
+/// Result of `profile_metadata`. `scanned` is less than `total` when sampled.
+/// Keys are ordered from most to least common.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct MetadataProfile {
+    pub total: u32,
+    pub scanned: usize,
+    pub keys: Vec<MetadataKeyProfile>,
+}
+
+/// Progress of a command that scans every matching record. `total` is `None`
+/// when it is not known up front.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct ScanProgress {
+    pub scanned: usize,
+    pub total: Option<u32>,
+}
+
+/// Result of `facet_counts`. `buckets` holds the most common values; `other`
+/// counts the rest and `missing` the scanned records without the key.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct FacetCounts {
+    pub key: String,
+    pub scanned: usize,
+    pub missing: usize,
+    pub distinct: usize,
+    pub buckets: Vec<ValueCount>,
+    pub other: usize,
+}
+
+/// How `metadata_histogram` reads a key's values. Dates are epoch seconds,
+/// whether stored as numbers or as ISO-8601 strings.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HistogramKind {
+    Numeric,
+    Date,
+}
+
+/// Calendar unit of the bins of a date histogram.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DateUnit {
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+/// One bin of a histogram, covering `start` up to but excluding `end`. The
+/// last bin of a numeric histogram also holds values equal to its `end`.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub struct HistogramBin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Result of `metadata_histogram`. Of the scanned records, `missing` lack the
+/// key, `invalid` hold a value of the wrong kind and `outside` one beyond the
+/// requested range; the rest are counted in `bins`.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Histogram {
+    pub key: String,
+    pub kind: HistogramKind,
+    pub unit: Option<DateUnit>,
+    pub scanned: usize,
+    pub missing: usize,
+    pub invalid: usize,
+    pub outside: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub bins: Vec<HistogramBin>,
+}
+
+/// Records failing one check of `scan_embeddings`. `ids` lists at most the
+/// first 1000 of them.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+pub struct EmbeddingIssue {
+    pub count: usize,
+    pub ids: Vec<String>,
+}
+
+/// Distribution of the L2 norms of the finite, non-zero vectors.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct NormStats {
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+    pub bins: Vec<HistogramBin>,
+}
+
+/// Result of `scan_embeddings`. `dimension` is the most common dimension;
+/// vectors of any other length are reported in `dimension_mismatches`.
+/// `normalized` counts vectors with a norm within 0.001 of 1, and
+/// `looks_normalized` is set when that holds for all of them.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct EmbeddingHealth {
+    pub scanned: usize,
+    pub dimension: Option<usize>,
+    pub dimension_mismatches: EmbeddingIssue,
+    pub non_finite: EmbeddingIssue,
+    pub zero: EmbeddingIssue,
+    pub norms: Option<NormStats>,
+    pub normalized: usize,
+    pub looks_normalized: bool,
+}
+
+/// Records `find_duplicates` found to be copies of each other. `keep` is the
+/// first of them in scan order; `duplicates` are the rest.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    pub keep: String,
+    pub duplicates: Vec<String>,
+}
+
+/// Result of `find_duplicates`, largest group first. `delete_ids` keeps one
+/// record per group and can be passed to `delete_records` as is. `skipped`
+/// counts records that could not be compared: those without a document, or
+/// with a zero vector for near-duplicates.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct DuplicateReport {
+    pub scanned: usize,
+    pub skipped: usize,
+    pub groups: Vec<DuplicateGroup>,
+    pub delete_ids: Vec<String>,
+}
+
+/// One record of `project_embeddings`. `value` is the record's value of the
+/// requested colour key, if any.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct ProjectedPoint {
+    pub id: String,
+    pub coordinates: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+}
+
+/// Result of `project_embeddings`. `explained_variance` is the share of the
+/// variance along each axis for PCA, and `skipped` counts sampled records
+/// whose vector had another dimension than most or non-finite components.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Projection {
+    pub total: u32,
+    pub points: Vec<ProjectedPoint>,
+    pub explained_variance: Option<Vec<f64>>,
+    pub skipped: usize,
+}
+
+/// A record of a cluster and its Euclidean distance to the centroid.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct ClusterMember {
+    pub id: String,
+    pub distance: f32,
+}
+
+/// One cluster of `cluster_embeddings`; its position in `clusters` is the id
+/// `write_cluster_ids` stores.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Cluster {
+    pub size: usize,
+    pub centroid: Vec<f32>,
+    pub nearest: Vec<ClusterMember>,
+}
+
+/// Result of `cluster_embeddings`. `inertia` is the sum of squared distances
+/// of the sampled vectors to their centroids; `converged` is false when the
+/// iteration limit was reached first.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct Clustering {
+    pub total: u32,
+    pub sampled: usize,
+    pub skipped: usize,
+    pub iterations: usize,
+    pub converged: bool,
+    pub inertia: f64,
+    pub clusters: Vec<Cluster>,
+}
+
+/// Result of `compare_embeddings`. Row and column `i` of each matrix belong to
+/// `ids[i]`. `cosine_similarity` is `None` where either vector is zero, and
+/// `l2_distance` is the Euclidean distance rather than Chroma's squared one.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct EmbeddingComparison {
+    pub ids: Vec<String>,
+    pub norms: Vec<f32>,
+    pub cosine_similarity: Vec<Vec<Option<f32>>>,
+    pub l2_distance: Vec<Vec<f32>>,
+    pub inner_product: Vec<Vec<f32>>,
+}
+
+/// Distance function of a collection's vector index, named as in Chroma's
+/// `hnsw:space`.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceSpace {
+    L2,
+    Cosine,
+    Ip,
+}
+
+/// A query of `measure_recall` and the exact neighbours the index missed.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct RecallQuery {
+    pub id: String,
+    pub recall: f64,
+    pub missed: Vec<String>,
+}
+
+/// Query latency in milliseconds, by nearest-rank percentile.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct LatencyStats {
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Result of `measure_recall`. `recall` is the mean share of each query's
+/// exact top `k` the index returned, counting a result that ties the k-th
+/// exact distance as a hit; `overlap` is the same by id alone. `worst` lists
+/// the queries with the lowest recall.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct RecallReport {
+    pub records: usize,
+    pub skipped: usize,
+    pub queries: usize,
+    pub k: usize,
+    pub space: DistanceSpace,
+    pub space_detected: bool,
+    pub recall: f64,
+    pub min_recall: f64,
+    pub overlap: f64,
+    pub perfect: usize,
+    pub latency: LatencyStats,
+    pub worst: Vec<RecallQuery>,
+}
+
+/// How one labelled query of `evaluate_retrieval` fared. `rank` is the
+/// 1-based position of the first expected id among `retrieved`, and `missed`
+/// the expected ids not retrieved at all.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct QueryEvaluation {
+    pub id: String,
+    pub query: Option<String>,
+    pub expected: Vec<String>,
+    pub retrieved: Vec<String>,
+    pub rank: Option<usize>,
+    pub reciprocal_rank: f64,
+    pub ndcg: f64,
+    pub recall: f64,
+    pub missed: Vec<String>,
+}
+
+/// Result of `evaluate_retrieval`, averaged over the queries at cutoff `k`,
+/// along with the settings of the run so saved runs can be told apart.
+/// `provider` never includes an API key.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct RetrievalEvaluation {
+    pub collection: String,
+    pub created_at: String,
+    pub k: usize,
+    pub where_filter: Option<Value>,
+    pub where_document: Option<Value>,
+    pub provider: Option<Value>,
+    pub queries: usize,
+    pub hit_rate: f64,
+    pub mrr: f64,
+    pub ndcg: f64,
+    pub recall: f64,
+    pub cases: Vec<QueryEvaluation>,
 }
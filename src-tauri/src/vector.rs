@@ -0,0 +1,303 @@
+use chroma_types::{decode_base64_embedding, SparseVector};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// How many of a sparse vector's largest weights are listed in its stats.
+const SPARSE_TOP_WEIGHTS: usize = 10;
+
+/// A raw embedding vector as pasted into the app, tagged with its text format.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "format", content = "value", rename_all = "lowercase")]
+pub(crate) enum VectorInput {
+    /// A JSON number array, e.g. `[0.1, 0.2]`.
+    Json(Vec<f32>),
+    /// Comma-separated numbers, e.g. `0.1, 0.2`. Surrounding brackets are allowed.
+    Csv(String),
+    /// Base64 of little-endian `f32` bytes, as Chroma's own binary payloads use.
+    Base64(String),
+    /// Path to a NumPy `.npy` file holding a single float vector.
+    Npy(String),
+}
+
+impl VectorInput {
+    /// Decodes the vector. A `.npy` file is read on the blocking thread pool
+    /// so a slow disk does not stall the async runtime.
+    pub(crate) async fn parse(self) -> Result<Vec<f32>, String> {
+        match self {
+            VectorInput::Json(values) => Ok(values),
+            VectorInput::Csv(text) => parse_csv_vector(&text),
+            VectorInput::Base64(text) => decode_base64_embedding(&text.trim().to_string())
+                .map_err(|e| format!("Invalid base64 vector: {}", e)),
+            VectorInput::Npy(path) => {
+                let read_path = path.clone();
+                let bytes = tauri::async_runtime::spawn_blocking(move || std::fs::read(read_path))
+                    .await
+                    .map_err(|e| format!("Error reading {}: {}", path, e))?
+                    .map_err(|e| format!("Error reading {}: {}", path, e))?;
+                parse_npy_vector(&bytes).map_err(|e| format!("Invalid .npy file {}: {}", path, e))
+            }
+        }
+    }
+}
+
+fn parse_csv_vector(text: &str) -> Result<Vec<f32>, String> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .unwrap_or(text);
+
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    text.split(',')
+        .map(str::trim)
+        .enumerate()
+        .map(|(i, part)| {
+            if part.is_empty() {
+                return Err(format!("Empty value at position {}", i));
+            }
+            part.parse::<f32>()
+                .map_err(|e| format!("Invalid number at position {}: {} ({})", i, part, e))
+        })
+        .collect()
+}
+
+/// Reads a `.npy` (format version 1-3) holding a little-endian `float32` or
+/// `float64` array of shape `(n,)` or `(1, n)`, which is what `np.save` writes
+/// for a single embedding.
+fn parse_npy_vector(bytes: &[u8]) -> Result<Vec<f32>, String> {
+    let rest = bytes
+        .strip_prefix(b"\x93NUMPY")
+        .ok_or_else(|| "missing NUMPY magic string".to_string())?;
+    let (major, rest) = match rest {
+        [major, _minor, rest @ ..] => (*major, rest),
+        _ => return Err("truncated header".to_string()),
+    };
+    let (header_len, rest) = match (major, rest) {
+        (1, [a, b, rest @ ..]) => (u16::from_le_bytes([*a, *b]) as usize, rest),
+        (2 | 3, [a, b, c, d, rest @ ..]) => (u32::from_le_bytes([*a, *b, *c, *d]) as usize, rest),
+        (1..=3, _) => return Err("truncated header".to_string()),
+        _ => return Err(format!("unsupported format version {}", major)),
+    };
+    if rest.len() < header_len {
+        return Err("truncated header".to_string());
+    }
+    let (header, data) = rest.split_at(header_len);
+    let header = String::from_utf8_lossy(header);
+
+    let descr = npy_header_value(&header, "descr")
+        .map(|v| v.trim_matches(|c| c == '\'' || c == '"').to_string())
+        .ok_or_else(|| "header has no descr".to_string())?;
+    if npy_header_value(&header, "fortran_order").is_some_and(|v| v == "True") {
+        return Err("Fortran-ordered arrays are not supported".to_string());
+    }
+    let shape =
+        npy_header_value(&header, "shape").ok_or_else(|| "header has no shape".to_string())?;
+    let dims: Vec<usize> = shape
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| {
+            d.parse::<usize>()
+                .map_err(|_| format!("invalid shape {}", shape))
+        })
+        .collect::<Result<_, _>>()?;
+    let len = match dims.as_slice() {
+        [n] | [1, n] => *n,
+        _ => return Err(format!("expected a single vector, found shape {}", shape)),
+    };
+
+    let values: Vec<f32> = match descr.as_str() {
+        "<f4" => data
+            .chunks_exact(4)
+            .filter_map(|c| c.try_into().ok().map(f32::from_le_bytes))
+            .collect(),
+        "<f8" => data
+            .chunks_exact(8)
+            .filter_map(|c| c.try_into().ok().map(|c| f64::from_le_bytes(c) as f32))
+            .collect(),
+        _ => return Err(format!("unsupported dtype {}, expected <f4 or <f8", descr)),
+    };
+    if values.len() != len {
+        return Err(format!(
+            "shape {} does not match {} value(s) of data",
+            shape,
+            values.len()
+        ));
+    }
+
+    Ok(values)
+}
+
+/// Extracts the raw text of `key`'s value from a `.npy` header, which is a
+/// Python dict literal such as `{'descr': '<f4', 'fortran_order': False, 'shape': (3,), }`.
+fn npy_header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header
+        .find(&format!("'{}'", key))
+        .or_else(|| header.find(&format!("\"{}\"", key)))?;
+    let value = header
+        .get(start + key.len() + 2..)?
+        .trim_start()
+        .strip_prefix(':')?;
+    let value = value.trim_start();
+    let end = if value.starts_with('(') {
+        value.find(')')? + 1
+    } else {
+        value.find([',', '}'])?
+    };
+
+    value.get(..end).map(str::trim)
+}
+
+/// Rejects empty vectors, NaN/Inf components and, when `dimension` is known, a
+/// length that does not match the collection.
+pub(crate) fn validate_vector(vector: &[f32], dimension: Option<u32>) -> Result<(), String> {
+    if vector.is_empty() {
+        return Err("Vector is empty".to_string());
+    }
+
+    if let Some(i) = vector.iter().position(|v| !v.is_finite()) {
+        return Err(format!("Vector has a non-finite value at position {}", i));
+    }
+
+    match dimension {
+        Some(dim) if vector.len() != dim as usize => Err(format!(
+            "Vector has dimension {}, but the collection expects {}",
+            vector.len(),
+            dim
+        )),
+        Some(_) | None => Ok(()),
+    }
+}
+
+pub(crate) fn l2_norm(vector: &[f32]) -> f32 {
+    l2_norm_f64(vector) as f32
+}
+
+/// The squares are summed in `f64`, so large components such as `1e20` do not
+/// overflow to infinity.
+fn l2_norm_f64(vector: &[f32]) -> f64 {
+    vector
+        .iter()
+        .map(|&v| f64::from(v) * f64::from(v))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Scales `vector` to unit length. A zero vector has no direction, and a
+/// vector with NaN/Inf components has no usable length, so both are errors.
+pub(crate) fn normalize(vector: &mut [f32]) -> Result<(), String> {
+    let norm = l2_norm_f64(vector);
+    if norm == 0.0 {
+        return Err("Cannot normalise a zero vector".to_string());
+    }
+    if !norm.is_finite() {
+        return Err("Cannot normalise a vector with non-finite values".to_string());
+    }
+
+    for v in vector.iter_mut() {
+        *v = (f64::from(*v) / norm) as f32;
+    }
+
+    validate_vector(vector, None)
+}
+
+/// Renders a sparse vector stored in metadata as
+/// `{indices, values, tokens?, stats: {nnz, top}}`, where `top` lists the
+/// largest weights by magnitude so BM25/SPLADE features can be checked at a glance.
+pub(crate) fn sparse_vector_to_json(vector: SparseVector) -> Value {
+    let mut top: Vec<(usize, f32)> = vector.values.iter().copied().enumerate().collect();
+    top.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+    top.truncate(SPARSE_TOP_WEIGHTS);
+    let top: Vec<Value> = top
+        .into_iter()
+        .map(|(position, value)| {
+            let mut entry = json!({
+                "index": vector.indices.get(position),
+                "value": value,
+            });
+            if let (Some(token), Some(object)) = (
+                vector.tokens.as_ref().and_then(|t| t.get(position)),
+                entry.as_object_mut(),
+            ) {
+                object.insert("token".to_string(), json!(token));
+            }
+            entry
+        })
+        .collect();
+
+    let mut object = json!({
+        "indices": vector.indices,
+        "values": vector.values,
+        "stats": { "nnz": vector.indices.len(), "top": top },
+    });
+    if let (Some(tokens), Some(map)) = (vector.tokens, object.as_object_mut()) {
+        map.insert("tokens".to_string(), json!(tokens));
+    }
+    object
+}
+
+/// Parses the shape written by `sparse_vector_to_json`. `stats` is derived and
+/// ignored; indices must be strictly ascending, as Chroma requires.
+pub(crate) fn sparse_vector_from_json(object: Map<String, Value>) -> Result<SparseVector, String> {
+    if let Some(key) = object
+        .keys()
+        .find(|k| !matches!(k.as_str(), "indices" | "values" | "tokens" | "stats"))
+    {
+        return Err(format!("unexpected field {}", key));
+    }
+
+    let field = |name: &str| object.get(name).cloned().unwrap_or(Value::Null);
+    let indices: Vec<u32> = serde_json::from_value(field("indices"))
+        .map_err(|_| "indices must be a list of non-negative integers".to_string())?;
+    let values: Vec<f32> = serde_json::from_value(field("values"))
+        .map_err(|_| "values must be a list of numbers".to_string())?;
+    let tokens: Option<Vec<String>> = serde_json::from_value(field("tokens"))
+        .map_err(|_| "tokens must be a list of strings".to_string())?;
+
+    if let Some(i) = values.iter().position(|v| !v.is_finite()) {
+        return Err(format!("non-finite value at position {}", i));
+    }
+
+    let vector = match tokens {
+        Some(tokens) => SparseVector::new_with_tokens(indices, values, tokens),
+        None => SparseVector::new(indices, values),
+    }
+    .map_err(|e| e.to_string())?;
+    vector.validate().map_err(|e| e.to_string())?;
+
+    Ok(vector)
+}
+
+/// Appends `vector` to `out` as little-endian `f32` bytes, the layout of a
+/// JavaScript `Float32Array` on every platform Tauri supports.
+pub(crate) fn write_le_f32(vector: &[f32], out: &mut Vec<u8>) {
+    out.extend(vector.iter().flat_map(|v| v.to_le_bytes()));
+}
+
+/// Drops the records whose vector has NaN/Inf components or a length other
+/// than the most common one, so the rest can be compared. Returns the length
+/// kept and how many records were dropped.
+pub(crate) fn retain_common_dimension<T>(
+    records: &mut Vec<T>,
+    vector: impl Fn(&T) -> &[f32],
+) -> (usize, usize) {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for record in records.iter() {
+        *counts.entry(vector(record).len()).or_default() += 1;
+    }
+    let dimension = counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map_or(0, |(dimension, _)| dimension);
+
+    let before = records.len();
+    records.retain(|record| {
+        let vector = vector(record);
+        vector.len() == dimension && vector.iter().all(|c| c.is_finite())
+    });
+    (dimension, before - records.len())
+}
@@ -47,7 +47,9 @@ describe('Collections', () => {
         ] as unknown as T),
       )
       .with(TauriCommand.FETCH_EMBEDDING, () =>
-        Promise.resolve([1, 2, 3] as unknown as T),
+        Promise.resolve(
+          new Float32Array([1, 2, 3]).buffer as unknown as T,
+        ),
       )
       .otherwise(() => Promise.resolve('unknown command' as unknown as T))
   }
@@ -193,7 +195,9 @@ describe('Collections', () => {
           ] as unknown as T),
         )
         .with(TauriCommand.FETCH_EMBEDDING, () =>
-          Promise.resolve(embedding as unknown as T),
+          Promise.resolve(
+            new Float32Array(embedding).buffer as unknown as T,
+          ),
         )
         .otherwise(() => Promise.resolve('unknown command' as unknown as T))
     }
@@ -307,7 +311,9 @@ describe('Collections', () => {
           ] as unknown as T)
         })
         .with(TauriCommand.FETCH_EMBEDDING, () =>
-          Promise.resolve(embedding as unknown as T),
+          Promise.resolve(
+            new Float32Array(embedding).buffer as unknown as T,
+          ),
         )
         .otherwise(() => Promise.resolve('unknown command' as unknown as T))
     }
@@ -420,7 +426,9 @@ describe('Collections', () => {
             return Promise.resolve(embeddings[args?.offset | 0] as unknown as T)
           })
           .with(TauriCommand.FETCH_EMBEDDING, () =>
-            Promise.resolve(embedding as unknown as T),
+            Promise.resolve(
+              new Float32Array(embedding).buffer as unknown as T,
+            ),
           )
           .otherwise(() => Promise.resolve('unknown command' as unknown as T))
       }
@@ -499,7 +507,9 @@ describe('Collections', () => {
           ] as unknown as T),
         )
         .with(TauriCommand.FETCH_EMBEDDING, () =>
-          Promise.resolve([0.1, 0.2, 0.3] as unknown as T),
+          Promise.resolve(
+            new Float32Array([0.1, 0.2, 0.3]).buffer as unknown as T,
+          ),
         )
         .otherwise(() => Promise.resolve('unknown command' as unknown as T))
     }
@@ -1150,7 +1160,9 @@ describe('Collections', () => {
           ] as unknown as T),
         )
         .with(TauriCommand.FETCH_EMBEDDING, () =>
-          Promise.resolve([1, 2, 3] as unknown as T),
+          Promise.resolve(
+            new Float32Array([1, 2, 3]).buffer as unknown as T,
+          ),
         )
         .otherwise(() => {
           throw new Error(`Unexpected command: ${cmd}`)
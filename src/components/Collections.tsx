@@ -1243,7 +1243,7 @@ const Collections: React.FC<{ display?: BoxProps['display'] }> = ({
       if (embeddingCache.has(id)) {
         return embeddingCache.get(id)!
       }
-      const result = await invokeWrapper<number[]>(
+      const result = await invokeWrapper<ArrayBuffer>(
         TauriCommand.FETCH_EMBEDDING,
         {
           collectionName: currentCollection,
@@ -1251,7 +1251,8 @@ const Collections: React.FC<{ display?: BoxProps['display'] }> = ({
         },
       )
       if (result.type === 'error') throw new Error(result.error)
-      const vec = result.result
+      // The backend sends the vector as raw little-endian float32 bytes.
+      const vec = Array.from(new Float32Array(result.result))
       setEmbeddingCache((prev) => new Map(prev).set(id, vec))
       return vec
     },